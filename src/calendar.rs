@@ -0,0 +1,416 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Nepal Standard Time, UTC+०५:४५, which all dates are in.
+const NEPAL_OFFSET: i64 = (5 * 60 + 45) * 60;
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+pub const MONTHS: [&str; 12] = [
+    "बैशाख",
+    "जेठ",
+    "असार",
+    "साउन",
+    "भदौ",
+    "असोज",
+    "कात्तिक",
+    "मंसिर",
+    "पुस",
+    "माघ",
+    "फागुन",
+    "चैत",
+];
+
+/// From आइतबार, as `बार` in उदाहरण_फलनहरु.अ names them.
+pub const WEEKDAYS: [&str; 7] = [
+    "आइतबार",
+    "सोमबार",
+    "मंगलबार",
+    "बुधबार",
+    "बिहीबार",
+    "शुक्रबार",
+    "शनिबार",
+];
+
+const FIRST_YEAR: i32 = 2000;
+
+/// Days from १९७० जनवरी १ to बैशाख १, २०००, which was १९४३ अप्रिल १४.
+const FIRST_NEW_YEAR: i64 = -9759;
+
+/// Days in each month of the years from `FIRST_YEAR`, as the published
+/// calendars (पात्रो) have them. Bikram Sambat months follow the sun, so
+/// their lengths can only be looked up.
+const MONTH_DAYS: [[u8; 12]; 91] = [
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // २०००
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २००१
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २००२
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २००३
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // २००४
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २००५
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २००६
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २००७
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 29, 31], // २००८
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २००९
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २०१०
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०११
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 30, 30], // २०१२
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०१३
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २०१४
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०१५
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 30, 30], // २०१६
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०१७
+    [31, 32, 31, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २०१८
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // २०१९
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // २०२०
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०२१
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 30], // २०२२
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // २०२३
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // २०२४
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०२५
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०२६
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // २०२७
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०२८
+    [31, 31, 32, 31, 32, 30, 30, 29, 30, 29, 30, 30], // २०२९
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०३०
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // २०३१
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०३२
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २०३३
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०३४
+    [30, 32, 31, 32, 31, 31, 29, 30, 30, 29, 29, 31], // २०३५
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०३६
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २०३७
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०३८
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 30, 30], // २०३९
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०४०
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २०४१
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०४२
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 30, 30], // २०४३
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०४४
+    [31, 32, 31, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २०४५
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०४६
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // २०४७
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०४८
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 30], // २०४९
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // २०५०
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // २०५१
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०५२
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 30], // २०५३
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // २०५४
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०५५
+    [31, 31, 32, 31, 32, 30, 30, 29, 30, 29, 30, 30], // २०५६
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०५७
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // २०५८
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०५९
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २०६०
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०६१
+    [30, 32, 31, 32, 31, 31, 29, 30, 29, 30, 29, 31], // २०६२
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०६३
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २०६४
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०६५
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 29, 31], // २०६६
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०६७
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २०६८
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०६९
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 30, 30], // २०७०
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०७१
+    [31, 32, 31, 32, 31, 30, 30, 29, 30, 29, 30, 30], // २०७२
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // २०७३
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // २०७४
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०७५
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 30], // २०७६
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // २०७७
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // २०७८
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // २०७९
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 30], // २०८०
+    [31, 31, 32, 32, 31, 30, 30, 30, 29, 30, 30, 30], // २०८१
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 30, 30], // २०८२
+    [31, 31, 32, 31, 31, 30, 30, 30, 29, 30, 30, 30], // २०८३
+    [31, 31, 32, 31, 31, 30, 30, 30, 29, 30, 30, 30], // २०८४
+    [31, 32, 31, 32, 30, 31, 30, 30, 29, 30, 30, 30], // २०८५
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 30, 30], // २०८६
+    [31, 31, 32, 31, 31, 31, 30, 30, 29, 30, 30, 30], // २०८७
+    [30, 31, 32, 32, 30, 31, 30, 30, 29, 30, 30, 30], // २०८८
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 30, 30], // २०८९
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 30, 30], // २०९०
+];
+
+const DEFAULT_FORMAT: &str = "%Y %B %d, %A %H:%M:%S";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Date {
+    pub year: i32,
+    /// From १.
+    pub month: u32,
+    pub day: u32,
+}
+
+/// A date, either Bikram Sambat or Gregorian, and a time of day in Nepal.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DateTime {
+    pub date: Date,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// From ० for आइतबार.
+    pub weekday: usize,
+}
+
+/// Days since १९७० जनवरी १ of a Gregorian date.
+fn days_from_gregorian(date: Date) -> i64 {
+    let year = i64::from(date.year) - i64::from(date.month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from((date.month + 9) % 12);
+    let day_of_year = (153 * month + 2) / 5 + i64::from(date.day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+fn gregorian_from_days(days: i64) -> Date {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+
+    Date {
+        year: (year_of_era + era * 400 + i64::from(month <= 2)) as i32,
+        month: month as u32,
+        day: day as u32,
+    }
+}
+
+fn is_gregorian(date: Date) -> bool {
+    let leap = date.year % 4 == 0 && (date.year % 100 != 0 || date.year % 400 == 0);
+    let days = match date.month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+
+    (1..=12).contains(&date.month) && (1..=days).contains(&date.day)
+}
+
+fn month_days(year: i32) -> Option<&'static [u8; 12]> {
+    usize::try_from(year - FIRST_YEAR)
+        .ok()
+        .and_then(|idx| MONTH_DAYS.get(idx))
+}
+
+fn days_from_bs(date: Date) -> Option<i64> {
+    let months = month_days(date.year)?;
+    let month = usize::try_from(date.month).ok()?.checked_sub(1)?;
+    if date.day == 0 || date.day > u32::from(*months.get(month)?) {
+        return None;
+    }
+
+    let years: i64 = MONTH_DAYS[..(date.year - FIRST_YEAR) as usize]
+        .iter()
+        .flatten()
+        .map(|days| i64::from(*days))
+        .sum();
+    let months: i64 = months[..month].iter().map(|days| i64::from(*days)).sum();
+
+    Some(FIRST_NEW_YEAR + years + months + i64::from(date.day) - 1)
+}
+
+fn bs_from_days(days: i64) -> Option<Date> {
+    let mut left = days - FIRST_NEW_YEAR;
+    if left < 0 {
+        return None;
+    }
+
+    for (year, months) in MONTH_DAYS.iter().enumerate() {
+        for (month, month_days) in months.iter().enumerate() {
+            let month_days = i64::from(*month_days);
+            if left < month_days {
+                return Some(Date {
+                    year: FIRST_YEAR + year as i32,
+                    month: month as u32 + 1,
+                    day: left as u32 + 1,
+                });
+            }
+            left -= month_days;
+        }
+    }
+
+    None
+}
+
+pub fn bs_to_gregorian(date: Date) -> Option<Date> {
+    days_from_bs(date).map(gregorian_from_days)
+}
+
+pub fn gregorian_to_bs(date: Date) -> Option<Date> {
+    match is_gregorian(date) {
+        true => bs_from_days(days_from_gregorian(date)),
+        false => None,
+    }
+}
+
+fn digits(n: i64, width: usize) -> String {
+    format!("{:0width$}", n, width = width)
+        .chars()
+        .map(|c| match c.to_digit(10) {
+            Some(d) => char::from_u32('०' as u32 + d).unwrap_or(c),
+            None => c,
+        })
+        .collect()
+}
+
+/// An instant, in seconds since १९७० जनवरी १ UTC.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Samaya(pub i64);
+
+impl Samaya {
+    pub fn now() -> Samaya {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => Samaya(elapsed.as_secs() as i64),
+            Err(before) => Samaya(-(before.duration().as_secs() as i64)),
+        }
+    }
+
+    fn from_days(days: i64, hour: u32, minute: u32, second: u32) -> Option<Samaya> {
+        if hour >= 24 || minute >= 60 || second >= 60 {
+            return None;
+        }
+
+        let seconds = i64::from(hour * 60 * 60 + minute * 60 + second);
+        Some(Samaya(days * SECONDS_PER_DAY + seconds - NEPAL_OFFSET))
+    }
+
+    /// The instant of a Bikram Sambat date and time in Nepal, if the date is
+    /// in the calendar's table.
+    pub fn from_bs(date: Date, hour: u32, minute: u32, second: u32) -> Option<Samaya> {
+        Samaya::from_days(days_from_bs(date)?, hour, minute, second)
+    }
+
+    pub fn from_gregorian(date: Date, hour: u32, minute: u32, second: u32) -> Option<Samaya> {
+        match is_gregorian(date) {
+            true => Samaya::from_days(days_from_gregorian(date), hour, minute, second),
+            false => None,
+        }
+    }
+
+    pub fn checked_add(self, seconds: i64) -> Option<Samaya> {
+        self.0.checked_add(seconds).map(Samaya)
+    }
+
+    fn local(self, to_date: fn(i64) -> Option<Date>) -> Option<DateTime> {
+        let local = self.0.checked_add(NEPAL_OFFSET)?;
+        let days = local.div_euclid(SECONDS_PER_DAY);
+        let seconds = local.rem_euclid(SECONDS_PER_DAY) as u32;
+
+        Some(DateTime {
+            date: to_date(days)?,
+            hour: seconds / (60 * 60),
+            minute: seconds / 60 % 60,
+            second: seconds % 60,
+            weekday: (days + 4).rem_euclid(7) as usize,
+        })
+    }
+
+    /// The Bikram Sambat date and time in Nepal, if the date is in the
+    /// calendar's table.
+    pub fn bs(self) -> Option<DateTime> {
+        self.local(bs_from_days)
+    }
+
+    pub fn gregorian(self) -> Option<DateTime> {
+        self.local(|days| Some(gregorian_from_days(days)))
+    }
+
+    /// Writes the Bikram Sambat date with `%Y` for the year, `%m` and `%B`
+    /// for the month's number and name, `%d` for the day, `%A` for the
+    /// weekday, `%H`, `%M` and `%S` for the time and `%%` for `%`.
+    pub fn format(self, pattern: &str) -> Option<String> {
+        let bs = self.bs()?;
+        let mut out = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => out.push_str(&digits(i64::from(bs.date.year), 4)),
+                Some('m') => out.push_str(&digits(i64::from(bs.date.month), 2)),
+                Some('B') => out.push_str(MONTHS[bs.date.month as usize - 1]),
+                Some('d') => out.push_str(&digits(i64::from(bs.date.day), 2)),
+                Some('A') => out.push_str(WEEKDAYS[bs.weekday]),
+                Some('H') => out.push_str(&digits(i64::from(bs.hour), 2)),
+                Some('M') => out.push_str(&digits(i64::from(bs.minute), 2)),
+                Some('S') => out.push_str(&digits(i64::from(bs.second), 2)),
+                Some('%') => out.push('%'),
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+                None => out.push('%'),
+            }
+        }
+
+        Some(out)
+    }
+}
+
+impl fmt::Display for Samaya {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.format(DEFAULT_FORMAT) {
+            Some(formatted) => write!(f, "समय({})", formatted),
+            None => write!(f, "समय({} सेकेन्ड)", digits(self.0, 0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    #[test]
+    fn test_conversion() {
+        assert_eq!(days_from_gregorian(date(1943, 4, 14)), FIRST_NEW_YEAR);
+        assert_eq!(gregorian_from_days(FIRST_NEW_YEAR), date(1943, 4, 14));
+        assert_eq!(bs_to_gregorian(date(2000, 1, 1)), Some(date(1943, 4, 14)));
+        assert_eq!(bs_to_gregorian(date(2081, 1, 1)), Some(date(2024, 4, 13)));
+        assert_eq!(gregorian_to_bs(date(2025, 4, 14)), Some(date(2082, 1, 1)));
+        assert_eq!(gregorian_to_bs(date(2024, 4, 12)), Some(date(2080, 12, 30)));
+        assert_eq!(bs_to_gregorian(date(2080, 12, 31)), None);
+        assert_eq!(gregorian_to_bs(date(2023, 2, 29)), None);
+        assert_eq!(gregorian_to_bs(date(1943, 4, 13)), None);
+        for days in (FIRST_NEW_YEAR..FIRST_NEW_YEAR + 33000).step_by(97) {
+            let bs = bs_from_days(days).unwrap();
+            assert_eq!(days_from_bs(bs), Some(days));
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        let samaya = Samaya::from_bs(date(2082, 1, 1), 9, 5, 0).unwrap();
+        assert_eq!(samaya.0, 1744600800);
+        assert_eq!(samaya.bs().unwrap().weekday, 1);
+        assert_eq!(samaya.to_string(), "समय(२०८२ बैशाख ०१, सोमबार ०९:०५:००)");
+        assert_eq!(
+            samaya
+                .checked_add(-10 * 60 * 60)
+                .unwrap()
+                .format("%Y-%m-%d %H%%"),
+            Some(String::from("२०८१-१२-३० २३%"))
+        );
+        assert_eq!(
+            Samaya(-1_000_000_000_000).to_string(),
+            "समय(-१०००००००००००० सेकेन्ड)"
+        );
+    }
+}
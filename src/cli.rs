@@ -0,0 +1,416 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::profiler;
+use crate::repl;
+use crate::sandbox::{Capabilities, Limits};
+use crate::tracer;
+
+pub const HELP: &str = "\
+अल्प (alpa) - नेपाली लिस्प
+
+प्रयोग (usage):
+  alpa                              REPL सुरु गर्ने (start the REPL)
+  alpa <फाइल.अ> [तर्कहरु...]          फाइल चलाउने (run a file)
+  alpa run [विकल्प] <फाइल>... [-- तर्कहरु...]
+                                    फाइलहरु क्रमैसँग चलाउने (run files in order)
+  alpa -e <एक्सपर्>                  एक्सपर् बिस्तार गरेर छाप्ने (evaluate and print)
+  alpa - [तर्कहरु...]                stdin बाट प्रोग्राम पढ्ने (read the program from stdin)
+  alpa repl [--no-prelude] [--no-init] [--history <फाइल>] [--history-size <n>]
+                                    REPL सुरु गर्ने (start the REPL)
+  alpa doc [--html] [--out <फोल्डर>] <फाइल.अ>...
+                                    कागजात बनाउने (generate documentation)
+  alpa fmt [--check] [फाइल/फोल्डर...]
+                                    फाइलहरु फर्म्याट गर्ने, `-` भए stdin बाट
+                                    (format files in place, `-` for stdin)
+  alpa lint [फाइल/फोल्डर...]          सामान्य गल्तीहरु खोज्ने (check for common mistakes)
+  alpa test [फाइल/फोल्डर...]          परीक्षणहरु चलाउने (run tests)
+  alpa lsp                          stdio मा भाषा सर्भर चलाउने
+                                    (run the language server over stdio)
+  alpa version                      संस्करण देखाउने (show the version)
+  alpa help                         यो सहायता देखाउने (show this help)
+
+विकल्पहरु (options):
+  -e, --eval <एक्सपर्>    एक्सपर् बिस्तार गरेर नतिजा छाप्ने, धेरै पटक दिन मिल्छ
+                         (evaluate and print, may be repeated)
+  --no-prelude           प्रस्तावना.अ लोड नगर्ने (do not load the prelude)
+  --check                पार्स मात्र गर्ने, नचलाउने (parse only, do not run)
+  --trace                हरेक फलनको प्रयोग stderr मा छाप्ने (trace function
+                         applications to stderr)
+  --trace-fn <नाम>       यो फलन मात्र ट्रेस गर्ने, धेरै पटक दिन मिल्छ
+                         (trace only this function, may be repeated)
+  --trace-limit <n>      ट्रेसका बढीमा n लाइन (at most n trace lines, default 1000)
+  --profile              फलन र स्थान अनुसार प्रयोग र समय stderr मा छाप्ने
+                         (report calls and time per function and location)
+  --profile-out <फाइल>   flamegraph को लागि folded stacks लेख्ने
+                         (write folded stacks for flamegraph tools)
+  --profile-top <n>      रिपोर्टका तालिकामा बढीमा n पङ्क्ति (rows per table,
+                         default 20)
+  --max-steps <n>        बढीमा n पाइला बिस्तार गर्ने (evaluation step limit)
+  --max-depth <n>        फलनहरुको बढीमा n तह (function call depth limit,
+                         default 10000)
+  --max-memory <n>       कोर फलनहरुले बढीमा n बाइट बनाउने (allocation budget
+                         in bytes)
+  --timeout <सेकेन्ड>     यति सेकेन्डपछि रोक्ने (wall-clock timeout)
+  --deny <क्षमता>         import, files वा print बन्द गर्ने, धेरै पटक दिन मिल्छ
+                         (disable a capability, may be repeated)
+  --sandbox              आयात, फाइल र छपाइ सबै बन्द गर्ने (deny all capabilities)
+  -h, --help             यो सहायता देखाउने (show this help)
+  -V, --version          संस्करण देखाउने (show the version)
+
+REPL विकल्पहरु (REPL options):
+  --no-init              init फाइल लोड नगर्ने (do not load the init file)
+  --history <फाइल>       इतिहास फाइल (history file, default
+                         $ALPA_HISTORY or $XDG_DATA_HOME/alpa/history)
+  --history-size <n>     इतिहासमा राख्ने लाइनहरु (history entries to keep, default
+                         $ALPA_HISTORY_SIZE or 1000)
+
+REPL सुरु हुँदा $ALPA_INIT, $XDG_CONFIG_HOME/alpa/init.अ वा ~/.alpaarc मध्ये
+पहिले भेटिएको फाइल लोड हुन्छ।
+(the first of $ALPA_INIT, $XDG_CONFIG_HOME/alpa/init.अ or ~/.alpaarc is loaded
+before the first prompt)
+
+स्क्रिप्टका तर्कहरु `तर्कहरु` नाममा स्ट्रिङको क्यु-एक्सपर्को रुपमा र फाइलको बाटो
+`लिपि` नाममा पाइन्छन्। फाइलको पहिलो लाइन `#!/usr/bin/env alpa` हुन सक्छ।
+(script arguments are bound to `तर्कहरु` as a Q-expression of strings and the
+script path to `लिपि`; a leading `#!` line is ignored)";
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Source {
+    File(String),
+    Stdin,
+    Eval(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RunOptions {
+    pub sources: Vec<Source>,
+    pub args: Vec<String>,
+    pub prelude: bool,
+    pub check: bool,
+    pub trace: Option<tracer::Options>,
+    pub profile: Option<profiler::Options>,
+    pub limits: Limits,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Command {
+    Repl(repl::Options),
+    Run(RunOptions),
+    Doc(Vec<String>),
+    Fmt(Vec<String>),
+    Lint(Vec<String>),
+    Test(Vec<String>),
+    Lsp,
+    Version,
+    Help,
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    match args.first().map(String::as_str) {
+        None => Ok(Command::Repl(repl::Options::default())),
+        Some("help") => Ok(Command::Help),
+        Some("version") => Ok(Command::Version),
+        Some("doc") => Ok(Command::Doc(args[1..].to_vec())),
+        Some("fmt") => Ok(Command::Fmt(args[1..].to_vec())),
+        Some("lint") => Ok(Command::Lint(args[1..].to_vec())),
+        Some("test") => Ok(Command::Test(args[1..].to_vec())),
+        Some("lsp") => Ok(Command::Lsp),
+        Some("repl") => parse_repl(&args[1..]),
+        Some("run") => parse_run(&args[1..], true),
+        Some(_) => parse_run(args, false),
+    }
+}
+
+fn parse_repl(args: &[String]) -> Result<Command, String> {
+    let mut options = repl::Options::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--no-prelude" => options.prelude = false,
+            "--no-init" => options.init = false,
+            "--history" => match args.next() {
+                Some(path) => options.history = Some(PathBuf::from(path)),
+                None => return Err(format!("{} लाई फाइल चाहिन्छ", arg)),
+            },
+            "--history-size" => match args.next().map(|n| n.parse()) {
+                Some(Ok(size)) => options.history_size = Some(size),
+                _ => return Err(format!("{} लाई संख्या चाहिन्छ", arg)),
+            },
+            _ => return Err(format!("repl ले यो विकल्प लिँदैन : {}", arg)),
+        }
+    }
+
+    Ok(Command::Repl(options))
+}
+
+fn parse_run(args: &[String], explicit: bool) -> Result<Command, String> {
+    let mut options = RunOptions {
+        sources: vec![],
+        args: vec![],
+        prelude: true,
+        check: false,
+        trace: None,
+        profile: None,
+        limits: Limits::default(),
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--no-prelude" => options.prelude = false,
+            "--check" => options.check = true,
+            "--trace" => {
+                options.trace.get_or_insert_with(tracer::Options::default);
+            }
+            "--trace-fn" => match args.next() {
+                Some(name) => {
+                    let trace = options.trace.get_or_insert_with(tracer::Options::default);
+                    trace.functions.push(name.clone());
+                }
+                None => return Err(format!("{} लाई फलनको नाम चाहिन्छ", arg)),
+            },
+            "--trace-limit" => match args.next().map(|n| n.parse()) {
+                Some(Ok(limit)) => {
+                    options
+                        .trace
+                        .get_or_insert_with(tracer::Options::default)
+                        .limit = limit;
+                }
+                _ => return Err(format!("{} लाई संख्या चाहिन्छ", arg)),
+            },
+            "--profile" => {
+                options
+                    .profile
+                    .get_or_insert_with(profiler::Options::default);
+            }
+            "--profile-out" => match args.next() {
+                Some(path) => {
+                    let profile = options
+                        .profile
+                        .get_or_insert_with(profiler::Options::default);
+                    profile.folded = Some(PathBuf::from(path));
+                }
+                None => return Err(format!("{} लाई फाइल चाहिन्छ", arg)),
+            },
+            "--profile-top" => match args.next().map(|n| n.parse()) {
+                Some(Ok(top)) => {
+                    options
+                        .profile
+                        .get_or_insert_with(profiler::Options::default)
+                        .top = top;
+                }
+                _ => return Err(format!("{} लाई संख्या चाहिन्छ", arg)),
+            },
+            "--max-steps" => match args.next().map(|n| n.parse()) {
+                Some(Ok(max)) => options.limits.max_steps = Some(max),
+                _ => return Err(format!("{} लाई संख्या चाहिन्छ", arg)),
+            },
+            "--max-depth" => match args.next().map(|n| n.parse()) {
+                Some(Ok(max)) => options.limits.max_depth = Some(max),
+                _ => return Err(format!("{} लाई संख्या चाहिन्छ", arg)),
+            },
+            "--max-memory" => match args.next().map(|n| n.parse()) {
+                Some(Ok(max)) => options.limits.max_memory = Some(max),
+                _ => return Err(format!("{} लाई संख्या चाहिन्छ", arg)),
+            },
+            "--timeout" => match args.next().map(|secs| secs.parse::<f64>()) {
+                Some(Ok(secs)) if secs >= 0.0 && secs.is_finite() => {
+                    options.limits.timeout = Some(Duration::from_secs_f64(secs))
+                }
+                _ => return Err(format!("{} लाई सेकेन्ड चाहिन्छ", arg)),
+            },
+            "--deny" => match args.next().and_then(|name| Capabilities::from_name(name)) {
+                Some(capability) => {
+                    options.limits.capabilities = options.limits.capabilities.without(capability)
+                }
+                None => return Err(format!("{} लाई import, files वा print चाहिन्छ", arg)),
+            },
+            "--sandbox" => options.limits.capabilities = Capabilities::NONE,
+            "-e" | "--eval" => match args.next() {
+                Some(src) => options.sources.push(Source::Eval(src.clone())),
+                None => return Err(format!("{} लाई एक्सपर् चाहिन्छ", arg)),
+            },
+            "--" => {
+                options.args.extend(args.cloned());
+                break;
+            }
+            flag if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(format!("अज्ञात विकल्प : {}", flag))
+            }
+            source => {
+                options.sources.push(match source {
+                    "-" => Source::Stdin,
+                    file => Source::File(file.to_string()),
+                });
+                if !explicit {
+                    options.args.extend(args.cloned());
+                    break;
+                }
+            }
+        }
+    }
+
+    if options.trace.is_some() && options.profile.is_some() {
+        return Err(String::from("ट्रेस र प्रोफाइल सँगै चलाउन मिल्दैन"));
+    }
+
+    match options.sources.is_empty() {
+        true if !options.check && !explicit => Ok(Command::Repl(repl::Options {
+            prelude: options.prelude,
+            ..repl::Options::default()
+        })),
+        true => Err(String::from("चलाउनको लागि कुनै फाइल वा एक्सपर् दिइएन")),
+        false => Ok(Command::Run(options)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(&args.iter().map(|a| a.to_string()).collect::<Vec<String>>())
+    }
+
+    fn run(sources: Vec<Source>, args: &[&str], prelude: bool, check: bool) -> Command {
+        Command::Run(RunOptions {
+            sources,
+            args: args.iter().map(|a| a.to_string()).collect(),
+            prelude,
+            check,
+            trace: None,
+            profile: None,
+            limits: Limits::default(),
+        })
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&[]), Ok(Command::Repl(repl::Options::default())));
+        assert_eq!(
+            parse(&[
+                "repl",
+                "--no-prelude",
+                "--no-init",
+                "--history",
+                "h",
+                "--history-size",
+                "५०"
+            ]),
+            Err(String::from("--history-size लाई संख्या चाहिन्छ"))
+        );
+        assert_eq!(
+            parse(&[
+                "repl",
+                "--no-prelude",
+                "--no-init",
+                "--history",
+                "h",
+                "--history-size",
+                "50"
+            ]),
+            Ok(Command::Repl(repl::Options {
+                prelude: false,
+                init: false,
+                history: Some(PathBuf::from("h")),
+                history_size: Some(50),
+            }))
+        );
+        assert_eq!(parse(&["--version"]), Ok(Command::Version));
+        assert_eq!(parse(&["help"]), Ok(Command::Help));
+        assert_eq!(parse(&["lsp"]), Ok(Command::Lsp));
+        assert_eq!(
+            parse(&["क.अ", "--no-prelude", "x"]),
+            Ok(run(
+                vec![Source::File(String::from("क.अ"))],
+                &["--no-prelude", "x"],
+                true,
+                false
+            ))
+        );
+        assert_eq!(
+            parse(&["run", "--check", "क.अ", "ख.अ", "--", "१", "२"]),
+            Ok(run(
+                vec![
+                    Source::File(String::from("क.अ")),
+                    Source::File(String::from("ख.अ"))
+                ],
+                &["१", "२"],
+                true,
+                true
+            ))
+        );
+        assert_eq!(
+            parse(&["--no-prelude", "-e", "(+ १ २)", "-"]),
+            Ok(run(
+                vec![Source::Eval(String::from("(+ १ २)")), Source::Stdin],
+                &[],
+                false,
+                false
+            ))
+        );
+        assert_eq!(
+            parse(&["-", "क", "-ख"]),
+            Ok(run(vec![Source::Stdin], &["क", "-ख"], true, false))
+        );
+        assert_eq!(
+            parse(&["run", "--trace-fn", "फिब", "--trace-limit", "10", "क.अ"]),
+            Ok(Command::Run(RunOptions {
+                trace: Some(tracer::Options {
+                    functions: vec![String::from("फिब")],
+                    limit: 10,
+                }),
+                ..match run(vec![Source::File(String::from("क.अ"))], &[], true, false) {
+                    Command::Run(options) => options,
+                    _ => unreachable!(),
+                }
+            }))
+        );
+        assert!(parse(&["--trace-limit", "धेरै", "क.अ"]).is_err());
+        assert_eq!(
+            parse(&["--profile-out", "फिब.folded", "क.अ"]),
+            Ok(Command::Run(RunOptions {
+                profile: Some(profiler::Options {
+                    folded: Some(PathBuf::from("फिब.folded")),
+                    top: profiler::DEFAULT_TOP,
+                }),
+                ..match run(vec![Source::File(String::from("क.अ"))], &[], true, false) {
+                    Command::Run(options) => options,
+                    _ => unreachable!(),
+                }
+            }))
+        );
+        assert!(parse(&["--trace", "--profile", "क.अ"]).is_err());
+        assert_eq!(
+            parse(&["--max-depth", "100", "--timeout", "0.5", "--deny", "print", "क.अ"]),
+            Ok(Command::Run(RunOptions {
+                limits: Limits {
+                    max_depth: Some(100),
+                    timeout: Some(Duration::from_millis(500)),
+                    capabilities: Capabilities::ALL.without(Capabilities::PRINT),
+                    ..Limits::default()
+                },
+                ..match run(vec![Source::File(String::from("क.अ"))], &[], true, false) {
+                    Command::Run(options) => options,
+                    _ => unreachable!(),
+                }
+            }))
+        );
+        assert!(parse(&["--deny", "network", "क.अ"]).is_err());
+        assert!(parse(&["--timeout", "-1", "क.अ"]).is_err());
+        assert!(parse(&["-e"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+        assert!(parse(&["run"]).is_err());
+        assert_eq!(
+            parse(&["--no-prelude"]),
+            Ok(Command::Repl(repl::Options {
+                prelude: false,
+                ..repl::Options::default()
+            }))
+        );
+    }
+}
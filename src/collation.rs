@@ -0,0 +1,143 @@
+use std::cmp::Ordering;
+
+/// Vowels in the order a Nepali dictionary lists them. The grammar's `swar`
+/// puts ऋ last, dictionaries put it after ऊ.
+const VOWELS: [char; 11] = ['अ', 'आ', 'इ', 'ई', 'उ', 'ऊ', 'ऋ', 'ए', 'ऐ', 'ओ', 'औ'];
+
+/// Vowel signs, each standing for the vowel after it in `VOWELS`.
+const MAATRAAS: [char; 10] = ['ा', 'ि', 'ी', 'ु', 'ू', 'ृ', 'े', 'ै', 'ो', 'ौ'];
+
+const CONSONANTS: [char; 33] = [
+    'क', 'ख', 'ग', 'घ', 'ङ', 'च', 'छ', 'ज', 'झ', 'ञ', 'ट', 'ठ', 'ड', 'ढ', 'ण', 'त', 'थ', 'द', 'ध',
+    'न', 'प', 'फ', 'ब', 'भ', 'म', 'य', 'र', 'ल', 'व', 'श', 'ष', 'स', 'ह',
+];
+
+const HALANTA: char = '्';
+
+/// Marks that only matter between words that are otherwise the same:
+/// chandrabindu, anusvara, visarga and nukta.
+const MARKS: [char; 4] = ['ँ', 'ं', 'ः', '़'];
+
+/// The weight of one letter, or of the vowel that follows a consonant.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Weight {
+    /// Spaces, punctuation and latin letters, by code point.
+    Other(u32),
+    /// Latin and Devanagari digits alike, by value.
+    Digit(u32),
+    /// A vowel, written on its own or as the vowel of a consonant. A
+    /// consonant with a halanta has the vowel `VOWELS.len()`, after all the
+    /// others, so that conjuncts come after the consonant's last vowel sign.
+    Vowel(usize),
+    Consonant(usize),
+    /// Devanagari that is neither a letter nor a digit, by code point.
+    Sign(u32),
+}
+
+/// A string as the collation sees it. Keys order the same way their strings
+/// do under `compare`, so they can be used with `sort_by_key` or as map keys.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct SortKey {
+    letters: Vec<Weight>,
+    /// Marks, with how many letters came before each one.
+    marks: Vec<(usize, char)>,
+    text: String,
+}
+
+fn position(chars: &[char], c: char) -> Option<usize> {
+    chars.iter().position(|x| *x == c)
+}
+
+pub fn sort_key(text: &str) -> SortKey {
+    let mut letters = vec![];
+    let mut marks = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if let Some(idx) = position(&CONSONANTS, c) {
+            letters.push(Weight::Consonant(idx));
+            while let Some(mark) = chars.next_if(|c| *c == '़') {
+                marks.push((letters.len(), mark));
+            }
+            let vowel = match chars.peek() {
+                Some(&HALANTA) => VOWELS.len(),
+                Some(sign) => position(&MAATRAAS, *sign).map_or(0, |idx| idx + 1),
+                None => 0,
+            };
+            if vowel != 0 {
+                chars.next();
+            }
+            letters.push(Weight::Vowel(vowel));
+        } else if let Some(idx) = position(&VOWELS, c) {
+            letters.push(Weight::Vowel(idx));
+        } else if let Some(idx) = position(&MAATRAAS, c) {
+            letters.push(Weight::Vowel(idx + 1));
+        } else if MARKS.contains(&c) {
+            marks.push((letters.len(), c));
+        } else if let Some(digit) = c.to_digit(10) {
+            letters.push(Weight::Digit(digit));
+        } else if ('०'..='९').contains(&c) {
+            letters.push(Weight::Digit(c as u32 - '०' as u32));
+        } else if ('\u{0900}'..='\u{097f}').contains(&c) {
+            letters.push(Weight::Sign(c as u32));
+        } else {
+            letters.push(Weight::Other(c as u32));
+        }
+    }
+
+    SortKey {
+        letters,
+        marks,
+        text: text.to_string(),
+    }
+}
+
+/// Compares strings in Nepali dictionary order: letters by the alphabet, a
+/// consonant's vowel signs in the order of the vowels, conjuncts after them,
+/// and nasal marks only between words that are otherwise the same. Strings
+/// that are not equal never compare as equal.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    sort_key(a).cmp(&sort_key(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dictionary_order() {
+        let words = [
+            "१२",
+            "अ",
+            "अंक",
+            "आमा",
+            "ऋषि",
+            "ए",
+            "औषधि",
+            "क",
+            "कमल",
+            "कंस",
+            "का",
+            "काँध",
+            "कि",
+            "कौवा",
+            "क्या",
+            "क्षमा",
+            "ख",
+            "त्रिशूल",
+            "ह",
+        ];
+        let mut sorted = words.to_vec();
+        sorted.reverse();
+        sorted.sort_by(|a, b| compare(a, b));
+        assert_eq!(sorted, words);
+
+        assert_eq!(compare("हास", "हाँस"), Ordering::Less);
+        assert_eq!(compare("१", "1"), Ordering::Greater);
+        assert_eq!(compare("कमल", "कमल"), Ordering::Equal);
+        assert_eq!(sort_key("का").cmp(&sort_key("कि")), Ordering::Less);
+    }
+}
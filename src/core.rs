@@ -1,17 +1,64 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fs;
+use std::io;
+use std::iter;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
+use crate::calendar::{Date, DateTime, Samaya, SECONDS_PER_DAY};
+use crate::collation;
+use crate::docs;
 use crate::environment::{self, Env};
 use crate::evaluator;
 use crate::ntypes::Sankhya;
 use crate::parser;
-use crate::types::{Boolean, Error, Expr, ExprsOp, Function, NumOp, QExprOp, QExprsOp, Symbol};
+use crate::sandbox;
+use crate::tracer;
+use crate::types::{
+    BoolOp, Boolean, Error, Expr, ExprsOp, Function, ListOp, LoopOp, NumOp, QExprOp, QExprsOp,
+    RefOp, Reference, Symbol, TestOp, TimeOp, VectorOp,
+};
 
 pub type CoreFn = fn(&mut Env, &[Box<Expr>]) -> Result<Expr, Error>;
 
+thread_local! {
+    /// The files being evaluated, the innermost last.
+    static FILES: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f` as the evaluation of `path`, so that `आयात` inside it looks for
+/// modules next to `path`.
+pub fn within_file<T>(path: &Path, f: impl FnOnce() -> T) -> T {
+    FILES.with(|files| files.borrow_mut().push(path.to_path_buf()));
+    let result = f();
+    FILES.with(|files| files.borrow_mut().pop());
+    result
+}
+
+/// The file being evaluated, if any.
+pub fn current_file() -> Option<PathBuf> {
+    FILES.with(|files| files.borrow().last().cloned())
+}
+
+/// The file `(आयात मोड्युल)` reads : next to the file being evaluated, or in
+/// the working directory at the REPL, for `-e` and for stdin.
+fn import_path(module: &str) -> PathBuf {
+    let filename = format!("{}.अ", module);
+    FILES.with(
+        |files| match files.borrow().last().and_then(|file| file.parent()) {
+            Some(dir) => dir.join(filename),
+            None => PathBuf::from(filename),
+        },
+    )
+}
+
 pub fn exprs_if(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
-    match &exprs[..] {
-        [cond_expr, if_expr, else_expr] => match evaluator::eval(env, &**cond_expr)? {
+    match exprs {
+        [cond_expr, if_expr, else_expr] => match evaluator::eval(env, cond_expr)? {
             Expr::Bool(Boolean(b)) => match (&**if_expr, &**else_expr) {
                 (Expr::QExpr(q1), Expr::QExpr(q2)) => match b {
                     true => evaluator::eval(env, &Expr::SExpr(q1.to_vec())),
@@ -29,125 +76,1434 @@ pub fn exprs_if(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
     }
 }
 
-pub fn exprs_list(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
-    let mut list = vec![];
-    for expr in exprs {
-        list.push(Box::new(evaluator::eval(env, &**expr)?));
+/// Matches `value` against `pattern`, adding the names the pattern binds to
+/// `bindings`. A name that appears twice must match equal values.
+fn match_pattern(
+    pattern: &Expr,
+    value: &Expr,
+    bindings: &mut Vec<(Symbol, Expr)>,
+) -> Result<bool, Error> {
+    match pattern {
+        Expr::Sym(Symbol::Identifier(name)) if name == "_" => Ok(true),
+        Expr::Sym(sym @ Symbol::Identifier(_)) => {
+            match bindings.iter().find(|(bound, _)| bound == sym) {
+                Some((_, bound)) => Ok(bound == value),
+                None => {
+                    bindings.push((sym.clone(), value.clone()));
+                    Ok(true)
+                }
+            }
+        }
+        Expr::Num(_) | Expr::Str(_) | Expr::Bool(_) => Ok(pattern == value),
+        Expr::SExpr(patterns) | Expr::QExpr(patterns) => match value {
+            // Lists nested in a quoted list are still S-expressions.
+            Expr::SExpr(values) | Expr::QExpr(values) => match_items(
+                pattern,
+                &patterns.iter().map(|p| &**p).collect::<Vec<_>>(),
+                &values.iter().map(|v| &**v).collect::<Vec<_>>(),
+                |rest| Expr::QExpr(rest.into_iter().map(Box::new).collect()),
+                bindings,
+            ),
+            _ => Ok(false),
+        },
+        Expr::Vector(patterns) => match value {
+            Expr::Vector(values) => match_items(
+                pattern,
+                &patterns.iter().collect::<Vec<_>>(),
+                &values.iter().collect::<Vec<_>>(),
+                |rest| Expr::Vector(Rc::new(rest)),
+                bindings,
+            ),
+            _ => Ok(false),
+        },
+        _ => Err(Error::InvalidPattern(pattern.clone())),
+    }
+}
+
+/// Matches the items of a list or vector `pattern`, where `ऽ बाँकी` binds the
+/// items left over, gathered by `gather` into a value of the same kind.
+fn match_items(
+    pattern: &Expr,
+    patterns: &[&Expr],
+    values: &[&Expr],
+    gather: fn(Vec<Expr>) -> Expr,
+    bindings: &mut Vec<(Symbol, Expr)>,
+) -> Result<bool, Error> {
+    let rest_marker = Expr::Sym(Symbol::Identifier(String::from("ऽ")));
+    let (fixed, rest) = match patterns.iter().position(|p| **p == rest_marker) {
+        Some(i) => match &patterns[i + 1..] {
+            [rest] if matches!(rest, Expr::Sym(Symbol::Identifier(_))) => {
+                (&patterns[..i], Some(rest))
+            }
+            _ => return Err(Error::InvalidPattern(pattern.clone())),
+        },
+        None => (patterns, None),
+    };
+
+    match rest {
+        Some(_) if values.len() < fixed.len() => return Ok(false),
+        None if values.len() != fixed.len() => return Ok(false),
+        _ => {}
+    }
+    for (pattern, value) in fixed.iter().zip(values.iter()) {
+        if !match_pattern(pattern, value, bindings)? {
+            return Ok(false);
+        }
+    }
+
+    match rest {
+        Some(rest) => {
+            let rest_values = gather(values[fixed.len()..].iter().map(|v| (*v).clone()).collect());
+            match_pattern(rest, &rest_values, bindings)
+        }
+        None => Ok(true),
+    }
+}
+
+pub fn exprs_match(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    let (value, clauses) = match exprs {
+        [value, clauses @ ..] if !clauses.is_empty() => (evaluator::eval(env, value)?, clauses),
+        _ => {
+            return Err(Error::InvalidNumberOfExprsArguments(
+                ExprsOp::Match,
+                exprs.len(),
+            ))
+        }
+    };
+
+    for clause in clauses {
+        let clause = eval_qexpr(env, clause)?;
+        let (pattern, guard, result) = match &clause[..] {
+            [pattern, result] => (pattern, None, result),
+            [pattern, guard, result] => (pattern, Some(guard), result),
+            _ => return Err(Error::InvalidMatchClause(Expr::QExpr(clause.clone()))),
+        };
+
+        let mut bindings = vec![];
+        if !match_pattern(pattern, &value, &mut bindings)? {
+            continue;
+        }
+        let mut scope = environment::new(HashMap::new(), Some(Rc::clone(env)));
+        for (sym, value) in &bindings {
+            environment::bind_local_symbol(&scope, sym, value);
+        }
+        let guarded = match guard {
+            Some(guard) => match evaluator::eval(&mut scope, guard)? {
+                Expr::Bool(Boolean(b)) => b,
+                x => return Err(Error::NotABoolean(x)),
+            },
+            None => true,
+        };
+        if guarded {
+            return evaluator::eval(&mut scope, result);
+        }
+    }
+
+    Err(Error::NoMatch(value))
+}
+
+pub fn exprs_list(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    let mut list = vec![];
+    for expr in exprs {
+        list.push(Box::new(evaluator::eval(env, expr)?));
+    }
+    Ok(Expr::QExpr(list))
+}
+
+pub fn exprs_equal(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr1, expr2] => exprs_compare(env, expr1, expr2),
+        _ => Err(Error::InvalidNumberOfExprsArguments(
+            ExprsOp::Equal,
+            exprs.len(),
+        )),
+    }
+}
+
+fn exprs_compare(env: &mut Env, expr1: &Expr, expr2: &Expr) -> Result<Expr, Error> {
+    match (expr1, expr2) {
+        (Expr::Bool(b1), Expr::Bool(b2)) => Ok(Expr::Bool(Boolean(b1 == b2))),
+        (Expr::Num(n1), Expr::Num(n2)) => Ok(Expr::Bool(Boolean(n1 == n2))),
+        (Expr::Str(s1), Expr::Str(s2)) => Ok(Expr::Bool(Boolean(s1 == s2))),
+        (Expr::Sym(_), Expr::Sym(_)) => Ok(Expr::Bool(Boolean(
+            evaluator::eval(env, expr1)? == evaluator::eval(env, expr2)?,
+        ))),
+        (Expr::QExpr(q1), Expr::QExpr(q2)) => Ok(Expr::Bool(Boolean(q1 == q2))),
+        (Expr::Fun(f1), Expr::Fun(f2)) => Ok(Expr::Bool(Boolean(f1 == f2))),
+        (Expr::Ref(r1), Expr::Ref(r2)) => Ok(Expr::Bool(Boolean(r1 == r2))),
+        (Expr::Vector(v1), Expr::Vector(v2)) => Ok(Expr::Bool(Boolean(v1 == v2))),
+        (Expr::Time(t1), Expr::Time(t2)) => Ok(Expr::Bool(Boolean(t1 == t2))),
+        (Expr::SExpr(_), _) => {
+            let es1 = evaluator::eval(env, expr1)?;
+            exprs_compare(env, &es1, expr2)
+        }
+        (_, Expr::SExpr(_)) => {
+            let es2 = evaluator::eval(env, expr2)?;
+            exprs_compare(env, expr1, &es2)
+        }
+        (Expr::Sym(_), _) => {
+            let es1 = evaluator::eval(env, expr1)?;
+            exprs_compare(env, &es1, expr2)
+        }
+        (_, Expr::Sym(_)) => {
+            let es2 = evaluator::eval(env, expr2)?;
+            exprs_compare(env, expr1, &es2)
+        }
+
+        _ => Ok(Expr::Bool(Boolean(false))),
+    }
+}
+
+pub fn exprs_not_equal(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs_equal(env, exprs)? {
+        Expr::Bool(Boolean(b)) => Ok(Expr::Bool(Boolean(!b))),
+        _ => Ok(Expr::Bool(Boolean(true))),
+    }
+}
+
+pub fn exprs_import(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => match &**expr {
+            Expr::Sym(Symbol::Identifier(target)) => {
+                let path = import_path(target);
+                match fs::read_to_string(&path) {
+                    Ok(contents) => within_file(&path, || parse_and_eval_str(env, &contents)),
+                    Err(_) => Err(Error::ImportError(*expr.clone())),
+                }
+            }
+            x => Err(Error::NotAnIdentifier(x.clone())),
+        },
+        _ => Err(Error::InvalidNumberOfExprsArguments(
+            ExprsOp::Import,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn parse_and_eval_str(env: &mut Env, contents: &str) -> Result<Expr, Error> {
+    match parser::parse(contents) {
+        Err(pe) => Err(Error::ParseError(pe.to_string())),
+        Ok(Expr::SExpr(pexprs)) => eval_all(env, &pexprs),
+        Ok(_) => unreachable!(),
+    }
+}
+
+pub fn parse_file(path: &Path) -> Result<Vec<Box<Expr>>, Error> {
+    let contents = fs::read_to_string(path)
+        .map_err(|_| Error::ImportError(Expr::Str(path.display().to_string())))?;
+
+    match parser::parse(&contents) {
+        Err(pe) => Err(Error::ParseError(
+            pe.with_path(&path.display().to_string()).to_string(),
+        )),
+        Ok(Expr::SExpr(pexprs)) => Ok(pexprs),
+        Ok(_) => unreachable!(),
+    }
+}
+
+pub fn eval_file(env: &mut Env, path: &Path) -> Result<Expr, Error> {
+    let pexprs = parse_file(path)?;
+    within_file(path, || eval_all(env, &pexprs))
+}
+
+fn eval_all(env: &mut Env, pexprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    let mut result = vec![];
+    for pexpr in pexprs.iter() {
+        result.push(Box::new(evaluator::eval(env, pexpr)?));
+    }
+
+    Ok(Expr::QExpr(result))
+}
+
+pub fn exprs_print(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    for expr in exprs {
+        println!("{}", &evaluator::eval(env, expr)?);
+    }
+    Ok(Expr::QExpr(vec![]))
+}
+
+pub fn exprs_error(_env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => match &**expr {
+            Expr::Str(error) => Err(Error::ThrowError(error.to_string())),
+            x => Err(Error::NotAString(x.clone())),
+        },
+        _ => Err(Error::InvalidNumberOfExprsArguments(
+            ExprsOp::Error,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn exprs_exit(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => match evaluator::eval(env, expr)? {
+            Expr::Num(Sankhya(code @ 0..=255)) => Err(Error::Exit(code)),
+            Expr::Num(Sankhya(code)) => Err(Error::InvalidExitCode(code)),
+            x => Err(Error::NotANumber(x)),
+        },
+        _ => Err(Error::InvalidNumberOfExprsArguments(
+            ExprsOp::Exit,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn exprs_help(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => {
+            let name = match &**expr {
+                Expr::Sym(sym) => sym.clone(),
+                _ => Symbol::Identifier(String::from("?")),
+            };
+            match evaluator::eval(env, expr)? {
+                Expr::Fun(fun) => {
+                    println!("{}", docs::render(&name, &fun));
+                    Ok(Expr::QExpr(vec![]))
+                }
+                x => Err(Error::NotAFunction(x)),
+            }
+        }
+        _ => Err(Error::InvalidNumberOfExprsArguments(
+            ExprsOp::Help,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn bool_not(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => match evaluator::eval(env, expr)? {
+            Expr::Bool(Boolean(b)) => Ok(Expr::Bool(Boolean(!b))),
+            x => Err(Error::NotABoolean(x)),
+        },
+        _ => Err(Error::InvalidNumberOfBoolArguments(
+            BoolOp::Not,
+            exprs.len(),
+        )),
+    }
+}
+
+/// Evaluates `exprs` in order until one of them is `decider`, which is then
+/// the result. Any value that is not a boolean is an error.
+fn bools_until(env: &mut Env, exprs: &[Box<Expr>], decider: bool) -> Result<Expr, Error> {
+    for expr in exprs {
+        match evaluator::eval(env, expr)? {
+            Expr::Bool(Boolean(b)) if b == decider => return Ok(Expr::Bool(Boolean(decider))),
+            Expr::Bool(_) => {}
+            x => return Err(Error::NotABoolean(x)),
+        }
+    }
+
+    Ok(Expr::Bool(Boolean(!decider)))
+}
+
+pub fn bools_or(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    bools_until(env, exprs, true)
+}
+
+pub fn bools_and(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    bools_until(env, exprs, false)
+}
+
+#[allow(clippy::vec_box)]
+fn eval_qexpr(env: &mut Env, expr: &Expr) -> Result<Vec<Box<Expr>>, Error> {
+    match evaluator::eval(env, expr)? {
+        Expr::QExpr(qexpr) => Ok(qexpr),
+        x => Err(Error::NotAQExpr(x)),
+    }
+}
+
+fn eval_num(env: &mut Env, expr: &Expr) -> Result<i32, Error> {
+    match evaluator::eval(env, expr)? {
+        Expr::Num(Sankhya(n)) => Ok(n),
+        x => Err(Error::NotANumber(x)),
+    }
+}
+
+/// The single name in `'(नाम)`.
+fn loop_var(env: &mut Env, expr: &Expr) -> Result<Symbol, Error> {
+    let qexpr = eval_qexpr(env, expr)?;
+    match &qexpr[..] {
+        [name] => match &**name {
+            Expr::Sym(sym @ Symbol::Identifier(_)) => Ok(sym.clone()),
+            x => Err(Error::NotAnIdentifier(x.clone())),
+        },
+        _ => Err(Error::InvalidBinding(Expr::QExpr(qexpr))),
+    }
+}
+
+/// Runs one iteration of a loop. `रोक` ends the whole loop with its value,
+/// `जारी` only the iteration.
+fn run_body(env: &mut Env, body: &[Box<Expr>]) -> Result<ControlFlow<Expr, Expr>, Error> {
+    match evaluator::eval(env, &Expr::SExpr(body.to_vec())) {
+        Ok(value) | Err(Error::Continue(value)) => Ok(ControlFlow::Continue(value)),
+        Err(Error::Break(value)) => Ok(ControlFlow::Break(value)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Binds each of `values` to `sym` in a new child of `env` and runs `body`
+/// there. The result is the value of the last iteration, or `'()` if there
+/// were none.
+fn run_over(
+    env: &mut Env,
+    sym: &Symbol,
+    values: impl Iterator<Item = Expr>,
+    body: &[Box<Expr>],
+) -> Result<Expr, Error> {
+    let mut scope = environment::new(HashMap::new(), Some(Rc::clone(env)));
+    let mut last = Expr::QExpr(vec![]);
+    for value in values {
+        environment::bind_local_symbol(&scope, sym, &value);
+        match run_body(&mut scope, body)? {
+            ControlFlow::Continue(value) => last = value,
+            ControlFlow::Break(value) => return Ok(value),
+        }
+    }
+
+    Ok(last)
+}
+
+/// `start`, `start + step`, ... up to but not including `end`.
+fn range(start: i32, end: i32, step: i32) -> Result<impl Iterator<Item = i32>, Error> {
+    match step {
+        0 => Err(Error::ZeroStep),
+        _ => Ok(
+            iter::successors(Some(start), move |n| n.checked_add(step)).take_while(move |n| {
+                match step > 0 {
+                    true => *n < end,
+                    false => *n > end,
+                }
+            }),
+        ),
+    }
+}
+
+/// The start, end and step of `सुरु अन्त्य [पाइला]`.
+fn range_args(env: &mut Env, exprs: &[Box<Expr>]) -> Result<(i32, i32, i32), Error> {
+    let start = eval_num(env, &exprs[0])?;
+    let end = eval_num(env, &exprs[1])?;
+    match exprs.get(2) {
+        Some(step) => Ok((start, end, eval_num(env, step)?)),
+        None => Ok((start, end, 1)),
+    }
+}
+
+pub fn loop_while(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [cond, body] => {
+            let cond = Expr::SExpr(eval_qexpr(env, cond)?);
+            let body = eval_qexpr(env, body)?;
+            let mut last = Expr::QExpr(vec![]);
+            loop {
+                match evaluator::eval(env, &cond)? {
+                    Expr::Bool(Boolean(true)) => {}
+                    Expr::Bool(Boolean(false)) => return Ok(last),
+                    x => return Err(Error::NotABoolean(x)),
+                }
+                match run_body(env, &body)? {
+                    ControlFlow::Continue(value) => last = value,
+                    ControlFlow::Break(value) => return Ok(value),
+                }
+            }
+        }
+        _ => Err(Error::InvalidNumberOfLoopArguments(
+            LoopOp::While,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn loop_for_each(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [var, list, body] => {
+            let sym = loop_var(env, var)?;
+            let list = eval_qexpr(env, list)?;
+            let body = eval_qexpr(env, body)?;
+            run_over(env, &sym, list.into_iter().map(|item| *item), &body)
+        }
+        _ => Err(Error::InvalidNumberOfLoopArguments(
+            LoopOp::ForEach,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn loop_count(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [var, bounds @ .., body] if (2..=3).contains(&bounds.len()) => {
+            let sym = loop_var(env, var)?;
+            let (start, end, step) = range_args(env, bounds)?;
+            let body = eval_qexpr(env, body)?;
+            let values = range(start, end, step)?.map(|n| Expr::Num(Sankhya(n)));
+            run_over(env, &sym, values, &body)
+        }
+        _ => Err(Error::InvalidNumberOfLoopArguments(
+            LoopOp::Count,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn loop_range(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs.len() {
+        2 | 3 => {
+            let (start, end, step) = range_args(env, exprs)?;
+            let items = range(start, end, step)?
+                .map(|n| {
+                    sandbox::tick()?;
+                    Ok(Box::new(Expr::Num(Sankhya(n))))
+                })
+                .collect::<Result<_, Error>>()?;
+            Ok(Expr::QExpr(items))
+        }
+        _ => Err(Error::InvalidNumberOfLoopArguments(
+            LoopOp::Range,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn loop_break(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => Err(Error::Break(evaluator::eval(env, expr)?)),
+        _ => Err(Error::InvalidNumberOfLoopArguments(
+            LoopOp::Break,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn loop_continue(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => Err(Error::Continue(evaluator::eval(env, expr)?)),
+        _ => Err(Error::InvalidNumberOfLoopArguments(
+            LoopOp::Continue,
+            exprs.len(),
+        )),
+    }
+}
+
+fn eval_vector(env: &mut Env, expr: &Expr) -> Result<Rc<Vec<Expr>>, Error> {
+    match evaluator::eval(env, expr)? {
+        Expr::Vector(items) => Ok(items),
+        x => Err(Error::NotAVector(x)),
+    }
+}
+
+/// `expr` as an index into `len` items, or `len` itself when `end`.
+fn eval_index(env: &mut Env, expr: &Expr, len: usize, end: bool) -> Result<usize, Error> {
+    let idx = eval_num(env, expr)?;
+    match usize::try_from(idx) {
+        Ok(i) if i < len || (end && i == len) => Ok(i),
+        _ => Err(Error::IndexOutOfRange(idx, len)),
+    }
+}
+
+pub fn vector_new(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    let items = exprs
+        .iter()
+        .map(|expr| evaluator::eval(env, expr))
+        .collect::<Result<Vec<Expr>, Error>>()?;
+
+    Ok(Expr::Vector(Rc::new(items)))
+}
+
+pub fn vector_get(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [vector, idx] => {
+            let items = eval_vector(env, vector)?;
+            let idx = eval_index(env, idx, items.len(), false)?;
+            Ok(items[idx].clone())
+        }
+        _ => Err(Error::InvalidNumberOfVectorArguments(
+            VectorOp::Get,
+            exprs.len(),
+        )),
+    }
+}
+
+/// A copy of the vector with one item replaced. The original is unchanged.
+pub fn vector_set(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [vector, idx, value] => {
+            let mut items = eval_vector(env, vector)?;
+            let idx = eval_index(env, idx, items.len(), false)?;
+            Rc::make_mut(&mut items)[idx] = evaluator::eval(env, value)?;
+            Ok(Expr::Vector(items))
+        }
+        _ => Err(Error::InvalidNumberOfVectorArguments(
+            VectorOp::Set,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn vector_push(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [vector, values @ ..] if !values.is_empty() => {
+            let mut items = eval_vector(env, vector)?;
+            for value in values {
+                let value = evaluator::eval(env, value)?;
+                Rc::make_mut(&mut items).push(value);
+            }
+            Ok(Expr::Vector(items))
+        }
+        _ => Err(Error::InvalidNumberOfVectorArguments(
+            VectorOp::Push,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn vector_slice(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    let (vector, start, end) = match exprs {
+        [vector, start] => (vector, start, None),
+        [vector, start, end] => (vector, start, Some(end)),
+        _ => {
+            return Err(Error::InvalidNumberOfVectorArguments(
+                VectorOp::Slice,
+                exprs.len(),
+            ))
+        }
+    };
+
+    let items = eval_vector(env, vector)?;
+    let start = eval_index(env, start, items.len(), true)?;
+    let end = match end {
+        Some(end) => eval_index(env, end, items.len(), true)?,
+        None => items.len(),
+    };
+    match start <= end {
+        true => Ok(Expr::Vector(Rc::new(items[start..end].to_vec()))),
+        false => Err(Error::InvalidSlice(start, end)),
+    }
+}
+
+pub fn vector_from_qexpr(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => {
+            let items = eval_qexpr(env, expr)?;
+            Ok(Expr::Vector(Rc::new(
+                items.into_iter().map(|item| *item).collect(),
+            )))
+        }
+        _ => Err(Error::InvalidNumberOfVectorArguments(
+            VectorOp::FromQExpr,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn vector_to_qexpr(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => {
+            let items = eval_vector(env, expr)?;
+            Ok(Expr::QExpr(
+                items.iter().map(|item| Box::new(item.clone())).collect(),
+            ))
+        }
+        _ => Err(Error::InvalidNumberOfVectorArguments(
+            VectorOp::ToQExpr,
+            exprs.len(),
+        )),
+    }
+}
+
+fn eval_time(env: &mut Env, expr: &Expr) -> Result<Samaya, Error> {
+    match evaluator::eval(env, expr)? {
+        Expr::Time(samaya) => Ok(samaya),
+        x => Err(Error::NotATime(x)),
+    }
+}
+
+/// The instant of `(वर्ष महिना गते)` or `(वर्ष महिना गते घण्टा मिनेट सेकेन्ड)`
+/// in Nepal, in the calendar `from` reads the date in.
+fn eval_date(
+    env: &mut Env,
+    exprs: &[Box<Expr>],
+    op: TimeOp,
+    from: fn(Date, u32, u32, u32) -> Option<Samaya>,
+) -> Result<Expr, Error> {
+    if exprs.len() != 3 && exprs.len() != 6 {
+        return Err(Error::InvalidNumberOfTimeArguments(op, exprs.len()));
+    }
+
+    let mut nums = vec![];
+    for expr in exprs {
+        nums.push(eval_num(env, expr)?);
+    }
+    let invalid = || {
+        let date = nums.iter().map(|n| Sankhya(*n).to_string());
+        Error::InvalidDate(date.collect::<Vec<String>>().join(" "))
+    };
+    let mut parts = vec![];
+    for n in &nums[1..] {
+        parts.push(u32::try_from(*n).map_err(|_| invalid())?);
+    }
+    parts.resize(5, 0);
+    let date = Date {
+        year: nums[0],
+        month: parts[0],
+        day: parts[1],
+    };
+
+    match from(date, parts[2], parts[3], parts[4]) {
+        Some(samaya) => Ok(Expr::Time(samaya)),
+        None => Err(invalid()),
+    }
+}
+
+/// `'(वर्ष महिना गते घण्टा मिनेट सेकेन्ड बार)`, with बार from १ for आइतबार.
+fn date_parts(date_time: Option<DateTime>) -> Result<Expr, Error> {
+    let date_time = date_time.ok_or(Error::DateOutOfRange)?;
+    let parts = [
+        date_time.date.year,
+        date_time.date.month as i32,
+        date_time.date.day as i32,
+        date_time.hour as i32,
+        date_time.minute as i32,
+        date_time.second as i32,
+        date_time.weekday as i32 + 1,
+    ];
+
+    Ok(Expr::QExpr(
+        parts
+            .iter()
+            .map(|n| Box::new(Expr::Num(Sankhya(*n))))
+            .collect(),
+    ))
+}
+
+/// The time `अवधि` from now. `(अहिले)` alone is the function itself, like
+/// any other one-item S-expression, so the current time is `(अहिले ०)`.
+pub fn time_now(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [span] => {
+            let seconds = eval_span(env, span)?;
+            match Samaya::now().checked_add(seconds) {
+                Some(samaya) => Ok(Expr::Time(samaya)),
+                None => Err(Error::DateOutOfRange),
+            }
+        }
+        _ => Err(Error::InvalidNumberOfTimeArguments(
+            TimeOp::Now,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn time_from_bs(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    eval_date(env, exprs, TimeOp::FromBs, Samaya::from_bs)
+}
+
+pub fn time_from_gregorian(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    eval_date(env, exprs, TimeOp::FromGregorian, Samaya::from_gregorian)
+}
+
+pub fn time_to_bs(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [samaya] => date_parts(eval_time(env, samaya)?.bs()),
+        _ => Err(Error::InvalidNumberOfTimeArguments(
+            TimeOp::ToBs,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn time_to_gregorian(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [samaya] => date_parts(eval_time(env, samaya)?.gregorian()),
+        _ => Err(Error::InvalidNumberOfTimeArguments(
+            TimeOp::ToGregorian,
+            exprs.len(),
+        )),
+    }
+}
+
+/// A span of time given as seconds, or as `'(दिन सेकेन्ड)` the way
+/// `समयअन्तर` gives it, in seconds.
+fn eval_span(env: &mut Env, expr: &Expr) -> Result<i64, Error> {
+    match evaluator::eval(env, expr)? {
+        Expr::Num(Sankhya(seconds)) => Ok(i64::from(seconds)),
+        Expr::QExpr(parts) => match &parts[..] {
+            [days, seconds] => match (&**days, &**seconds) {
+                (Expr::Num(Sankhya(days)), Expr::Num(Sankhya(seconds))) => {
+                    Ok(i64::from(*days) * SECONDS_PER_DAY + i64::from(*seconds))
+                }
+                (Expr::Num(_), x) | (x, _) => Err(Error::NotANumber(x.clone())),
+            },
+            _ => Err(Error::NotANumber(Expr::QExpr(parts))),
+        },
+        x => Err(Error::NotANumber(x)),
+    }
+}
+
+pub fn time_add(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [samaya, span] => {
+            let samaya = eval_time(env, samaya)?;
+            let seconds = eval_span(env, span)?;
+            match samaya.checked_add(seconds) {
+                Some(samaya) => Ok(Expr::Time(samaya)),
+                None => Err(Error::DateOutOfRange),
+            }
+        }
+        _ => Err(Error::InvalidNumberOfTimeArguments(
+            TimeOp::Add,
+            exprs.len(),
+        )),
+    }
+}
+
+/// The time from the second time to the first as `'(दिन सेकेन्ड)`, whole
+/// days and the seconds left over, both negative when the first is earlier.
+/// Seconds alone would not fit a number for spans of more than 68 years.
+pub fn time_diff(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [later, earlier] => {
+            let span = eval_time(env, later)?.0 - eval_time(env, earlier)?.0;
+            let days = i32::try_from(span / SECONDS_PER_DAY).map_err(|_| Error::DateOutOfRange)?;
+            let seconds = (span % SECONDS_PER_DAY) as i32;
+            Ok(Expr::QExpr(vec![
+                Box::new(Expr::Num(Sankhya(days))),
+                Box::new(Expr::Num(Sankhya(seconds))),
+            ]))
+        }
+        _ => Err(Error::InvalidNumberOfTimeArguments(
+            TimeOp::Diff,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn time_format(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [samaya, pattern] => {
+            let samaya = eval_time(env, samaya)?;
+            match evaluator::eval(env, pattern)? {
+                Expr::Str(pattern) => match samaya.format(&pattern) {
+                    Some(formatted) => Ok(Expr::Str(formatted)),
+                    None => Err(Error::DateOutOfRange),
+                },
+                x => Err(Error::NotAString(x)),
+            }
+        }
+        _ => Err(Error::InvalidNumberOfTimeArguments(
+            TimeOp::Format,
+            exprs.len(),
+        )),
+    }
+}
+
+/// The function to call for `expr`: a name stays a name, so the debugger,
+/// tracer and profiler see calls to it, anything else is evaluated once.
+fn callee(env: &mut Env, expr: &Expr) -> Result<Expr, Error> {
+    match expr {
+        Expr::Sym(sym) => {
+            environment::lookup(env, sym)?;
+            Ok(expr.clone())
+        }
+        _ => evaluator::eval(env, expr),
+    }
+}
+
+/// Applies `fun` to `args`, which are evaluated as arguments again, just as
+/// the prelude's `(फ (प्रथम कहरु))` did.
+#[allow(clippy::vec_box)]
+fn call_with(env: &mut Env, fun: &Expr, args: Vec<Box<Expr>>) -> Result<Expr, Error> {
+    let mut call = vec![Box::new(fun.clone())];
+    call.extend(args);
+    evaluator::eval(env, &Expr::SExpr(call))
+}
+
+fn call_predicate(env: &mut Env, fun: &Expr, item: &Expr) -> Result<bool, Error> {
+    match call_with(env, fun, vec![Box::new(item.clone())])? {
+        Expr::Bool(Boolean(b)) => Ok(b),
+        x => Err(Error::NotABoolean(x)),
+    }
+}
+
+/// The item itself, evaluated the way `प्रथम` evaluates it.
+fn item_value(env: &mut Env, item: &Expr) -> Result<Expr, Error> {
+    evaluator::eval(env, &Expr::SExpr(vec![Box::new(item.clone())]))
+}
+
+/// `expr` as a count of items to take or drop, at most `len`.
+fn eval_count(env: &mut Env, expr: &Expr, len: usize) -> Result<usize, Error> {
+    let n = eval_num(env, expr)?;
+    match usize::try_from(n) {
+        Ok(n) => Ok(n.min(len)),
+        Err(_) => Err(Error::IndexOutOfRange(n, len)),
+    }
+}
+
+/// Where values of each kind go relative to values of other kinds.
+fn kind_rank(expr: &Expr) -> Option<u8> {
+    match expr {
+        Expr::Bool(_) => Some(0),
+        Expr::Num(_) => Some(1),
+        Expr::Str(_) => Some(2),
+        Expr::Sym(_) => Some(3),
+        Expr::SExpr(_) | Expr::QExpr(_) => Some(4),
+        Expr::Vector(_) => Some(5),
+        Expr::Time(_) => Some(6),
+        Expr::Fun(_) | Expr::Ref(_) => None,
+    }
+}
+
+fn compare_lists<'a>(
+    a: impl Iterator<Item = &'a Expr>,
+    b: impl Iterator<Item = &'a Expr>,
+) -> Result<Ordering, Error> {
+    let mut b = b;
+    for x in a {
+        match b.next() {
+            Some(y) => match compare_values(x, y)? {
+                Ordering::Equal => continue,
+                ordering => return Ok(ordering),
+            },
+            None => return Ok(Ordering::Greater),
+        }
+    }
+    match b.next() {
+        Some(_) => Ok(Ordering::Less),
+        None => Ok(Ordering::Equal),
+    }
+}
+
+/// The ordering used by `<`, `>` and `क्रमबद्ध`. Values of different kinds are
+/// ordered by kind, booleans first, and lists compare item by item. Functions
+/// and references have no order.
+fn compare_values(a: &Expr, b: &Expr) -> Result<Ordering, Error> {
+    let not_comparable = || Error::NotComparable(Box::new(a.clone()), Box::new(b.clone()));
+    let (rank_a, rank_b) = match (kind_rank(a), kind_rank(b)) {
+        (Some(rank_a), Some(rank_b)) => (rank_a, rank_b),
+        _ => return Err(not_comparable()),
+    };
+    if rank_a != rank_b {
+        return Ok(rank_a.cmp(&rank_b));
+    }
+
+    match (a, b) {
+        (Expr::Bool(x), Expr::Bool(y)) => Ok(x.0.cmp(&y.0)),
+        (Expr::Num(x), Expr::Num(y)) => Ok(x.0.cmp(&y.0)),
+        (Expr::Str(x), Expr::Str(y)) => Ok(collation::compare(x, y)),
+        (Expr::Sym(x), Expr::Sym(y)) => Ok(collation::compare(&x.to_string(), &y.to_string())),
+        (Expr::SExpr(x), Expr::SExpr(y))
+        | (Expr::SExpr(x), Expr::QExpr(y))
+        | (Expr::QExpr(x), Expr::SExpr(y))
+        | (Expr::QExpr(x), Expr::QExpr(y)) => {
+            compare_lists(x.iter().map(|e| &**e), y.iter().map(|e| &**e))
+        }
+        (Expr::Vector(x), Expr::Vector(y)) => compare_lists(x.iter(), y.iter()),
+        (Expr::Time(x), Expr::Time(y)) => Ok(x.cmp(y)),
+        _ => Err(not_comparable()),
+    }
+}
+
+pub fn list_map(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [fun, list] => {
+            let fun = callee(env, fun)?;
+            let mut mapped = vec![];
+            for item in eval_qexpr(env, list)? {
+                mapped.push(Box::new(call_with(env, &fun, vec![item])?));
+            }
+            Ok(Expr::QExpr(mapped))
+        }
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::Map,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn list_filter(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [fun, list] => {
+            let fun = callee(env, fun)?;
+            let mut kept = vec![];
+            for item in eval_qexpr(env, list)? {
+                if call_predicate(env, &fun, &item)? {
+                    kept.push(item);
+                }
+            }
+            Ok(Expr::QExpr(kept))
+        }
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::Filter,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn list_fold(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [fun, init, list] => {
+            let fun = callee(env, fun)?;
+            let mut acc = evaluator::eval(env, init)?;
+            for item in eval_qexpr(env, list)? {
+                acc = call_with(env, &fun, vec![Box::new(acc), item])?;
+            }
+            Ok(acc)
+        }
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::Fold,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn list_length(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [list] => match evaluator::eval(env, list)? {
+            Expr::QExpr(items) => Ok(Expr::Num(Sankhya(items.len() as i32))),
+            Expr::Vector(items) => Ok(Expr::Num(Sankhya(items.len() as i32))),
+            x => Err(Error::NotAQExpr(x)),
+        },
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::Length,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn list_nth(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [idx, list] => {
+            let idx = evaluator::eval(env, idx)?;
+            let items = eval_qexpr(env, list)?;
+            let idx = eval_index(env, &idx, items.len(), false)?;
+            item_value(env, &items[idx])
+        }
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::Nth,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn list_last(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [list] => match eval_qexpr(env, list)?.last() {
+            Some(item) => item_value(env, item),
+            None => Err(Error::EmptyQExpr(Expr::QExpr(vec![]))),
+        },
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::Last,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn list_take(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [n, list] => {
+            let n = evaluator::eval(env, n)?;
+            let mut items = eval_qexpr(env, list)?;
+            items.truncate(eval_count(env, &n, items.len())?);
+            Ok(Expr::QExpr(items))
+        }
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::Take,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn list_drop(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [n, list] => {
+            let n = evaluator::eval(env, n)?;
+            let mut items = eval_qexpr(env, list)?;
+            items.drain(..eval_count(env, &n, items.len())?);
+            Ok(Expr::QExpr(items))
+        }
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::Drop,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn list_elem(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr, list] => {
+            let value = evaluator::eval(env, expr)?;
+            for item in eval_qexpr(env, list)? {
+                let item = item_value(env, &item)?;
+                if exprs_compare(env, &value, &item)? == Expr::Bool(Boolean(true)) {
+                    return Ok(Expr::Bool(Boolean(true)));
+                }
+            }
+            Ok(Expr::Bool(Boolean(false)))
+        }
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::Elem,
+            exprs.len(),
+        )),
     }
-    Ok(Expr::QExpr(list))
 }
 
-pub fn exprs_equal(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
-    match &exprs[..] {
-        [expr1, expr2] => exprs_compare(env, expr1, expr2),
-        _ => Err(Error::InvalidNumberOfExprsArguments(
-            ExprsOp::Equal,
-            exprs.len(),
-        )),
+/// Orders `a` and `b` with `(तुलना क ख)`, which says whether `क` goes
+/// before `ख`; items it puts before neither of the two are equal.
+fn compare_with(env: &mut Env, fun: &Expr, a: &Expr, b: &Expr) -> Result<Ordering, Error> {
+    let before = |env: &mut Env, x: &Expr, y: &Expr| match call_with(
+        env,
+        fun,
+        vec![Box::new(x.clone()), Box::new(y.clone())],
+    )? {
+        Expr::Bool(Boolean(b)) => Ok(b),
+        x => Err(Error::NotABoolean(x)),
+    };
+    if before(env, a, b)? {
+        Ok(Ordering::Less)
+    } else if before(env, b, a)? {
+        Ok(Ordering::Greater)
+    } else {
+        Ok(Ordering::Equal)
     }
 }
 
-fn exprs_compare(env: &mut Env, expr1: &Expr, expr2: &Expr) -> Result<Expr, Error> {
-    match (expr1, expr2) {
-        (Expr::Bool(b1), Expr::Bool(b2)) => Ok(Expr::Bool(Boolean(b1 == b2))),
-        (Expr::Num(n1), Expr::Num(n2)) => Ok(Expr::Bool(Boolean(n1 == n2))),
-        (Expr::Str(s1), Expr::Str(s2)) => Ok(Expr::Bool(Boolean(s1 == s2))),
-        (Expr::Sym(_), Expr::Sym(_)) => Ok(Expr::Bool(Boolean(
-            evaluator::eval(env, expr1)? == evaluator::eval(env, expr2)?,
-        ))),
-        (Expr::QExpr(q1), Expr::QExpr(q2)) => Ok(Expr::Bool(Boolean(q1 == q2))),
-        (Expr::Fun(f1), Expr::Fun(f2)) => Ok(Expr::Bool(Boolean(f1 == f2))),
-        (Expr::SExpr(_), _) => {
-            let es1 = evaluator::eval(env, expr1)?;
-            exprs_compare(env, &es1, expr2)
-        }
-        (_, Expr::SExpr(_)) => {
-            let es2 = evaluator::eval(env, expr2)?;
-            exprs_compare(env, expr1, &es2)
+/// Sorts the values of the items by their `कुञ्जी` (the value itself when it is
+/// not given or is `नील`), keeping equal ones in their order.
+pub fn list_sort(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    let (list, key, comparator) = match exprs {
+        [list] => (list, None, None),
+        [list, key] => (list, Some(key), None),
+        [list, key, comparator] => (list, Some(key), Some(comparator)),
+        _ => {
+            return Err(Error::InvalidNumberOfListArguments(
+                ListOp::Sort,
+                exprs.len(),
+            ))
         }
-        (Expr::Sym(_), _) => {
-            let es1 = evaluator::eval(env, expr1)?;
-            exprs_compare(env, &es1, expr2)
+    };
+    let items = eval_qexpr(env, list)?;
+    let key = match key {
+        Some(key) => match evaluator::eval(env, key)? {
+            Expr::QExpr(qexpr) if qexpr.is_empty() => None,
+            _ if matches!(**key, Expr::Sym(_)) => Some((**key).clone()),
+            key => Some(key),
+        },
+        None => None,
+    };
+    let comparator = match comparator {
+        Some(comparator) => Some(callee(env, comparator)?),
+        None => None,
+    };
+
+    let mut keyed = vec![];
+    for item in items {
+        let value = item_value(env, &item)?;
+        let sort_key = match &key {
+            Some(key) => call_with(env, key, vec![Box::new(value.clone())])?,
+            None => value.clone(),
+        };
+        keyed.push((sort_key, value));
+    }
+
+    let mut error = None;
+    keyed.sort_by(|(a, _), (b, _)| {
+        if error.is_some() {
+            return Ordering::Equal;
         }
-        (_, Expr::Sym(_)) => {
-            let es2 = evaluator::eval(env, expr2)?;
-            exprs_compare(env, expr1, &es2)
+        let ordering = sandbox::tick().and_then(|_| match &comparator {
+            Some(comparator) => compare_with(env, comparator, a, b),
+            None => compare_values(a, b),
+        });
+        ordering.unwrap_or_else(|e| {
+            error = Some(e);
+            Ordering::Equal
+        })
+    });
+    match error {
+        Some(e) => Err(e),
+        None => Ok(Expr::QExpr(
+            keyed
+                .into_iter()
+                .map(|(_, value)| Box::new(value))
+                .collect(),
+        )),
+    }
+}
+
+pub fn list_reverse(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [list] => {
+            let mut items = eval_qexpr(env, list)?;
+            items.reverse();
+            Ok(Expr::QExpr(items))
         }
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::Reverse,
+            exprs.len(),
+        )),
+    }
+}
 
-        _ => Ok(Expr::Bool(Boolean(false))),
+/// Pairs up the items of the lists, stopping at the end of the shortest.
+pub fn list_zip(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    if exprs.len() < 2 {
+        return Err(Error::InvalidNumberOfListArguments(
+            ListOp::Zip,
+            exprs.len(),
+        ));
     }
+
+    let lists = exprs
+        .iter()
+        .map(|expr| eval_qexpr(env, expr))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let len = lists.iter().map(Vec::len).min().unwrap_or(0);
+    let tuples = (0..len)
+        .map(|i| {
+            sandbox::tick()?;
+            Ok(Box::new(Expr::QExpr(
+                lists.iter().map(|list| list[i].clone()).collect(),
+            )))
+        })
+        .collect::<Result<_, Error>>()?;
+
+    Ok(Expr::QExpr(tuples))
 }
 
-pub fn exprs_not_equal(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
-    match exprs_equal(env, exprs)? {
-        Expr::Bool(Boolean(b)) => Ok(Expr::Bool(Boolean(!b))),
-        _ => Ok(Expr::Bool(Boolean(true))),
+pub fn list_enumerate(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [list] => {
+            let pairs = eval_qexpr(env, list)?
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    sandbox::tick()?;
+                    Ok(Box::new(Expr::QExpr(vec![
+                        Box::new(Expr::Num(Sankhya(i as i32))),
+                        item,
+                    ])))
+                })
+                .collect::<Result<_, Error>>()?;
+            Ok(Expr::QExpr(pairs))
+        }
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::Enumerate,
+            exprs.len(),
+        )),
     }
 }
 
-pub fn exprs_import(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
-    match &exprs[..] {
-        [expr] => match &**expr {
-            Expr::Sym(Symbol::Identifier(target)) => {
-                let extension = "अ";
-                let filename = format!("{}.{}", target, extension);
-                match fs::read_to_string(filename) {
-                    Ok(contents) => parse_and_eval_str(env, &contents),
-                    Err(_) => Err(Error::ImportError(*expr.clone())),
+/// Whether `फ` returns `decider` for some item, stopping at the first one.
+fn list_until(
+    env: &mut Env,
+    exprs: &[Box<Expr>],
+    op: ListOp,
+    decider: bool,
+) -> Result<Expr, Error> {
+    match exprs {
+        [fun, list] => {
+            let fun = callee(env, fun)?;
+            for item in eval_qexpr(env, list)? {
+                if call_predicate(env, &fun, &item)? == decider {
+                    return Ok(Expr::Bool(Boolean(decider)));
                 }
             }
-            x => Err(Error::NotAnIdentifier(x.clone())),
-        },
-        _ => Err(Error::InvalidNumberOfExprsArguments(
-            ExprsOp::Import,
+            Ok(Expr::Bool(Boolean(!decider)))
+        }
+        _ => Err(Error::InvalidNumberOfListArguments(op, exprs.len())),
+    }
+}
+
+pub fn list_any(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    list_until(env, exprs, ListOp::Any, true)
+}
+
+pub fn list_all(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    list_until(env, exprs, ListOp::All, false)
+}
+
+pub fn list_flat_map(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [fun, list] => {
+            let fun = callee(env, fun)?;
+            let mut mapped = vec![];
+            for item in eval_qexpr(env, list)? {
+                match call_with(env, &fun, vec![item])? {
+                    Expr::QExpr(items) => mapped.extend(items),
+                    x => return Err(Error::NotAQExpr(x)),
+                }
+            }
+            Ok(Expr::QExpr(mapped))
+        }
+        _ => Err(Error::InvalidNumberOfListArguments(
+            ListOp::FlatMap,
             exprs.len(),
         )),
     }
 }
 
-pub fn parse_and_eval_str(env: &mut Env, contents: &str) -> Result<Expr, Error> {
-    match parser::parse(contents) {
-        Err(pe) => {
-            eprintln!("Could not parse :\n{:?}", pe);
-            Err(Error::ParseError(String::from(contents)))
-        }
-        Ok(Expr::SExpr(pexprs)) => {
-            // println!("Parsed : {:?}", &pexprs);
+fn eval_ref(env: &mut Env, expr: &Expr) -> Result<Reference, Error> {
+    match evaluator::eval(env, expr)? {
+        Expr::Ref(reference) => Ok(reference),
+        x => Err(Error::NotAReference(x)),
+    }
+}
+
+pub fn ref_new(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => Ok(Expr::Ref(Reference::new(evaluator::eval(env, expr)?))),
+        _ => Err(Error::InvalidNumberOfRefArguments(RefOp::New, exprs.len())),
+    }
+}
 
-	    let mut result = vec![];
+pub fn ref_get(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => Ok(eval_ref(env, expr)?.0.borrow().clone()),
+        _ => Err(Error::InvalidNumberOfRefArguments(RefOp::Get, exprs.len())),
+    }
+}
+
+pub fn ref_set(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [reference, expr] => {
+            let reference = eval_ref(env, reference)?;
+            let value = evaluator::eval(env, expr)?;
+            *reference.0.borrow_mut() = value.clone();
+            Ok(value)
+        }
+        _ => Err(Error::InvalidNumberOfRefArguments(RefOp::Set, exprs.len())),
+    }
+}
 
-            for pexpr in pexprs.iter() {
-                let val = evaluator::eval(env, pexpr)?;
-		result.push(Box::new(val));
-                // println!("{}", &val);
+/// Replaces the value with `(फ मान तर्क ...)` and returns the new value. The
+/// cell is not borrowed while `फ` runs, so `फ` may read it too.
+pub fn ref_swap(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [reference, fun, args @ ..] => {
+            let reference = eval_ref(env, reference)?;
+            let mut call = vec![Box::new(evaluator::eval(env, fun)?)];
+            call.push(Box::new(reference.0.borrow().clone()));
+            for arg in args {
+                call.push(Box::new(evaluator::eval(env, arg)?));
             }
 
-            Ok(Expr::QExpr(result))
+            let value = evaluator::eval(env, &Expr::SExpr(call))?;
+            *reference.0.borrow_mut() = value.clone();
+            Ok(value)
         }
-        Ok(_) => unreachable!(),
+        _ => Err(Error::InvalidNumberOfRefArguments(RefOp::Swap, exprs.len())),
     }
 }
 
-pub fn exprs_print(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
-    for expr in exprs {
-        println!("{}", &evaluator::eval(env, &**expr)?);
+pub fn test_assert(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => match evaluator::eval(env, expr)? {
+            Expr::Bool(Boolean(true)) => Ok(Expr::Bool(Boolean(true))),
+            Expr::Bool(Boolean(false)) => Err(Error::AssertionFailed(*expr.clone())),
+            x => Err(Error::NotABoolean(x)),
+        },
+        _ => Err(Error::InvalidNumberOfTestArguments(
+            TestOp::Assert,
+            exprs.len(),
+        )),
     }
-    Ok(Expr::QExpr(vec![]))
 }
 
-pub fn exprs_error(_env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
-    match &exprs[..] {
-        [expr] => match &**expr {
-            Expr::Str(error) => Err(Error::ThrowError(error.to_string())),
-            x => Err(Error::NotAString(x.clone())),
+pub fn test_assert_equal(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expected, actual] => {
+            let expected = evaluator::eval(env, expected)?;
+            let actual = evaluator::eval(env, actual)?;
+            match exprs_compare(env, &expected, &actual)? {
+                Expr::Bool(Boolean(true)) => Ok(Expr::Bool(Boolean(true))),
+                _ => Err(Error::AssertEqualFailed(
+                    Box::new(expected),
+                    Box::new(actual),
+                )),
+            }
+        }
+        _ => Err(Error::InvalidNumberOfTestArguments(
+            TestOp::AssertEqual,
+            exprs.len(),
+        )),
+    }
+}
+
+pub fn test_assert_throws(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    let (qexpr, message) = match exprs {
+        [qexpr] => (qexpr, None),
+        [qexpr, message] => match evaluator::eval(env, message)? {
+            Expr::Str(message) => (qexpr, Some(message)),
+            x => return Err(Error::NotAString(x)),
         },
-        _ => Err(Error::InvalidNumberOfExprsArguments(
-            ExprsOp::Error,
+        _ => {
+            return Err(Error::InvalidNumberOfTestArguments(
+                TestOp::AssertThrows,
+                exprs.len(),
+            ))
+        }
+    };
+
+    let body = match evaluator::eval(env, qexpr)? {
+        Expr::QExpr(body) => body,
+        x => return Err(Error::NotAQExpr(x)),
+    };
+
+    match (evaluator::eval(env, &Expr::SExpr(body)), message) {
+        (Ok(value), _) => Err(Error::AssertThrowsFailed(value)),
+        (
+            Err(
+                e @ (Error::Exit(_)
+                | Error::Break(_)
+                | Error::Continue(_)
+                | Error::DepthLimitExceeded(_)
+                | Error::StepLimitExceeded(_)
+                | Error::Timeout(_)
+                | Error::MemoryLimitExceeded(_)
+                | Error::Interrupted
+                | Error::NotPermitted(_)),
+            ),
+            _,
+        ) => Err(e),
+        (Err(e), Some(message)) if e.to_string() != message => Err(Error::AssertEqualFailed(
+            Box::new(Expr::Str(message)),
+            Box::new(Expr::Str(e.to_string())),
+        )),
+        (Err(_), _) => Ok(Expr::Bool(Boolean(true))),
+    }
+}
+
+pub fn test_define(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [name, bodies @ ..] => match evaluator::eval(env, name)? {
+            Expr::Str(name) => {
+                run_test_bodies(env, bodies)?;
+                println!("परीक्षण सफल : {}", name);
+                Ok(Expr::Bool(Boolean(true)))
+            }
+            x => Err(Error::NotAString(x)),
+        },
+        _ => Err(Error::InvalidNumberOfTestArguments(
+            TestOp::Test,
             exprs.len(),
         )),
     }
 }
 
+pub fn run_test_bodies(env: &Env, bodies: &[Box<Expr>]) -> Result<Expr, Error> {
+    let mut t_env = environment::new(HashMap::new(), Some(Rc::clone(env)));
+
+    let mut result = Expr::QExpr(vec![]);
+    for body in bodies {
+        result = match evaluator::eval(&mut t_env, body)? {
+            Expr::QExpr(qexpr) => evaluator::eval(&mut t_env, &Expr::SExpr(qexpr))?,
+            x => return Err(Error::NotAQExpr(x)),
+        };
+    }
+
+    Ok(result)
+}
+
 macro_rules! nums_fn {
     ($fn_name:ident, $op:expr, $x:ident, $y:ident, $x_y_body:block) => {
         pub fn $fn_name(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
@@ -186,21 +1542,37 @@ nums_fn!(nums_divide, NumOp::Divide, x, y, {
     }
 });
 
-nums_fn!(nums_gt, NumOp::GreaterThan, x, y, {
-    Ok(Expr::Bool(Boolean(x.0 > y.0)))
-});
+fn nums_order(
+    env: &mut Env,
+    exprs: &[Box<Expr>],
+    op: NumOp,
+    accept: fn(Ordering) -> bool,
+) -> Result<Expr, Error> {
+    match exprs {
+        [x, y] => {
+            let x = evaluator::eval(env, x)?;
+            let y = evaluator::eval(env, y)?;
+            Ok(Expr::Bool(Boolean(accept(compare_values(&x, &y)?))))
+        }
+        _ => Err(Error::InvalidNumberOfNumArguments(op, exprs.len())),
+    }
+}
 
-nums_fn!(nums_gte, NumOp::GreaterThanOrEqual, x, y, {
-    Ok(Expr::Bool(Boolean(x.0 >= y.0)))
-});
+pub fn nums_gt(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    nums_order(env, exprs, NumOp::GreaterThan, Ordering::is_gt)
+}
 
-nums_fn!(nums_lt, NumOp::LessThan, x, y, {
-    Ok(Expr::Bool(Boolean(x.0 < y.0)))
-});
+pub fn nums_gte(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    nums_order(env, exprs, NumOp::GreaterThanOrEqual, Ordering::is_ge)
+}
 
-nums_fn!(nums_lte, NumOp::LessThanOrEqual, x, y, {
-    Ok(Expr::Bool(Boolean(x.0 <= y.0)))
-});
+pub fn nums_lt(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    nums_order(env, exprs, NumOp::LessThan, Ordering::is_lt)
+}
+
+pub fn nums_lte(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    nums_order(env, exprs, NumOp::LessThanOrEqual, Ordering::is_le)
+}
 
 macro_rules! qexpr_fn {
     ($fn_name:ident, $op:expr, $env:ident, $qexpr:ident, $qexpr_body:block) => {
@@ -230,17 +1602,55 @@ qexpr_fn!(qexpr_tail, QExprOp::Tail, env, qexpr, {
     }
 });
 
-qexpr_fn!(qexpr_len, QExprOp::Len, env, qexpr, {
-    Ok(Expr::Num(Sankhya(qexpr.len() as i32)))
-});
+pub fn qexpr_len(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [expr] => match evaluator::eval(env, expr)? {
+            Expr::QExpr(qexpr) => Ok(Expr::Num(Sankhya(qexpr.len() as i32))),
+            Expr::Vector(items) => Ok(Expr::Num(Sankhya(items.len() as i32))),
+            x => Err(Error::NotAQExpr(x)),
+        },
+        _ => Err(Error::InvalidNumberOfQExprArguments(
+            QExprOp::Len,
+            exprs.len(),
+        )),
+    }
+}
 
 qexpr_fn!(qexpr_eval, QExprOp::Eval, env, qexpr, {
     evaluator::eval(env, &Expr::SExpr(qexpr.to_vec()))
 });
 
+pub fn qexpr_trace(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    let (body, functions) = match exprs {
+        [body] => (body, vec![]),
+        [body, names] => match evaluator::eval(env, names)? {
+            Expr::QExpr(names) => (body, names.iter().map(|name| name.to_string()).collect()),
+            x => return Err(Error::NotAQExpr(x)),
+        },
+        _ => {
+            return Err(Error::InvalidNumberOfQExprArguments(
+                QExprOp::Trace,
+                exprs.len(),
+            ))
+        }
+    };
+
+    match evaluator::eval(env, body)? {
+        Expr::QExpr(qexpr) => {
+            let options = tracer::Options {
+                functions,
+                ..tracer::Options::default()
+            };
+            let tracer = tracer::Tracer::new(options, Box::new(io::stderr()));
+            tracer::eval(tracer, env, &Expr::SExpr(qexpr))
+        }
+        x => Err(Error::NotAQExpr(x)),
+    }
+}
+
 pub fn qexprs_cons(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
-    match &exprs[..] {
-        [pref_expr, expr] => match evaluator::eval(env, &**expr)? {
+    match exprs {
+        [pref_expr, expr] => match evaluator::eval(env, expr)? {
             Expr::QExpr(_) => {
                 let first = Box::new(Expr::QExpr(vec![pref_expr.clone()]));
                 qexprs_join(env, &[first, expr.clone()])
@@ -255,13 +1665,13 @@ pub fn qexprs_cons(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
 }
 
 pub fn qexprs_join(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
-    match &exprs[..] {
+    match exprs {
         [] => Err(Error::InvalidNumberOfQExprsArguments(
             QExprsOp::Join,
             exprs.len(),
         )),
-        _ => exprs.iter().fold(Ok(Expr::QExpr(vec![])), |a, b| {
-            match (a?, evaluator::eval(env, b)?) {
+        _ => exprs.iter().try_fold(Expr::QExpr(vec![]), |a, b| {
+            match (a, evaluator::eval(env, b)?) {
                 (Expr::QExpr(mut x), Expr::QExpr(y)) => {
                     x.extend(y);
                     Ok(Expr::QExpr(x))
@@ -296,7 +1706,7 @@ macro_rules! qexprs_assign_fn {
 				None => Ok(Expr::SExpr(vec![]))
 			    }
                         }
-                        false => Err(Error::UnEqualDefList(*first.clone(), rest.to_vec())),
+                        false => Err(Error::UnEqualDefList(first.clone(), rest.to_vec())),
                     },
                     _ => Err(Error::NotAQExpr(*first.clone())),
                 },
@@ -318,32 +1728,87 @@ qexprs_assign_fn!(qexprs_put, env, sym, expr, {
     environment::bind_local_symbol(env, sym, &expr);
 });
 
-pub fn qexprs_lambda(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
-    match &exprs[..] {
-        [q_syms, q_body] => match (
-            evaluator::eval(env, &**q_syms)?,
-            evaluator::eval(env, &**q_body)?,
-        ) {
-            (Expr::QExpr(qexpr), Expr::QExpr(body)) => {
-                let mut sym_exprs = vec![];
-                let mut non_sym_exprs = vec![];
-
-                qexpr.iter().for_each(|q| match &**q {
-                    Expr::Sym(sym) => sym_exprs.push(sym.clone()),
-                    x => non_sym_exprs.push(x.clone()),
-                });
-
-                match non_sym_exprs.first() {
-                    Some(first) => Err(Error::NotASymbol(first.clone())),
-                    None => Ok(Expr::Fun(Function::Lambda(
-                        sym_exprs,
-                        Box::new(Expr::QExpr(body.clone())),
-                        HashMap::new(),
-                    ))),
+/// The names and value expressions of `'((नाम मान) ...)`.
+fn let_bindings(env: &mut Env, expr: &Expr) -> Result<Vec<(Symbol, Box<Expr>)>, Error> {
+    match evaluator::eval(env, expr)? {
+        Expr::QExpr(pairs) => pairs
+            .iter()
+            .map(|pair| match &**pair {
+                Expr::SExpr(items) | Expr::QExpr(items) => match &items[..] {
+                    [name, value] => match &**name {
+                        Expr::Sym(sym @ Symbol::Identifier(_)) => Ok((sym.clone(), value.clone())),
+                        x => Err(Error::NotAnIdentifier(x.clone())),
+                    },
+                    _ => Err(Error::InvalidBinding(*pair.clone())),
+                },
+                x => Err(Error::InvalidBinding(x.clone())),
+            })
+            .collect(),
+        x => Err(Error::NotAQExpr(x)),
+    }
+}
+
+/// Binds the pairs in a new child of `env` and evaluates the body there.
+/// Values are evaluated in `env`, or in the new scope one after another
+/// when `recursive`, so they can refer to each other.
+fn eval_let(
+    env: &mut Env,
+    exprs: &[Box<Expr>],
+    op: QExprsOp,
+    recursive: bool,
+) -> Result<Expr, Error> {
+    match exprs {
+        [bindings, body] => {
+            let bindings = let_bindings(env, bindings)?;
+            let mut scope = environment::new(HashMap::new(), Some(Rc::clone(env)));
+            for (sym, value) in bindings {
+                let value = match recursive {
+                    true => evaluator::eval(&mut scope, &value)?,
+                    false => evaluator::eval(env, &value)?,
+                };
+                environment::bind_local_symbol(&scope, &sym, &value);
+            }
+
+            match evaluator::eval(env, body)? {
+                Expr::QExpr(body) => evaluator::eval(&mut scope, &Expr::SExpr(body))
+                    .map(|value| close_over(value, &scope)),
+                x => Err(Error::NotAQExpr(x)),
+            }
+        }
+        _ => Err(Error::InvalidNumberOfQExprsArguments(op, exprs.len())),
+    }
+}
+
+/// A lambda that leaves the scope it was made in keeps that scope's
+/// bindings, as it can no longer find them by the time it is called.
+fn close_over(value: Expr, scope: &Env) -> Expr {
+    match value {
+        Expr::Fun(Function::Lambda(formals, body, mut hmap, doc)) => {
+            for (sym, value) in environment::bindings(scope) {
+                if !formals.contains(&sym) {
+                    hmap.entry(sym).or_insert(value);
                 }
             }
-            (Expr::QExpr(_), expr) => Err(Error::NotAQExpr(expr.clone())),
-            (expr, _) => Err(Error::NotAQExpr(expr.clone())),
+            Expr::Fun(Function::Lambda(formals, body, hmap, doc))
+        }
+        value => value,
+    }
+}
+
+pub fn qexprs_let(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    eval_let(env, exprs, QExprsOp::Let, false)
+}
+
+pub fn qexprs_letrec(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    eval_let(env, exprs, QExprsOp::LetRec, true)
+}
+
+pub fn qexprs_lambda(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    match exprs {
+        [q_syms, q_body] => make_lambda(env, q_syms, None, q_body),
+        [q_syms, doc, q_body] => match evaluator::eval(env, doc)? {
+            Expr::Str(doc) => make_lambda(env, q_syms, Some(doc), q_body),
+            x => Err(Error::NotAString(x)),
         },
         _ => Err(Error::InvalidNumberOfQExprsArguments(
             QExprsOp::Lambda,
@@ -352,7 +1817,616 @@ pub fn qexprs_lambda(env: &mut Env, exprs: &[Box<Expr>]) -> Result<Expr, Error>
     }
 }
 
+fn make_lambda(
+    env: &mut Env,
+    q_syms: &Expr,
+    doc: Option<String>,
+    q_body: &Expr,
+) -> Result<Expr, Error> {
+    match (evaluator::eval(env, q_syms)?, evaluator::eval(env, q_body)?) {
+        (Expr::QExpr(qexpr), Expr::QExpr(body)) => {
+            let mut sym_exprs = vec![];
+            let mut non_sym_exprs = vec![];
+
+            qexpr.iter().for_each(|q| match &**q {
+                Expr::Sym(sym) => sym_exprs.push(sym.clone()),
+                x => non_sym_exprs.push(x.clone()),
+            });
+
+            match non_sym_exprs.first() {
+                Some(first) => Err(Error::NotASymbol(first.clone())),
+                None => Ok(Expr::Fun(Function::Lambda(
+                    sym_exprs,
+                    Box::new(Expr::QExpr(body)),
+                    HashMap::new(),
+                    doc,
+                ))),
+            }
+        }
+        (Expr::QExpr(_), expr) => Err(Error::NotAQExpr(expr)),
+        (expr, _) => Err(Error::NotAQExpr(expr)),
+    }
+}
+
 pub fn sexpr_printenv(env: &mut Env, _exprs: &[Box<Expr>]) -> Result<Expr, Error> {
     println!("{:#?}", env);
     Ok(Expr::QExpr(vec![]))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The value of the last expression in `source`.
+    fn eval(env: &mut Env, source: &str) -> Result<Expr, Error> {
+        match parse_and_eval_str(env, source)? {
+            Expr::QExpr(mut values) => Ok(*values.pop().unwrap()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_bool_ops() {
+        let mut env = environment::new_with_core_fns();
+
+        assert_eq!(eval(&mut env, "(हैन गलत)"), Ok(Expr::Bool(Boolean(true))));
+        assert_eq!(
+            eval(&mut env, "(अथवा गलत (> २ १) (/ १ ०))"),
+            Ok(Expr::Bool(Boolean(true)))
+        );
+        assert_eq!(
+            eval(&mut env, "(साथै सत्य गलत (समस्या \"नपुग्ने\"))"),
+            Ok(Expr::Bool(Boolean(false)))
+        );
+        assert_eq!(
+            eval(&mut env, "(साथै सत्य सत्य सत्य)"),
+            Ok(Expr::Bool(Boolean(true)))
+        );
+        assert_eq!(
+            eval(&mut env, "(अथवा गलत १)"),
+            Err(Error::NotABoolean(Expr::Num(Sankhya(1))))
+        );
+        assert_eq!(
+            eval(&mut env, "(हैन '())"),
+            Err(Error::NotABoolean(Expr::QExpr(vec![])))
+        );
+        assert_eq!(
+            eval(&mut env, "(हैन सत्य गलत)"),
+            Err(Error::InvalidNumberOfBoolArguments(BoolOp::Not, 2))
+        );
+    }
+
+    #[test]
+    fn test_let() {
+        let mut env = environment::new_with_prelude().unwrap();
+        let unbound = |name: &str| Err(Error::UnboundSymbol(Symbol::Identifier(name.to_string())));
+
+        assert_eq!(
+            eval(&mut env, "(स्थानीय '((क १) (ख (+ १ १))) '(+ क ख))"),
+            Ok(Expr::Num(Sankhya(3)))
+        );
+        assert_eq!(eval(&mut env, "क"), unbound("क"));
+        assert_eq!(
+            eval(&mut env, "(स्थानीय '((क १) (ख (+ क १))) '(+ क ख))"),
+            unbound("क")
+        );
+        assert!(eval(&mut env, "(स्थानीय '((क १)) '(= '(क) २))").is_ok());
+        assert_eq!(eval(&mut env, "क"), unbound("क"));
+
+        let source = "\
+(पुनःस्थानीय '((जोर (\\ '(न) '(यदि (== न ०) '(सत्य) '(बिजोर (- न १)))))
+               (बिजोर (\\ '(न) '(यदि (== न ०) '(गलत) '(जोर (- न १)))))
+               (दस (+ ५ ५)))
+             '(सुची (जोर दस) (बिजोर ७)))";
+        assert_eq!(
+            eval(&mut env, source),
+            Ok(Expr::QExpr(vec![
+                Box::new(Expr::Bool(Boolean(true))),
+                Box::new(Expr::Bool(Boolean(true)))
+            ]))
+        );
+        assert_eq!(eval(&mut env, "जोर"), unbound("जोर"));
+        assert_eq!(
+            eval(&mut env, "((स्थानीय '((क ५)) '(\\ '(ख) '(+ क ख))) १)"),
+            Ok(Expr::Num(Sankhya(6)))
+        );
+        assert_eq!(
+            eval(&mut env, "((स्थानीय '((क ५)) '(\\ '(क) '(* २ क))) १)"),
+            Ok(Expr::Num(Sankhya(2)))
+        );
+        assert_eq!(
+            eval(&mut env, "(स्थानीय '((क)) '(क))"),
+            Err(Error::InvalidBinding(Expr::SExpr(vec![Box::new(
+                Expr::Sym(Symbol::Identifier(String::from("क")))
+            )])))
+        );
+        assert_eq!(
+            eval(&mut env, "(स्थानीय '((१ २)) '(१))"),
+            Err(Error::NotAnIdentifier(Expr::Num(Sankhya(1))))
+        );
+    }
+
+    #[test]
+    fn test_match() {
+        let mut env = environment::new_with_prelude().unwrap();
+        let string = |s: &str| Ok(Expr::Str(String::from(s)));
+        let unbound = |name: &str| Err(Error::UnboundSymbol(Symbol::Identifier(name.to_string())));
+
+        let source = "(फलन '(वर्णन कहरु)
+                         '(मिलाउ कहरु
+                                 '(() \"खाली\")
+                                 '((_) \"एउटा\")
+                                 '((क क) \"जोडी\")
+                                 '((क ऽ बाँकी) (> क १०) \"ठुलो\")
+                                 '((१ ऽ _) \"एकबाट\")
+                                 '((_ ऽ बाँकी) (वटा बाँकी))))";
+        parse_and_eval_str(&mut env, source).unwrap();
+        assert_eq!(eval(&mut env, "(वर्णन '())"), string("खाली"));
+        assert_eq!(eval(&mut env, "(वर्णन '(५))"), string("एउटा"));
+        assert_eq!(eval(&mut env, "(वर्णन '(७ ७))"), string("जोडी"));
+        assert_eq!(eval(&mut env, "(वर्णन '(२० १))"), string("ठुलो"));
+        assert_eq!(eval(&mut env, "(वर्णन '(१ २ ३))"), string("एकबाट"));
+        assert_eq!(eval(&mut env, "(वर्णन '(२ ३ ४))"), Ok(Expr::Num(Sankhya(2))));
+        assert_eq!(eval(&mut env, "बाँकी"), unbound("बाँकी"));
+
+        assert_eq!(
+            eval(&mut env, "(मिलाउ '(१ (२ ३)) '((क (ख ग)) (+ क ख ग)))"),
+            Ok(Expr::Num(Sankhya(6)))
+        );
+        assert_eq!(
+            eval(&mut env, "(मिलाउ \"क\" '(सत्य १) '(१ २))"),
+            Err(Error::NoMatch(Expr::Str(String::from("क"))))
+        );
+        assert_eq!(
+            eval(&mut env, "(मिलाउ १ '(न \"न\" २))"),
+            Err(Error::NotABoolean(Expr::Str(String::from("न"))))
+        );
+        assert_eq!(
+            eval(&mut env, "(मिलाउ १ '(न))"),
+            Err(Error::InvalidMatchClause(Expr::QExpr(vec![Box::new(
+                Expr::Sym(Symbol::Identifier(String::from("न")))
+            )])))
+        );
+        assert!(matches!(
+            eval(&mut env, "(मिलाउ '(१) '((क ऽ) क))"),
+            Err(Error::InvalidPattern(_))
+        ));
+
+        let source = "(फलन '(भेक्टर-वर्णन भ)
+                         '(मिलाउ भ
+                                 '([] \"खाली\")
+                                 '([क क] \"जोडी\")
+                                 '([क ऽ बाँकी] बाँकी)
+                                 '(_ \"भेक्टर होइन\")))";
+        parse_and_eval_str(&mut env, source).unwrap();
+        assert_eq!(eval(&mut env, "(भेक्टर-वर्णन (भेक्टरमा नील))"), string("खाली"));
+        assert_eq!(
+            eval(&mut env, "(भेक्टर-वर्णन (भेक्टरमा '(७ ७)))"),
+            string("जोडी")
+        );
+        assert_eq!(
+            eval(&mut env, "(भेक्टर-वर्णन (भेक्टरमा '(१ २ ३)))"),
+            Ok(Expr::Vector(Rc::new(vec![
+                Expr::Num(Sankhya(2)),
+                Expr::Num(Sankhya(3))
+            ])))
+        );
+        assert_eq!(eval(&mut env, "(भेक्टर-वर्णन '(७ ७))"), string("भेक्टर होइन"));
+    }
+
+    #[test]
+    fn test_loops() {
+        let mut env = environment::new_with_prelude().unwrap();
+        let nums = |ns: &[i32]| {
+            Expr::QExpr(
+                ns.iter()
+                    .map(|n| Box::new(Expr::Num(Sankhya(*n))))
+                    .collect(),
+            )
+        };
+
+        assert_eq!(eval(&mut env, "(दायरा ० ४)"), Ok(nums(&[0, 1, 2, 3])));
+        assert_eq!(eval(&mut env, "(दायरा ५ ० -२)"), Ok(nums(&[5, 3, 1])));
+        assert_eq!(eval(&mut env, "(दायरा ३ ३)"), Ok(nums(&[])));
+        assert_eq!(eval(&mut env, "(दायरा ० ४ ०)"), Err(Error::ZeroStep));
+
+        let source = "(नामक '(जम्मा) ०) (गन्ती '(इ) १ १० ३ '(नामक '(जम्मा) (+ जम्मा इ))) जम्मा";
+        assert_eq!(
+            parse_and_eval_str(&mut env, source),
+            Ok(Expr::QExpr(vec![
+                Box::new(Expr::SExpr(vec![])),
+                Box::new(Expr::SExpr(vec![])),
+                Box::new(Expr::Num(Sankhya(12)))
+            ]))
+        );
+        eval(&mut env, "(जबसम्म '(< जम्मा २०) '(नामक '(जम्मा) (+ जम्मा ५)))").unwrap();
+        assert_eq!(eval(&mut env, "जम्मा"), Ok(Expr::Num(Sankhya(22))));
+        assert_eq!(
+            eval(&mut env, "(जबसम्म '(हैन सत्य) '(१))"),
+            Ok(Expr::QExpr(vec![]))
+        );
+        assert_eq!(
+            eval(&mut env, "(जबसम्म '(१) '(२))"),
+            Err(Error::NotABoolean(Expr::Num(Sankhya(1))))
+        );
+
+        assert_eq!(
+            eval(
+                &mut env,
+                "(हरेक '(क) '(१ २ ३ ४) '(यदि (== क ३) '((रोक (* क १०))) '(क)))"
+            ),
+            Ok(Expr::Num(Sankhya(30)))
+        );
+        assert_eq!(
+            eval(
+                &mut env,
+                "(गन्ती '(इ) ० ४ '(यदि (== इ ३) '((जारी १००)) '((छाप इ))))"
+            ),
+            Ok(Expr::Num(Sankhya(100)))
+        );
+        assert_eq!(
+            eval(&mut env, "(हरेक '(क) '() '(क))"),
+            Ok(Expr::QExpr(vec![]))
+        );
+        assert_eq!(
+            eval(&mut env, "(रोक १)"),
+            Err(Error::Break(Expr::Num(Sankhya(1))))
+        );
+        assert_eq!(
+            eval(&mut env, "(हरेक '(क ख) '(१) '(क))"),
+            Err(Error::InvalidBinding(Expr::QExpr(vec![
+                Box::new(Expr::Sym(Symbol::Identifier(String::from("क")))),
+                Box::new(Expr::Sym(Symbol::Identifier(String::from("ख"))))
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_references() {
+        let mut env = environment::new_with_prelude().unwrap();
+        let num = |n: i32| Ok(Expr::Num(Sankhya(n)));
+        let boolean = |b: bool| Ok(Expr::Bool(Boolean(b)));
+
+        let source = "(नामक '(गन्ने) (सन्दर्भ ०))
+                      (फलन '(बढाउ न) '(फेर गन्ने + न))
+                      (बढाउ २)
+                      (बढाउ ३)";
+        assert_eq!(eval(&mut env, source), num(5));
+        assert_eq!(eval(&mut env, "(हेर गन्ने)"), num(5));
+        assert_eq!(
+            eval(&mut env, "(फेर गन्ने (\\ '(क) '(+ क (हेर गन्ने))))"),
+            num(10)
+        );
+
+        assert_eq!(eval(&mut env, "(नामक '(अर्को) गन्ने) (राख अर्को ०)"), num(0));
+        assert_eq!(eval(&mut env, "(हेर गन्ने)"), num(0));
+        assert_eq!(eval(&mut env, "(== गन्ने अर्को)"), boolean(true));
+        assert_eq!(eval(&mut env, "(== (सन्दर्भ १) (सन्दर्भ १))"), boolean(false));
+        assert_eq!(
+            eval(&mut env, "(फेर (सन्दर्भ '(१)) एकत्र '(२))"),
+            Ok(Expr::QExpr(vec![
+                Box::new(Expr::Num(Sankhya(1))),
+                Box::new(Expr::Num(Sankhya(2)))
+            ]))
+        );
+        assert_eq!(
+            Expr::Ref(Reference::new(Expr::Num(Sankhya(1)))).to_string(),
+            "सन्दर्भ(१)"
+        );
+        assert_eq!(
+            eval(&mut env, "(हेर ५)"),
+            Err(Error::NotAReference(Expr::Num(Sankhya(5))))
+        );
+
+        let source = "(नामक '(स) (सन्दर्भ '())) (राख स (सुची स)) (हेर स)";
+        let value = eval(&mut env, source).unwrap();
+        assert_eq!(value.to_string(), "'(सन्दर्भ('(सन्दर्भ(…))))");
+        assert!(format!("{:?}", value).ends_with("Ref(Reference(…))])))])"));
+    }
+
+    #[test]
+    fn test_vectors() {
+        let mut env = environment::new_with_prelude().unwrap();
+        let num = |n: i32| Expr::Num(Sankhya(n));
+        let vector = |ns: &[i32]| Ok(Expr::Vector(Rc::new(ns.iter().map(|n| num(*n)).collect())));
+
+        assert_eq!(eval(&mut env, "[१ २ ३]"), vector(&[1, 2, 3]));
+        assert_eq!(eval(&mut env, "(भेक्टर १ (+ १ १))"), vector(&[1, 2]));
+        assert_eq!(
+            eval(&mut env, "(नामक '(भ) [१० २० ३०]) (तान भ २)"),
+            Ok(num(30))
+        );
+        assert_eq!(eval(&mut env, "(बदल भ ० ९)"), vector(&[9, 20, 30]));
+        assert_eq!(eval(&mut env, "भ"), vector(&[10, 20, 30]));
+        assert_eq!(
+            eval(&mut env, "(थप भ ४० ५०)"),
+            vector(&[10, 20, 30, 40, 50])
+        );
+        assert_eq!(eval(&mut env, "(टुक्रा भ १)"), vector(&[20, 30]));
+        assert_eq!(eval(&mut env, "(टुक्रा भ १ १)"), vector(&[]));
+        assert_eq!(eval(&mut env, "(वटा भ)"), Ok(num(3)));
+        assert_eq!(eval(&mut env, "(लम्बाई भ)"), Ok(num(3)));
+        assert_eq!(
+            eval(&mut env, "(== भ [१० २० ३०])"),
+            Ok(Expr::Bool(Boolean(true)))
+        );
+        assert_eq!(eval(&mut env, "(भेक्टरमा (सुचीमा भ))"), vector(&[10, 20, 30]));
+        assert_eq!(
+            eval(&mut env, "(सुचीमा [१ \"क\"])"),
+            Ok(Expr::QExpr(vec![
+                Box::new(num(1)),
+                Box::new(Expr::Str(String::from("क")))
+            ]))
+        );
+        assert_eq!(
+            eval(&mut env, "(तान भ ३)"),
+            Err(Error::IndexOutOfRange(3, 3))
+        );
+        assert_eq!(
+            eval(&mut env, "(तान भ -१)"),
+            Err(Error::IndexOutOfRange(-1, 3))
+        );
+        assert_eq!(
+            eval(&mut env, "(टुक्रा भ २ १)"),
+            Err(Error::InvalidSlice(2, 1))
+        );
+        assert_eq!(
+            eval(&mut env, "(तान '(१) ०)"),
+            Err(Error::NotAVector(Expr::QExpr(vec![Box::new(num(1))])))
+        );
+
+        let source = "(नामक '(ठुलो) [])
+                      (गन्ती '(इ) ० १००० '(नामक '(ठुलो) (थप ठुलो (* इ इ))))
+                      (तान ठुलो ९९९)";
+        assert_eq!(eval(&mut env, source), Ok(num(998001)));
+        assert_eq!(parser::parse("[क (ख)]").unwrap().to_string(), "([क (ख)])");
+        assert_eq!(
+            eval(&mut env, "[(+ १ २)]").map(|v| v.to_string()),
+            Ok(String::from("[(+ १ २)]"))
+        );
+    }
+
+    #[test]
+    fn test_list_functions() {
+        let mut env = environment::new_with_prelude().unwrap();
+        eval(&mut env, "(फलन '(दोब्बर क) '(* २ क))").unwrap();
+        eval(&mut env, "(फलन '(ठूलो क) '(> क १))").unwrap();
+
+        let cases = [
+            ("(लहरै दोब्बर '(१ २ ३))", "'(२ ४ ६)"),
+            ("(छान ठूलो '(१ २ ३))", "'(२ ३)"),
+            ("(पट्याउ - १० '(१ २ ३))", "४"),
+            ("(जोड '(१ २ ३))", "६"),
+            ("(लम्बाई '(१ २ ३))", "३"),
+            ("(औँ १ '(१ (+ १ १) ३))", "२"),
+            ("(अन्तिम '(१ २ ३))", "३"),
+            ("(लेउ ५ '(१ २ ३))", "'(१ २ ३)"),
+            ("(छोड २ '(१ २ ३))", "'(३)"),
+            ("(फोर १ '(१ २ ३))", "(सुची '(१) '(२ ३))"),
+            ("(मा ३ '(१ २ ३))", "सत्य"),
+            ("(मा ४ '(१ २ ३))", "गलत"),
+            ("(क्रमबद्ध '(३ १ २ १))", "'(१ १ २ ३)"),
+            ("(क्रमबद्ध '(\"ख\" \"क\"))", "'(\"क\" \"ख\")"),
+            ("(उल्टाउ '(१ २ ३))", "'(३ २ १)"),
+            ("(जोडी '(१ २ ३) '(४ ५))", "(सुची '(१ ४) '(२ ५))"),
+            ("(क्रमाङ्क '(७ ८))", "(सुची '(० ७) '(१ ८))"),
+            ("(कुनै ठूलो '(१ २))", "सत्य"),
+            ("(सबै ठूलो '(१ २))", "गलत"),
+            ("(सबै ठूलो '())", "सत्य"),
+            ("(लहरैएकत्र (\\ '(क) '(सुची क क)) '(१ २))", "'(१ १ २ २)"),
+        ];
+        for (source, expected) in cases.iter() {
+            assert_eq!(
+                eval(&mut env, source),
+                eval(&mut env, expected),
+                "{}",
+                source
+            );
+        }
+
+        assert_eq!(
+            eval(&mut env, "(औँ ३ '(१ २ ३))"),
+            Err(Error::IndexOutOfRange(3, 3))
+        );
+        assert_eq!(
+            eval(&mut env, "(कुनै (\\ '(क) '(क)) '(१))"),
+            Err(Error::NotABoolean(Expr::Num(Sankhya(1))))
+        );
+        assert!(eval(&mut env, "(क्रमबद्ध (सुची + -))").is_err());
+
+        // names stay ordinary bindings, so they can still be redefined
+        eval(&mut env, "(फलन '(लम्बाई कहरु) '(०))").unwrap();
+        assert_eq!(eval(&mut env, "(लम्बाई '(१ २))"), Ok(Expr::Num(Sankhya(0))));
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut env = environment::new_with_prelude().unwrap();
+        let yes = Ok(Expr::Bool(Boolean(true)));
+
+        assert_eq!(eval(&mut env, "(< \"क\" \"ख\")"), yes);
+        assert_eq!(
+            eval(&mut env, "(< \"ह\" \"अ\")"),
+            Ok(Expr::Bool(Boolean(false)))
+        );
+        assert_eq!(eval(&mut env, "(< \"औ\" \"क\")"), yes);
+        assert_eq!(eval(&mut env, "(< \"१२\" \"क\")"), yes);
+        assert_eq!(eval(&mut env, "(>= '(१ २) '(१))"), yes);
+        assert_eq!(eval(&mut env, "(< गलत सत्य)"), yes);
+        assert_eq!(eval(&mut env, "(< सत्य १)"), yes);
+        assert_eq!(eval(&mut env, "(> \"क\" १००)"), yes);
+        assert!(eval(&mut env, "(< + -)").is_err());
+
+        eval(&mut env, "(फलन '(दोस्रो जोडी) '(द्वितिय जोडी))").unwrap();
+        let cases = [
+            (
+                "(क्रमबद्ध '(\"ग\" \"आ\" \"क\" \"अ\"))",
+                "'(\"अ\" \"आ\" \"क\" \"ग\")",
+            ),
+            ("(क्रमबद्ध '(\"क\" २ सत्य '(१) १))", "(सुची सत्य १ २ \"क\" '(१))"),
+            (
+                "(क्रमबद्ध (सुची '(२ ३) '(१ ४) '(१ ३)))",
+                "(सुची '(१ ३) '(१ ४) '(२ ३))",
+            ),
+            ("(क्रमबद्ध '(३ १ २) नील >)", "'(३ २ १)"),
+            (
+                "(क्रमबद्ध (सुची '(\"ख\" १) '(\"क\" २) '(\"ग\" १)) दोस्रो)",
+                "(सुची '(\"ख\" १) '(\"ग\" १) '(\"क\" २))",
+            ),
+            ("(क्रमबद्ध '(१ २ ३ ४) (\\ '(क) '(- ० क)) <)", "'(४ ३ २ १)"),
+        ];
+        for (source, expected) in cases.iter() {
+            assert_eq!(
+                eval(&mut env, source),
+                eval(&mut env, expected),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_time() {
+        let mut env = environment::new_with_prelude().unwrap();
+        let show = |value: Result<Expr, Error>| value.unwrap().to_string();
+
+        eval(&mut env, "(नामक '(नयाँवर्ष) (मिति २०८२ १ १ ६ ३० ०))").unwrap();
+        assert_eq!(
+            show(eval(&mut env, "नयाँवर्ष")),
+            "समय(२०८२ बैशाख ०१, सोमबार ०६:३०:००)"
+        );
+        assert_eq!(
+            show(eval(&mut env, "(मितिभाग नयाँवर्ष)")),
+            "'(२०८२ १ १ ६ ३० ० २)"
+        );
+        assert_eq!(
+            show(eval(&mut env, "(इस्वीभाग नयाँवर्ष)")),
+            "'(२०२५ ४ १४ ६ ३० ० २)"
+        );
+        assert_eq!(
+            eval(&mut env, "(== नयाँवर्ष (इस्वी २०२५ ४ १४ ६ ३० ०))"),
+            Ok(Expr::Bool(Boolean(true)))
+        );
+        assert_eq!(
+            show(eval(
+                &mut env,
+                "(समयलेख (समयथप नयाँवर्ष (* ३० ८६४००)) \"%B %d, %A\")"
+            )),
+            "\"जेठ ०१, बुधबार\""
+        );
+        assert_eq!(
+            show(eval(&mut env, "(समयअन्तर नयाँवर्ष (मिति २०८१ १२ ३०))")),
+            "'(१ २३४००)"
+        );
+        assert_eq!(
+            show(eval(&mut env, "(समयअन्तर (मिति २००० १ १) (मिति २०९० १ १))")),
+            "'(-३२८७३ ०)"
+        );
+        assert_eq!(
+            eval(
+                &mut env,
+                "(== नयाँवर्ष (समयथप (मिति २०८१ १२ ३०) (समयअन्तर नयाँवर्ष (मिति २०८१ १२ ३०))))"
+            ),
+            Ok(Expr::Bool(Boolean(true)))
+        );
+        assert_eq!(
+            eval(&mut env, "(< (मिति २०८१ १ १) नयाँवर्ष)"),
+            Ok(Expr::Bool(Boolean(true)))
+        );
+        assert_eq!(
+            eval(&mut env, "(मिति २०८२ १ ३२)"),
+            Err(Error::InvalidDate(String::from("२०८२ १ ३२")))
+        );
+        assert_eq!(
+            eval(&mut env, "(मितिभाग (इस्वी १९०० १ १))"),
+            Err(Error::DateOutOfRange)
+        );
+        assert_eq!(
+            eval(&mut env, "(मितिभाग १)"),
+            Err(Error::NotATime(Expr::Num(Sankhya(1))))
+        );
+
+        match (
+            eval(&mut env, "(अहिले ०)"),
+            eval(&mut env, "(अहिले ६०)"),
+            eval(&mut env, "(अहिले '(१ ०))"),
+        ) {
+            (Ok(Expr::Time(now)), Ok(Expr::Time(later)), Ok(Expr::Time(tomorrow))) => {
+                assert!(later.0 >= now.0 + 60);
+                assert!(tomorrow.0 >= now.0 + SECONDS_PER_DAY);
+            }
+            x => panic!("expected times : {:?}", x),
+        }
+    }
+
+    #[test]
+    fn test_exit() {
+        let mut env = environment::new_with_prelude().unwrap();
+
+        let result = parse_and_eval_str(&mut env, "(नामक '(क) १) (निकास ३) (नामक '(क) २)");
+        assert_eq!(result, Err(Error::Exit(3)));
+        assert_eq!(
+            environment::lookup(&env, &Symbol::Identifier(String::from("क"))),
+            Ok(Expr::Num(Sankhya(1)))
+        );
+        assert_eq!(
+            parse_and_eval_str(&mut env, "(निकास २५६)"),
+            Err(Error::InvalidExitCode(256))
+        );
+        assert_eq!(
+            parse_and_eval_str(&mut env, "(निकास -१)"),
+            Err(Error::InvalidExitCode(-1))
+        );
+        assert_eq!(Error::Exit(3).exit_code(), 3);
+        assert_eq!(Error::ParseError(String::new()).exit_code(), 65);
+        assert_eq!(Error::ImportError(Expr::QExpr(vec![])).exit_code(), 66);
+        assert_eq!(Error::NotANumber(Expr::QExpr(vec![])).exit_code(), 1);
+    }
+
+    #[test]
+    fn test_assert_throws_keeps_exit() {
+        let mut env = environment::new_with_prelude().unwrap();
+
+        let result = parse_and_eval_str(&mut env, "(दाबीसमस्या '(निकास २))");
+        assert_eq!(result, Err(Error::Exit(2)));
+    }
+
+    #[test]
+    fn test_import_next_to_file() {
+        let dir = std::env::temp_dir().join(format!("alpa-import-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("मुख्य.अ"), "(आयात बिचको)\n(* साझा २)").unwrap();
+        fs::write(dir.join("बिचको.अ"), "(आयात सहायक)").unwrap();
+        fs::write(dir.join("सहायक.अ"), "(नामक '(साझा) २१)").unwrap();
+
+        let mut env = environment::new_with_prelude().unwrap();
+        let result = eval_file(&mut env, &dir.join("मुख्य.अ"));
+        let missing = parse_and_eval_str(&mut env, "(आयात सहायक)");
+        fs::remove_dir_all(&dir).unwrap();
+
+        match result {
+            Ok(Expr::QExpr(results)) => {
+                assert_eq!(results.last(), Some(&Box::new(Expr::Num(Sankhya(42)))))
+            }
+            x => panic!("{:?}", x),
+        }
+        assert!(matches!(missing, Err(Error::ImportError(_))));
+    }
+
+    #[test]
+    fn test_assert_throws_message() {
+        let mut env = environment::new_with_prelude().unwrap();
+
+        let result = parse_and_eval_str(
+            &mut env,
+            "(दाबीसमस्या '(/ १ ०) \"शुन्यले भाग गर्न मिल्दैन : (/ १ ०)\")",
+        );
+        assert_eq!(
+            result,
+            Ok(Expr::QExpr(vec![Box::new(Expr::Bool(Boolean(true)))]))
+        );
+
+        let result = parse_and_eval_str(&mut env, "(दाबीसमस्या '(/ १ ०) \"कुनै पनि\")");
+        assert!(matches!(result, Err(Error::AssertEqualFailed(_, _))));
+    }
+}
@@ -0,0 +1,499 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::slice;
+
+use crate::core;
+use crate::environment::{self, Env};
+use crate::evaluator::{self, Frame, Hook, SharedHook};
+use crate::ntypes::Sankhya;
+use crate::parser::{self, Span};
+use crate::types::{Error, Expr, Function};
+
+pub const HELP: &str = "\
+s, step            अर्को फलनभित्र पस्ने (step in)
+n, next            यही तहको अर्को फलनसम्म जाने (step over)
+o, out             हालको फलनबाट बाहिर निस्कने (step out)
+c, continue        अर्को ब्रेकपोइन्टसम्म चलाउने (continue)
+q, quit            मूल्याङ्कन रोक्ने (abort the evaluation)
+bt, backtrace      फलनहरुको थाक देखाउने (show the call stack)
+env                वातावरणको चेन देखाउने (show the environment chain)
+p <एक्सपर्>         रोकिएको ठाउँमा एक्सपर् बिस्तार गर्ने (evaluate in the paused frame)
+b <नाम|लाइन>       ब्रेकपोइन्ट राख्ने (add a breakpoint)
+h, help            यो सहायता देखाउने (show this help)";
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Breakpoint {
+    /// Pauses whenever a function is called by this name.
+    Function(String),
+    /// Pauses at the expressions that start on a line of a file.
+    Line {
+        path: PathBuf,
+        line: usize,
+        forms: Vec<Vec<Box<Expr>>>,
+        /// The top-level expressions of the file that hold `forms`.
+        scope: Vec<Box<Expr>>,
+    },
+}
+
+impl Breakpoint {
+    fn matches(&self, frames: &[Frame]) -> bool {
+        let frame = &frames[frames.len() - 1];
+        match self {
+            Breakpoint::Function(name) => frame
+                .function
+                .as_ref()
+                .is_some_and(|sym| Expr::Sym(sym.clone()).to_string() == *name),
+            Breakpoint::Line {
+                path, forms, scope, ..
+            } => forms.contains(&frame.form) && written_in(frames, path, scope),
+        }
+    }
+}
+
+/// Whether the innermost application comes from `scope` of the file at
+/// `path`, rather than being an equal expression written somewhere else.
+/// Walking out through the frames, the application sits either in the body
+/// of the nearest lambda it is not an argument of, or, when there is none,
+/// at the top level of the file being evaluated.
+fn written_in(frames: &[Frame], path: &Path, scope: &[Box<Expr>]) -> bool {
+    let mut form = &frames[frames.len() - 1].form;
+    for frame in frames[..frames.len() - 1].iter().rev() {
+        let is_arg = frame.form[1..]
+            .iter()
+            .any(|arg| matches!(&**arg, Expr::SExpr(items) if items == form));
+        match &frame.fun {
+            Function::Lambda(_, body, _, _) if !is_arg => {
+                return scope.iter().any(|expr| encloses(expr, body))
+            }
+            _ => form = &frame.form,
+        }
+    }
+
+    let top = Expr::SExpr(form.clone());
+    let same_file = core::current_file().is_some_and(|file| same_path(&file, path));
+    same_file && scope.iter().any(|expr| **expr == top)
+}
+
+fn encloses(expr: &Expr, part: &Expr) -> bool {
+    match expr {
+        _ if expr == part => true,
+        Expr::SExpr(items) | Expr::QExpr(items) => items.iter().any(|item| encloses(item, part)),
+        _ => false,
+    }
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+impl std::fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Breakpoint::Function(name) => write!(f, "फलन {}", name),
+            Breakpoint::Line { path, line, .. } => write!(f, "{}:{}", path.display(), line),
+        }
+    }
+}
+
+/// Applications written on `line`, quoted bodies included, as they look
+/// once evaluated. Only the outermost one starting on the line is kept for
+/// each top-level expression.
+#[allow(clippy::vec_box)]
+fn forms_at(exprs: &[Box<Expr>], spans: &[Span], line: usize, forms: &mut Vec<Vec<Box<Expr>>>) {
+    for (expr, span) in exprs.iter().zip(spans.iter()) {
+        if let Expr::SExpr(items) | Expr::QExpr(items) = &**expr {
+            match span.line == line {
+                true => forms.push(items.clone()),
+                false => forms_at(items, &span.children, line, forms),
+            }
+        }
+    }
+}
+
+/// Where `form` is written in `source`, as a line number.
+fn line_of(exprs: &[Box<Expr>], spans: &[Span], form: &[Box<Expr>]) -> Option<usize> {
+    for (expr, span) in exprs.iter().zip(spans.iter()) {
+        if let Expr::SExpr(items) | Expr::QExpr(items) = &**expr {
+            if items[..] == *form {
+                return Some(span.line);
+            }
+            if let Some(line) = line_of(items, &span.children, form) {
+                return Some(line);
+            }
+        }
+    }
+
+    None
+}
+
+enum Mode {
+    Run,
+    /// Pause at the next application at most this deep.
+    Step(usize),
+}
+
+pub struct Debugger {
+    pub breakpoints: Vec<Breakpoint>,
+    sources: Vec<PathBuf>,
+    mode: Mode,
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Debugger::new(
+            Box::new(io::BufReader::new(io::stdin())),
+            Box::new(io::stdout()),
+        )
+    }
+}
+
+impl Debugger {
+    pub fn new(input: Box<dyn BufRead>, output: Box<dyn Write>) -> Debugger {
+        Debugger {
+            breakpoints: vec![],
+            sources: vec![],
+            mode: Mode::Run,
+            input,
+            output,
+        }
+    }
+
+    /// Remembers a loaded file, so locations can be shown and line
+    /// breakpoints resolved against it.
+    pub fn add_source(&mut self, path: &Path) {
+        self.sources.retain(|source| source != path);
+        self.sources.push(path.to_path_buf());
+    }
+
+    /// Pauses at the first application of the next evaluation.
+    pub fn step_in(&mut self) {
+        self.mode = Mode::Step(usize::MAX);
+    }
+
+    /// `नाम`, `लाइन` in the last loaded file, or `फाइल:लाइन`.
+    pub fn add_breakpoint(&mut self, spec: &str) -> Result<&Breakpoint, String> {
+        let (path, line) = match spec.rsplit_once(':') {
+            Some((path, line)) => (Some(PathBuf::from(path)), line),
+            None => (self.sources.last().cloned(), spec),
+        };
+        let breakpoint = match line.parse::<Sankhya>() {
+            Err(_) => Breakpoint::Function(spec.to_string()),
+            Ok(Sankhya(line)) => {
+                let path = path.ok_or_else(|| String::from("कुनै फाइल लोड गरिएको छैन"))?;
+                let source = fs::read_to_string(&path)
+                    .map_err(|_| format!("फाइल पढ्न सकिएन : {}", path.display()))?;
+                let (exprs, spans) = parser::parse_spanned(&source).map_err(|pe| pe.to_string())?;
+
+                let mut forms = vec![];
+                let mut scope = vec![];
+                for (expr, span) in exprs.iter().zip(spans.iter()) {
+                    let found = forms.len();
+                    forms_at(
+                        slice::from_ref(expr),
+                        slice::from_ref(span),
+                        line as usize,
+                        &mut forms,
+                    );
+                    if forms.len() > found {
+                        scope.push(expr.clone());
+                    }
+                }
+                if forms.is_empty() {
+                    return Err(format!("{}:{} मा कुनै एक्सपर् छैन", path.display(), line));
+                }
+                self.add_source(&path);
+                Breakpoint::Line {
+                    path,
+                    line: line as usize,
+                    forms,
+                    scope,
+                }
+            }
+        };
+        self.breakpoints.push(breakpoint);
+
+        Ok(&self.breakpoints[self.breakpoints.len() - 1])
+    }
+
+    fn location(&self, form: &[Box<Expr>]) -> Option<String> {
+        self.sources.iter().rev().find_map(|path| {
+            let source = fs::read_to_string(path).ok()?;
+            let (exprs, spans) = parser::parse_spanned(&source).ok()?;
+            line_of(&exprs, &spans, form).map(|line| format!("{}:{}", path.display(), line))
+        })
+    }
+
+    fn show_frame(&mut self, frames: &[Frame], idx: usize) -> io::Result<()> {
+        let frame = &frames[idx];
+        let location = match self.location(&frame.form) {
+            Some(location) => format!(" [{}]", location),
+            None => String::new(),
+        };
+        writeln!(
+            self.output,
+            "#{} {}{}",
+            idx,
+            Expr::SExpr(frame.form.clone()),
+            location
+        )
+    }
+
+    fn show_env(&mut self, env: &Env) -> io::Result<()> {
+        let mut level = 0;
+        let mut current = Some(Rc::clone(env));
+        while let Some(env) = current {
+            let mut bindings: Vec<String> = environment::bindings(&env)
+                .iter()
+                .map(|(sym, expr)| format!("{} = {}", Expr::Sym(sym.clone()), expr))
+                .collect();
+            match &env.parent {
+                Some(_) => {
+                    bindings.sort();
+                    writeln!(self.output, "[{}] {}", level, bindings.join(", "))?;
+                }
+                None => writeln!(
+                    self.output,
+                    "[{}] विश्वव्यापी वातावरण ({} नामहरु)",
+                    level,
+                    Sankhya(bindings.len() as i32)
+                )?,
+            }
+            current = env.parent.clone();
+            level += 1;
+        }
+
+        Ok(())
+    }
+
+    fn pauses_at(&self, frames: &[Frame]) -> bool {
+        let stepping = match self.mode {
+            Mode::Step(depth) => frames.len() <= depth,
+            Mode::Run => false,
+        };
+        stepping || self.breakpoints.iter().any(|b| b.matches(frames))
+    }
+
+    /// Reads commands until one resumes the evaluation. The end of input
+    /// lets the rest of the evaluation run without pausing.
+    fn pause(&mut self, frames: &[Frame]) -> io::Result<Result<(), Error>> {
+        let depth = frames.len();
+        let frame = &frames[depth - 1];
+        let env = frame.locals.as_ref().unwrap_or(&frame.env);
+        self.show_frame(frames, depth - 1)?;
+
+        loop {
+            write!(self.output, "(डिबग) ")?;
+            self.output.flush()?;
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                self.mode = Mode::Run;
+                self.breakpoints.clear();
+                return Ok(Ok(()));
+            }
+
+            let line = line.trim();
+            let (command, arg) = match line.find(char::is_whitespace) {
+                Some(idx) => (&line[..idx], line[idx..].trim()),
+                None => (line, ""),
+            };
+            match command {
+                "s" | "step" => self.mode = Mode::Step(usize::MAX),
+                "n" | "next" => self.mode = Mode::Step(depth),
+                "o" | "out" => self.mode = Mode::Step(depth - 1),
+                "c" | "continue" => self.mode = Mode::Run,
+                "q" | "quit" => return Ok(Err(Error::Interrupted)),
+                "bt" | "backtrace" => {
+                    for idx in (0..depth).rev() {
+                        self.show_frame(frames, idx)?;
+                    }
+                    continue;
+                }
+                "env" => {
+                    self.show_env(env)?;
+                    continue;
+                }
+                "p" | "print" => {
+                    let mut env = Rc::clone(env);
+                    match core::parse_and_eval_str(&mut env, arg) {
+                        Ok(Expr::QExpr(mut values)) if !values.is_empty() => {
+                            writeln!(self.output, "{}", values.pop().unwrap())?
+                        }
+                        Ok(value) => writeln!(self.output, "{}", value)?,
+                        Err(e) => writeln!(self.output, "Error : {}", e)?,
+                    }
+                    continue;
+                }
+                "b" | "break" => {
+                    match self.add_breakpoint(arg) {
+                        Ok(breakpoint) => {
+                            let message = format!("ब्रेकपोइन्ट : {}", breakpoint);
+                            writeln!(self.output, "{}", message)?
+                        }
+                        Err(msg) => writeln!(self.output, "{}", msg)?,
+                    }
+                    continue;
+                }
+                "h" | "help" => {
+                    writeln!(self.output, "{}", HELP)?;
+                    continue;
+                }
+                _ => {
+                    writeln!(self.output, "अज्ञात आदेश : {} (h हेर्नुहोस्)", line)?;
+                    continue;
+                }
+            }
+
+            return Ok(Ok(()));
+        }
+    }
+}
+
+/// Lambdas pause once their arguments are bound, so that the paused frame
+/// can see them, and everything else as it is applied.
+impl Hook for Debugger {
+    fn enter(&mut self, frames: &[Frame]) -> Result<(), Error> {
+        let frame = &frames[frames.len() - 1];
+        if matches!(frame.fun, Function::Lambda(..)) || !self.pauses_at(frames) {
+            return Ok(());
+        }
+
+        self.pause(frames).unwrap_or(Ok(()))
+    }
+
+    fn body(&mut self, frames: &[Frame]) -> Result<(), Error> {
+        if !self.pauses_at(frames) {
+            return Ok(());
+        }
+
+        self.pause(frames).unwrap_or(Ok(()))
+    }
+}
+
+/// Evaluates `source` with `debugger` watching.
+pub fn eval_str(
+    debugger: &Rc<RefCell<Debugger>>,
+    env: &mut Env,
+    source: &str,
+) -> Result<Expr, Error> {
+    let previous = evaluator::set_hook(Some(Rc::clone(debugger) as SharedHook));
+    let result = core::parse_and_eval_str(env, source);
+    evaluator::set_hook(previous);
+    debugger.borrow_mut().mode = Mode::Run;
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    struct Output(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn session(commands: &str) -> (Rc<RefCell<Debugger>>, Rc<RefCell<Vec<u8>>>) {
+        let output = Rc::new(RefCell::new(vec![]));
+        let debugger = Debugger::new(
+            Box::new(Cursor::new(commands.to_string().into_bytes())),
+            Box::new(Output(Rc::clone(&output))),
+        );
+        (Rc::new(RefCell::new(debugger)), output)
+    }
+
+    fn text(output: &Rc<RefCell<Vec<u8>>>) -> String {
+        String::from_utf8(output.borrow().clone()).unwrap()
+    }
+
+    #[test]
+    fn test_breakpoint_and_inspect() {
+        let mut env = environment::new_with_prelude().unwrap();
+        core::parse_and_eval_str(
+            &mut env,
+            "(फलन '(घटाउ क) '(यदि (== क ०) '(०) '(घटाउ (- क १))))",
+        )
+        .unwrap();
+
+        let (debugger, output) = session("p (* क १०)\nenv\nc\nbt\nq\n");
+        debugger.borrow_mut().add_breakpoint("घटाउ").unwrap();
+        let result = eval_str(&debugger, &mut env, "(घटाउ २)");
+
+        assert_eq!(result, Err(Error::Interrupted));
+        let text = text(&output);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "#0 (घटाउ २)");
+        assert_eq!(lines[1], "(डिबग) २०");
+        assert_eq!(lines[2], "(डिबग) [0] क = २, ऽ = '()");
+        assert!(lines[3].starts_with("[1] विश्वव्यापी वातावरण"));
+        assert!(lines[4].starts_with("(डिबग) #"));
+        assert!(lines[4].ends_with("(घटाउ (- क १))"));
+        assert!(text.contains("(डिबग) #"));
+        assert!(text.contains("(घटाउ २)"));
+    }
+
+    #[test]
+    fn test_stepping() {
+        let mut env = environment::new_with_core_fns();
+        let (debugger, output) = session("s\nn\ns\np क\no\n");
+        core::parse_and_eval_str(&mut env, "(= '(दोब्बर) (\\ '(क) '(* २ क)))").unwrap();
+
+        debugger.borrow_mut().step_in();
+        let result = eval_str(&debugger, &mut env, "(+ (दोब्बर १) (दोब्बर ३))");
+
+        assert_eq!(
+            result,
+            Ok(Expr::QExpr(vec![Box::new(Expr::Num(Sankhya(8)))]))
+        );
+        assert_eq!(
+            text(&output),
+            "#0 (+ (दोब्बर १) (दोब्बर ३))\n(डिबग) #1 (दोब्बर १)\n(डिबग) #1 (दोब्बर ३)\n(डिबग) #2 (* २ क)\n(डिबग) ३\n(डिबग) "
+        );
+    }
+
+    #[test]
+    fn test_line_breakpoint() {
+        let path = std::env::temp_dir().join(format!("alpa-debug-{}.अ", std::process::id()));
+        fs::write(&path, "(फलन '(दोब्बर क)\n     '(* २ क))\n").unwrap();
+        let mut env = environment::new_with_prelude().unwrap();
+        core::eval_file(&mut env, &path).unwrap();
+
+        let (debugger, output) = session("c\n");
+        debugger.borrow_mut().add_source(&path);
+        let spec = format!("{}:२", path.display());
+        assert!(debugger.borrow_mut().add_breakpoint(&spec).is_ok());
+        assert!(debugger.borrow_mut().add_breakpoint("५").is_err());
+        let result = eval_str(&debugger, &mut env, "(दोब्बर ४)");
+        let elsewhere = eval_str(
+            &debugger,
+            &mut env,
+            "(फलन '(तेब्बर क) '(+ क (* २ क))) (तेब्बर १) (नामक '(क) ३) (* २ क)",
+        );
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            result,
+            Ok(Expr::QExpr(vec![Box::new(Expr::Num(Sankhya(8)))]))
+        );
+        assert!(elsewhere.is_ok());
+        assert_eq!(
+            text(&output),
+            format!("#1 (* २ क) [{}:2]\n(डिबग) ", path.display())
+        );
+    }
+}
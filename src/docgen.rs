@@ -0,0 +1,548 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::ntypes::Sankhya;
+use crate::parser;
+use crate::types::{Error, Expr, QExprsOp, Symbol};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Kind {
+    Function(Vec<String>),
+    Value,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Definition {
+    pub name: String,
+    pub kind: Kind,
+    pub docstring: Option<String>,
+    pub comments: Vec<String>,
+    pub line: usize,
+    pub references: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Module {
+    pub name: String,
+    pub definitions: Vec<Definition>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    Markdown,
+    Html,
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Html => "html",
+        }
+    }
+}
+
+pub fn extract(source: &str) -> Result<Vec<Definition>, Error> {
+    let (exprs, spans) =
+        parser::parse_spanned(source).map_err(|pe| Error::ParseError(pe.to_string()))?;
+
+    let mut definitions = vec![];
+    let mut prev_end = 0;
+    for (expr, span) in exprs.iter().zip(spans.iter()) {
+        let comments = leading_comments(&source[prev_end..span.start]);
+        prev_end = span.end;
+
+        for mut definition in definitions_in(expr) {
+            definition.comments = comments.clone();
+            definition.line = span.line;
+            definitions.push(definition);
+        }
+    }
+
+    Ok(definitions)
+}
+
+fn leading_comments(gap: &str) -> Vec<String> {
+    let mut comments = vec![];
+    for line in gap.lines().rev() {
+        let line = line.trim();
+        match line.strip_prefix(';') {
+            Some(comment) => comments.push(comment.trim().to_string()),
+            None if line.is_empty() && comments.is_empty() => continue,
+            None => break,
+        }
+    }
+    comments.reverse();
+
+    comments
+}
+
+fn name_of(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Sym(sym) => Some(Expr::Sym(sym.clone()).to_string()),
+        _ => None,
+    }
+}
+
+fn names_in(expr: &Expr) -> Option<Vec<String>> {
+    match expr {
+        Expr::QExpr(exprs) => exprs.iter().map(|e| name_of(e)).collect(),
+        _ => None,
+    }
+}
+
+fn definitions_in(expr: &Expr) -> Vec<Definition> {
+    let exprs = match expr {
+        Expr::SExpr(exprs) => exprs,
+        _ => return vec![],
+    };
+
+    match &exprs[..] {
+        [oper, signature, rest @ ..] if is_identifier(oper, "फलन") => {
+            match names_in(signature).as_deref() {
+                Some([name, params @ ..]) => match function_parts(rest) {
+                    Some((docstring, body)) => vec![definition(
+                        name,
+                        Kind::Function(params.to_vec()),
+                        docstring,
+                        body,
+                    )],
+                    None => vec![],
+                },
+                _ => vec![],
+            }
+        }
+        [oper, names, values @ ..] if **oper == Expr::Sym(Symbol::QExprsOp(QExprsOp::Def)) => {
+            match names_in(names) {
+                Some(names) => names
+                    .iter()
+                    .zip(values.iter())
+                    .map(|(name, value)| match lambda_parts(value) {
+                        Some((params, docstring, body)) => {
+                            definition(name, Kind::Function(params), docstring, body)
+                        }
+                        None => definition(name, Kind::Value, None, value),
+                    })
+                    .collect(),
+                None => vec![],
+            }
+        }
+        _ => vec![],
+    }
+}
+
+fn is_identifier(expr: &Expr, name: &str) -> bool {
+    matches!(expr, Expr::Sym(Symbol::Identifier(ident)) if ident == name)
+}
+
+fn function_parts(rest: &[Box<Expr>]) -> Option<(Option<String>, &Expr)> {
+    match rest {
+        [body] => Some((None, body)),
+        [doc, body] => match &**doc {
+            Expr::Str(doc) => Some((Some(doc.clone()), body)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn lambda_parts(expr: &Expr) -> Option<(Vec<String>, Option<String>, &Expr)> {
+    match expr {
+        Expr::SExpr(exprs) => match &exprs[..] {
+            [oper, params, rest @ ..]
+                if **oper == Expr::Sym(Symbol::QExprsOp(QExprsOp::Lambda)) =>
+            {
+                let params = names_in(params)?;
+                let (docstring, body) = function_parts(rest)?;
+                Some((params, docstring, body))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn definition(name: &str, kind: Kind, docstring: Option<String>, body: &Expr) -> Definition {
+    let mut references = vec![];
+    collect_identifiers(body, &mut references);
+
+    let params = match &kind {
+        Kind::Function(params) => params.clone(),
+        Kind::Value => vec![],
+    };
+    references.retain(|r| r != name && !params.contains(r));
+
+    Definition {
+        name: name.to_string(),
+        kind,
+        docstring,
+        comments: vec![],
+        line: 0,
+        references,
+    }
+}
+
+fn collect_identifiers(expr: &Expr, identifiers: &mut Vec<String>) {
+    match expr {
+        Expr::Sym(Symbol::Identifier(ident)) if !identifiers.contains(ident) => {
+            identifiers.push(ident.clone());
+        }
+        Expr::SExpr(exprs) | Expr::QExpr(exprs) => {
+            for expr in exprs {
+                collect_identifiers(expr, identifiers);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn module_from_file(path: &Path) -> Result<Module, Error> {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let source = fs::read_to_string(path)
+        .map_err(|_| Error::ImportError(Expr::Str(path.display().to_string())))?;
+
+    Ok(Module {
+        name,
+        definitions: extract(&source)?,
+    })
+}
+
+struct Renderer<'a> {
+    format: Format,
+    owners: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(modules: &'a [Module], format: Format) -> Renderer<'a> {
+        let mut owners = HashMap::new();
+        for module in modules {
+            for definition in &module.definitions {
+                owners
+                    .entry(definition.name.as_str())
+                    .or_insert(module.name.as_str());
+            }
+        }
+
+        Renderer { format, owners }
+    }
+
+    fn page(&self, module: &str) -> String {
+        format!("{}.{}", module, self.format.extension())
+    }
+
+    fn code(&self, text: &str) -> String {
+        match self.format {
+            Format::Markdown => format!("`{}`", text),
+            Format::Html => format!("<code>{}</code>", escape(text)),
+        }
+    }
+
+    fn text(&self, text: &str) -> String {
+        match self.format {
+            Format::Markdown => text.to_string(),
+            Format::Html => escape(text),
+        }
+    }
+
+    fn link(&self, text: &str, target: &str) -> String {
+        match self.format {
+            Format::Markdown => format!("[{}]({})", text, target),
+            Format::Html => format!("<a href=\"{}\">{}</a>", escape(target), text),
+        }
+    }
+
+    fn link_to(&self, name: &str, from: &str) -> String {
+        match self.owners.get(name) {
+            Some(&module) if module == from => self.link(&self.code(name), &format!("#{}", name)),
+            Some(&module) => self.link(
+                &self.code(name),
+                &format!("{}#{}", self.page(module), name),
+            ),
+            None => self.code(name),
+        }
+    }
+
+    fn heading(&self, level: usize, text: &str, anchor: Option<&str>) -> String {
+        match (self.format, anchor) {
+            (Format::Markdown, Some(anchor)) => format!(
+                "<a id=\"{}\"></a>\n{} {}",
+                escape(anchor),
+                "#".repeat(level),
+                text
+            ),
+            (Format::Markdown, None) => format!("{} {}", "#".repeat(level), text),
+            (Format::Html, Some(anchor)) => {
+                format!("<h{0} id=\"{1}\">{2}</h{0}>", level, escape(anchor), text)
+            }
+            (Format::Html, None) => format!("<h{0}>{1}</h{0}>", level, text),
+        }
+    }
+
+    fn paragraph(&self, text: &str) -> String {
+        match self.format {
+            Format::Markdown => text.to_string(),
+            Format::Html => format!("<p>{}</p>", text),
+        }
+    }
+
+    fn list(&self, items: &[String]) -> String {
+        match self.format {
+            Format::Markdown => items
+                .iter()
+                .map(|item| format!("- {}", item))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            Format::Html => format!(
+                "<ul>\n{}\n</ul>",
+                items
+                    .iter()
+                    .map(|item| format!("<li>{}</li>", item))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ),
+        }
+    }
+
+    fn signature(&self, definition: &Definition) -> String {
+        let signature = match &definition.kind {
+            Kind::Function(params) => {
+                let mut parts = vec![definition.name.clone()];
+                parts.extend(params.iter().cloned());
+                format!("({})", parts.join(" "))
+            }
+            Kind::Value => definition.name.clone(),
+        };
+        match self.format {
+            Format::Markdown => format!("```\n{}\n```", signature),
+            Format::Html => format!("<pre><code>{}</code></pre>", escape(&signature)),
+        }
+    }
+
+    fn summary(&self, definition: &Definition) -> String {
+        match (&definition.docstring, definition.comments.first()) {
+            (Some(doc), _) => self.text(doc),
+            (None, Some(comment)) => self.text(comment),
+            (None, None) => String::new(),
+        }
+    }
+
+    fn document(&self, title: &str, blocks: Vec<String>) -> String {
+        match self.format {
+            Format::Markdown => format!("{}\n", blocks.join("\n\n")),
+            Format::Html => format!(
+                "<!DOCTYPE html>\n<html lang=\"ne\">\n<head>\n<meta charset=\"utf-8\">\n\
+                 <title>{}</title>\n</head>\n<body>\n{}\n</body>\n</html>\n",
+                escape(title),
+                blocks.join("\n")
+            ),
+        }
+    }
+
+    fn index(&self, modules: &[Module]) -> String {
+        let mut blocks = vec![self.heading(1, "कागजात", None)];
+
+        blocks.push(self.heading(2, "मोड्युलहरु", None));
+        blocks.push(
+            self.list(
+                &modules
+                    .iter()
+                    .map(|module| {
+                        format!(
+                            "{} ({} परिभाषाहरु)",
+                            self.link(&self.text(&module.name), &self.page(&module.name)),
+                            Sankhya(module.definitions.len() as i32)
+                        )
+                    })
+                    .collect::<Vec<String>>(),
+            ),
+        );
+
+        let mut all: Vec<(&Module, &Definition)> = modules
+            .iter()
+            .flat_map(|m| m.definitions.iter().map(move |d| (m, d)))
+            .collect();
+        all.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+
+        blocks.push(self.heading(2, "सबै परिभाषाहरु", None));
+        blocks.push(
+            self.list(
+                &all.iter()
+                    .map(|(module, definition)| {
+                        let link = self.link(
+                            &self.code(&definition.name),
+                            &format!("{}#{}", self.page(&module.name), definition.name),
+                        );
+                        match self.summary(definition) {
+                            summary if summary.is_empty() => link,
+                            summary => format!("{} — {}", link, summary),
+                        }
+                    })
+                    .collect::<Vec<String>>(),
+            ),
+        );
+
+        self.document("कागजात", blocks)
+    }
+
+    fn module(&self, module: &Module) -> String {
+        let mut blocks = vec![
+            self.heading(1, &self.text(&module.name), None),
+            self.paragraph(&self.link("अनुक्रमणिका", &self.page("index"))),
+        ];
+
+        for definition in &module.definitions {
+            blocks.push(self.heading(
+                2,
+                &self.code(&definition.name),
+                Some(&definition.name),
+            ));
+            blocks.push(self.signature(definition));
+
+            if let Some(doc) = &definition.docstring {
+                blocks.push(self.paragraph(&self.text(doc)));
+            }
+            if !definition.comments.is_empty() {
+                let comments = definition
+                    .comments
+                    .iter()
+                    .map(|c| self.text(c))
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                blocks.push(self.paragraph(&format!("टिप्पणी : {}", comments)));
+            }
+            if let Kind::Function(params) = &definition.kind {
+                if !params.is_empty() {
+                    let params = params
+                        .iter()
+                        .map(|p| self.code(p))
+                        .collect::<Vec<String>>()
+                        .join(", ");
+                    blocks.push(self.paragraph(&format!("बुँदाहरु : {}", params)));
+                }
+            }
+
+            let references: Vec<String> = definition
+                .references
+                .iter()
+                .filter(|r| self.owners.contains_key(r.as_str()))
+                .map(|r| self.link_to(r, &module.name))
+                .collect();
+            if !references.is_empty() {
+                blocks.push(self.paragraph(&format!("प्रयोग गरिएका : {}", references.join(", "))));
+            }
+
+            blocks.push(self.paragraph(&format!(
+                "परिभाषा : {}.अ, लाइन {}",
+                self.text(&module.name),
+                Sankhya(definition.line as i32)
+            )));
+        }
+
+        self.document(&module.name, blocks)
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn render(modules: &[Module], format: Format) -> Vec<(String, String)> {
+    let renderer = Renderer::new(modules, format);
+
+    let mut pages = vec![(renderer.page("index"), renderer.index(modules))];
+    for module in modules {
+        pages.push((renderer.page(&module.name), renderer.module(module)));
+    }
+
+    pages
+}
+
+pub fn write(modules: &[Module], format: Format, out_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(out_dir)?;
+
+    let mut written = vec![];
+    for (filename, contents) in render(modules, format) {
+        let path = out_dir.join(filename);
+        fs::write(&path, contents)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prelude() -> Module {
+        Module {
+            name: String::from("प्रस्तावना"),
+            definitions: extract(include_str!("प्रस्तावना.अ")).unwrap(),
+        }
+    }
+
+    fn find<'a>(module: &'a Module, name: &str) -> &'a Definition {
+        module
+            .definitions
+            .iter()
+            .find(|d| d.name == name)
+            .unwrap_or_else(|| panic!("{} not documented", name))
+    }
+
+    #[test]
+    fn test_extract_prelude() {
+        let module = prelude();
+
+        let chhanaut = find(&module, "छनौट");
+        assert_eq!(
+            chhanaut.kind,
+            Kind::Function(vec![String::from("ऽ"), String::from("छहरु")])
+        );
+        assert_eq!(chhanaut.comments, vec![String::from("छनौट - सेलेक्ट")]);
+        assert!(chhanaut.docstring.is_some());
+        assert!(chhanaut.references.contains(&String::from("प्रथम")));
+        assert!(!chhanaut.references.contains(&String::from("छहरु")));
+
+        let phalan = find(&module, "फलन");
+        assert_eq!(
+            phalan.kind,
+            Kind::Function(vec![
+                String::from("बुँदाहरु"),
+                String::from("ऽ"),
+                String::from("बाँकी")
+            ])
+        );
+
+        let karee = find(&module, "करी");
+        assert_eq!(karee.kind, Kind::Value);
+        assert_eq!(karee.references, vec![String::from("फुकाल")]);
+
+        assert!(find(&module, "द्वितिय").comments.is_empty());
+        assert_eq!(find(&module, "नील").line, 4);
+    }
+
+    #[test]
+    fn test_render_prelude() {
+        let modules = vec![prelude()];
+
+        let pages = render(&modules, Format::Markdown);
+        assert_eq!(pages[0].0, "index.md");
+        assert!(pages[0].1.contains("[`छनौट`](प्रस्तावना.md#छनौट)"));
+        assert!(pages[1].1.contains("<a id=\"छनौट\"></a>\n## `छनौट`"));
+        assert!(pages[1].1.contains("[`प्रथम`](#प्रथम)"));
+
+        let pages = render(&modules, Format::Html);
+        assert_eq!(pages[1].0, "प्रस्तावना.html");
+        assert!(pages[1]
+            .1
+            .contains("<h2 id=\"छनौट\"><code>छनौट</code></h2>"));
+    }
+}
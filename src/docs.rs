@@ -0,0 +1,550 @@
+use crate::types::{
+    BoolOp, Expr, ExprsOp, Function, ListOp, LoopOp, NumOp, QExprOp, QExprsOp, RefOp, SExprOp,
+    Symbol, TestOp, TimeOp, VectorOp,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Doc {
+    pub signature: &'static str,
+    pub description: &'static str,
+    pub examples: &'static [&'static str],
+}
+
+const fn doc(
+    signature: &'static str,
+    description: &'static str,
+    examples: &'static [&'static str],
+) -> Doc {
+    Doc {
+        signature,
+        description,
+        examples,
+    }
+}
+
+pub fn core_doc(symbol: &Symbol) -> Doc {
+    match symbol {
+        Symbol::ExprsOp(op) => match op {
+            ExprsOp::Equal => doc(
+                "(== क ख)",
+                "दुई मान बराबर भए सत्य, नत्र गलत फर्काउने",
+                &["(== १ १) ; बुलियन(सत्य)", "(== '(१ २) '(१ ३)) ; बुलियन(गलत)"],
+            ),
+            ExprsOp::NotEqual => doc(
+                "(!= क ख)",
+                "दुई मान बराबर नभए सत्य, नत्र गलत फर्काउने",
+                &["(!= १ २) ; बुलियन(सत्य)"],
+            ),
+            ExprsOp::If => doc(
+                "(यदि शर्त '(सत्य भए) '(गलत भए))",
+                "शर्त सत्य भए पहिलो, गलत भए दोस्रो क्यु-एक्सपर् बिस्तार गर्ने",
+                &["(यदि (> ३ २) '(\"ठुलो\") '(\"सानो\")) ; \"ठुलो\""],
+            ),
+            ExprsOp::Match => doc(
+                "(मिलाउ मान '(ढाँचा शर्त नतिजा) ...)",
+                "मानसँग मिल्ने पहिलो ढाँचाको नतिजा फर्काउने; ढाँचाका नामहरु नयाँ वातावरणमा बाँधिन्छन्, _ जुनसुकै मानसँग मिल्छ, (क ऽ बाँकी) ले शिर र बाँकी छुट्याउँछ, [क ख] जस्ता ढाँचा भेक्टरसँग मिल्छन्, शर्त ऐच्छिक हो",
+                &[
+                    "(मिलाउ '(१ २ ३) '(() \"खाली\") '((क ऽ बाँकी) बाँकी)) ; '(२ ३)",
+                    "(मिलाउ ५ '(न (< न ०) \"ऋण\") '(_ \"धन\")) ; \"धन\"",
+                ],
+            ),
+            ExprsOp::List => doc(
+                "(सुची क ख ...)",
+                "सबै तर्कहरुको मान निकालेर क्यु-एक्सपर् बनाउने",
+                &["(सुची १ (+ १ १) ३) ; '(१ २ ३)"],
+            ),
+            ExprsOp::Import => doc(
+                "(आयात मोड्युल)",
+                "आयात गर्ने फाइलकै फोल्डरको (REPL मा हालको) मोड्युल.अ पढेर वातावरणमा बिस्तार गर्ने",
+                &["(आयात उदाहरण)"],
+            ),
+            ExprsOp::Print => doc(
+                "(छाप क ख ...)",
+                "हरेक तर्कको मान छुट्टाछुट्टै लाइनमा छाप्ने",
+                &["(छाप \"नमस्ते\" (+ १ २))"],
+            ),
+            ExprsOp::Error => doc(
+                "(समस्या \"सन्देश\")",
+                "दिइएको सन्देशसहित समस्या (एरर) निकाल्ने",
+                &["(समस्या \"केही बिग्रियो\")"],
+            ),
+            ExprsOp::Help => doc(
+                "(मद्दत नाम)",
+                "फलनको प्रयोग, विवरण र उदाहरणहरु छाप्ने",
+                &["(मद्दत लहरै)", "(मद्दत +)"],
+            ),
+            ExprsOp::Exit => doc(
+                "(निकास कोड)",
+                "दिइएको निकास कोड (० देखि २५५ सम्म) सहित प्रोग्राम अन्त्य गर्ने",
+                &["(निकास ०)", "(निकास ३)"],
+            ),
+        },
+        Symbol::BoolOp(op) => match op {
+            BoolOp::Not => doc(
+                "(हैन क)",
+                "सत्यलाई गलत र गलतलाई सत्य बनाउने",
+                &["(हैन सत्य) ; बुलियन(गलत)"],
+            ),
+            BoolOp::Or => doc(
+                "(अथवा क ख ...)",
+                "तर्कहरु क्रमैसँग हेरेर पहिलो सत्य भेटिनेबित्तिकै सत्य फर्काउने, नत्र गलत",
+                &["(अथवा गलत (> ३ २)) ; बुलियन(सत्य)", "(अथवा सत्य (/ १ ०)) ; बुलियन(सत्य)"],
+            ),
+            BoolOp::And => doc(
+                "(साथै क ख ...)",
+                "तर्कहरु क्रमैसँग हेरेर पहिलो गलत भेटिनेबित्तिकै गलत फर्काउने, नत्र सत्य",
+                &["(साथै सत्य (> ३ २)) ; बुलियन(सत्य)", "(साथै गलत (/ १ ०)) ; बुलियन(गलत)"],
+            ),
+        },
+        Symbol::LoopOp(op) => match op {
+            LoopOp::While => doc(
+                "(जबसम्म '(शर्त) '(शरीर))",
+                "शर्त सत्य रहुन्जेल शरीर दोहोर्याउने, अन्तिम पटकको मान फर्काउने",
+                &["(= '(न) ०) (जबसम्म '(< न ३) '(= '(न) (+ न १))) ; ३"],
+            ),
+            LoopOp::ForEach => doc(
+                "(हरेक '(नाम) '(क ख ...) '(शरीर))",
+                "क्यु-एक्सपर्को हरेक बुँदा नामसँग बाँधेर शरीर दोहोर्याउने",
+                &["(हरेक '(क) '(१ २ ३) '(छाप (* २ क)))"],
+            ),
+            LoopOp::Count => doc(
+                "(गन्ती '(नाम) सुरु अन्त्य पाइला '(शरीर))",
+                "सुरुदेखि अन्त्यसम्म (अन्त्य बाहेक) पाइलाको फरकमा गन्दै शरीर दोहोर्याउने, पाइला ऐच्छिक हो",
+                &["(गन्ती '(इ) ० ३ '(छाप इ))", "(गन्ती '(इ) १० ० -५ '(छाप इ))"],
+            ),
+            LoopOp::Range => doc(
+                "(दायरा सुरु अन्त्य पाइला)",
+                "सुरुदेखि अन्त्यसम्म (अन्त्य बाहेक) पाइलाको फरकमा संख्याहरुको क्यु-एक्सपर् बनाउने, पाइला ऐच्छिक हो",
+                &["(दायरा ० ४) ; '(० १ २ ३)", "(दायरा ५ ० -२) ; '(५ ३ १)"],
+            ),
+            LoopOp::Break => doc(
+                "(रोक मान)",
+                "लुप त्यहीँ अन्त्य गरेर मान फर्काउने",
+                &["(हरेक '(क) '(१ २ ३) '(यदि (== क २) '((रोक क)) '(क))) ; २"],
+            ),
+            LoopOp::Continue => doc(
+                "(जारी मान)",
+                "यो पटकको शरीर त्यहीँ छोडेर लुपको अर्को पटकमा जाने",
+                &["(गन्ती '(इ) ० ४ '(यदि (== इ १) '((जारी इ)) '((छाप इ))))"],
+            ),
+        },
+        Symbol::VectorOp(op) => match op {
+            VectorOp::New => doc(
+                "(भेक्टर क ख ...)",
+                "सबै तर्कहरुको मान निकालेर भेक्टर बनाउने; [क ख ...] चाहिँ '(क ख ...) जस्तै उद्धृत हुन्छ, त्यसका बुँदाहरु बिस्तार हुँदैनन्",
+                &["(भेक्टर १ (+ १ १)) ; [१ २]", "[१ (+ १ १)] ; [१ (+ १ १)]"],
+            ),
+            VectorOp::Get => doc(
+                "(तान भेक्टर औँ)",
+                "भेक्टरको औँ (शुन्यबाट गनेर) बुँदा, जतिसुकै लामो भेक्टरमा पनि उत्तिकै छिटो",
+                &["(तान [१० २० ३०] १) ; २०"],
+            ),
+            VectorOp::Set => doc(
+                "(बदल भेक्टर औँ मान)",
+                "औँ बुँदा मान भएको नयाँ भेक्टर फर्काउने, पुरानो भेक्टर उस्तै रहन्छ",
+                &["(बदल [१ २ ३] ० ९) ; [९ २ ३]"],
+            ),
+            VectorOp::Push => doc(
+                "(थप भेक्टर मान ...)",
+                "अन्त्यमा मानहरु थपिएको नयाँ भेक्टर फर्काउने",
+                &["(थप [१ २] ३ ४) ; [१ २ ३ ४]"],
+            ),
+            VectorOp::Slice => doc(
+                "(टुक्रा भेक्टर सुरु अन्त्य)",
+                "सुरुदेखि अन्त्यसम्म (अन्त्य बाहेक) का बुँदाहरुको भेक्टर, अन्त्य नदिए भेक्टरको अन्त्यसम्म",
+                &["(टुक्रा [१ २ ३ ४] १ ३) ; [२ ३]", "(टुक्रा [१ २ ३ ४] २) ; [३ ४]"],
+            ),
+            VectorOp::FromQExpr => doc(
+                "(भेक्टरमा '(क ख ...))",
+                "क्यु-एक्सपर्का बुँदाहरुबाट भेक्टर बनाउने",
+                &["(भेक्टरमा '(१ २)) ; [१ २]"],
+            ),
+            VectorOp::ToQExpr => doc(
+                "(सुचीमा भेक्टर)",
+                "भेक्टरका बुँदाहरुबाट क्यु-एक्सपर् बनाउने",
+                &["(सुचीमा [१ २]) ; '(१ २)"],
+            ),
+        },
+        Symbol::TimeOp(op) => match op {
+            TimeOp::Now => doc(
+                "(अहिले अवधि)",
+                "अहिलेदेखि अवधि (सेकेन्ड वा '(दिन सेकेन्ड)) पछिको समय; (अहिले) ले फलन आफैँ फर्काउँछ, त्यसैले अहिलेकै समयका लागि (अहिले ०)",
+                &["(अहिले ०)", "(अहिले ३६००) ; एक घण्टापछि", "(अहिले '(१ ०)) ; भोलि यही बेला"],
+            ),
+            TimeOp::FromBs => doc(
+                "(मिति वर्ष महिना गते घण्टा मिनेट सेकेन्ड)",
+                "विक्रम संवतको मिति (२००० देखि २०९० सम्म) र नेपाल समयबाट समय बनाउने, घण्टा मिनेट सेकेन्ड नदिए दिनको सुरु",
+                &["(मिति २०८२ १ १) ; समय(२०८२ बैशाख ०१, सोमबार ००:००:००)"],
+            ),
+            TimeOp::ToBs => doc(
+                "(मितिभाग समय)",
+                "समयको विक्रम संवत मिति र नेपाल समय, बार आइतबारलाई १ मानेर",
+                &["(मितिभाग (मिति २०८२ १ १)) ; '(२०८२ १ १ ० ० ० २)"],
+            ),
+            TimeOp::FromGregorian => doc(
+                "(इस्वी वर्ष महिना गते घण्टा मिनेट सेकेन्ड)",
+                "इस्वी संवतको मिति र नेपाल समयबाट समय बनाउने",
+                &["(इस्वी २०२५ ४ १४) ; समय(२०८२ बैशाख ०१, सोमबार ००:००:००)"],
+            ),
+            TimeOp::ToGregorian => doc(
+                "(इस्वीभाग समय)",
+                "समयको इस्वी संवत मिति र नेपाल समय, बार आइतबारलाई १ मानेर",
+                &["(इस्वीभाग (मिति २०८२ १ १)) ; '(२०२५ ४ १४ ० ० ० २)"],
+            ),
+            TimeOp::Add => doc(
+                "(समयथप समय सेकेन्ड)",
+                "समयभन्दा सेकेन्ड पछिको समय, सेकेन्ड ऋण भए अगाडिको; सेकेन्डको सट्टा समयअन्तरले दिने '(दिन सेकेन्ड) पनि दिन मिल्छ",
+                &["(समयथप (मिति २०८२ १ १) ८६४००) ; समय(२०८२ बैशाख ०२, मंगलबार ००:००:००)"],
+            ),
+            TimeOp::Diff => doc(
+                "(समयअन्तर क ख)",
+                "ख देखि क सम्मको समय '(दिन सेकेन्ड) को रुपमा, पूरा दिनहरु र बाँकी सेकेन्डहरु, क पहिले भए दुवै ऋण",
+                &["(समयअन्तर (मिति २०८२ १ २ ६ ० ०) (मिति २०८२ १ १)) ; '(१ २१६००)"],
+            ),
+            TimeOp::Format => doc(
+                "(समयलेख समय ढाँचा)",
+                "समयलाई ढाँचामा लेख्ने: %Y वर्ष, %m महिनाको अङ्क, %B महिनाको नाम, %d गते, %A बार, %H घण्टा, %M मिनेट, %S सेकेन्ड",
+                &["(समयलेख (मिति २०८२ १ १) \"%Y %B %d, %A\") ; \"२०८२ बैशाख ०१, सोमबार\""],
+            ),
+        },
+        Symbol::ListOp(op) => match op {
+            ListOp::Map => doc(
+                "(लहरै फ कहरु)",
+                "कहरुको हरेक बुँदामा फ लगाएर नयाँ सुची बनाउने",
+                &["(लहरै (\\ '(क) '(* क २)) '(१ २ ३)) ; '(२ ४ ६)"],
+            ),
+            ListOp::Filter => doc(
+                "(छान फ कहरु)",
+                "फ सत्य फर्काउने बुँदाहरु मात्र छान्ने",
+                &["(छान (\\ '(क) '(> क १)) '(१ २ ३)) ; '(२ ३)"],
+            ),
+            ListOp::Fold => doc(
+                "(पट्याउ फ अ कहरु)",
+                "सुरुको मान अबाट थालेर कहरुलाई फले बायाँबाट पट्याउने",
+                &["(पट्याउ + ० '(१ २ ३)) ; ६"],
+            ),
+            ListOp::Length => doc(
+                "(लम्बाई कहरु)",
+                "कहरु (सुची वा भेक्टर) मा भएका बुँदाहरुको संख्या",
+                &["(लम्बाई '(१ २ ३)) ; ३", "(लम्बाई [१ २]) ; २"],
+            ),
+            ListOp::Nth => doc(
+                "(औँ अ कहरु)",
+                "कहरुको अ औँ (शुन्यबाट गनेर) बुँदा",
+                &["(औँ १ '(१० २० ३०)) ; २०"],
+            ),
+            ListOp::Take => doc(
+                "(लेउ अ कहरु)",
+                "कहरुको सुरुका अ वटा बुँदाहरु",
+                &["(लेउ २ '(१ २ ३)) ; '(१ २)"],
+            ),
+            ListOp::Drop => doc(
+                "(छोड अ कहरु)",
+                "कहरुको सुरुका अ वटा बुँदाहरु छोडेर बाँकी",
+                &["(छोड २ '(१ २ ३)) ; '(३)"],
+            ),
+            ListOp::Elem => doc(
+                "(मा अ कहरु)",
+                "अ कहरुमा छ भने सत्य फर्काउने",
+                &["(मा २ '(१ २ ३)) ; सत्य"],
+            ),
+            ListOp::Last => doc(
+                "(अन्तिम कहरु)",
+                "कहरुको अन्तिम बुँदा",
+                &["(अन्तिम '(१ २ ३)) ; ३"],
+            ),
+            ListOp::Sort => doc(
+                "(क्रमबद्ध कहरु कुञ्जी तुलना)",
+                "कहरुलाई सानोदेखि ठूलोसम्म मिलाउने, बराबर बुँदाहरुको क्रम उस्तै रहन्छ; कुञ्जी दिए हरेक बुँदाको (कुञ्जी बुँदा) अनुसार, तुलना दिए (तुलना क ख) सत्य हुँदा क ख भन्दा अगाडि",
+                &[
+                    "(क्रमबद्ध '(३ १ २)) ; '(१ २ ३)",
+                    "(क्रमबद्ध '(\"ख\" \"क\")) ; '(\"क\" \"ख\")",
+                    "(क्रमबद्ध '(१ ३ २) नील >) ; '(३ २ १)",
+                ],
+            ),
+            ListOp::Reverse => doc(
+                "(उल्टाउ कहरु)",
+                "कहरुलाई उल्टो क्रममा राख्ने",
+                &["(उल्टाउ '(१ २ ३)) ; '(३ २ १)"],
+            ),
+            ListOp::Zip => doc(
+                "(जोडी कहरु खहरु ...)",
+                "सुचीहरुका उही स्थानका बुँदाहरुको जोडी बनाउने, सबैभन्दा छोटो सुचीसम्म",
+                &["(जोडी '(१ २) '(क ख)) ; '((१ क) (२ ख))"],
+            ),
+            ListOp::Enumerate => doc(
+                "(क्रमाङ्क कहरु)",
+                "हरेक बुँदालाई (शुन्यबाट गनेर) त्यसको स्थानसँग जोड्ने",
+                &["(क्रमाङ्क '(क ख)) ; '((० क) (१ ख))"],
+            ),
+            ListOp::Any => doc(
+                "(कुनै फ कहरु)",
+                "कुनै एउटा बुँदाका लागि फ सत्य भए सत्य, त्यहीँ रोकिने",
+                &["(कुनै (\\ '(क) '(> क २)) '(१ २ ३)) ; सत्य"],
+            ),
+            ListOp::All => doc(
+                "(सबै फ कहरु)",
+                "सबै बुँदाका लागि फ सत्य भए सत्य, पहिलो गलतमै रोकिने",
+                &["(सबै (\\ '(क) '(> क २)) '(१ २ ३)) ; गलत"],
+            ),
+            ListOp::FlatMap => doc(
+                "(लहरैएकत्र फ कहरु)",
+                "हरेक बुँदामा फ लगाएर आएका सुचीहरुलाई एकत्र गर्ने",
+                &["(लहरैएकत्र (\\ '(क) '(सुची क क)) '(१ २)) ; '(१ १ २ २)"],
+            ),
+        },
+        Symbol::RefOp(op) => match op {
+            RefOp::New => doc(
+                "(सन्दर्भ मान)",
+                "मान राखिएको नयाँ सन्दर्भ (परिवर्तन गर्न मिल्ने खोल) बनाउने; दुई सन्दर्भ एउटै खोल भए मात्र बराबर हुन्छन्",
+                &["(नामक '(गन्ने) (सन्दर्भ ०))", "(== (सन्दर्भ १) (सन्दर्भ १)) ; बुलियन(गलत)"],
+            ),
+            RefOp::Get => doc(
+                "(हेर सन्दर्भ)",
+                "सन्दर्भमा अहिले भएको मान फर्काउने",
+                &["(हेर (सन्दर्भ ५)) ; ५"],
+            ),
+            RefOp::Set => doc(
+                "(राख सन्दर्भ मान)",
+                "सन्दर्भमा नयाँ मान राख्ने र त्यही मान फर्काउने",
+                &["(राख गन्ने १०) ; १०"],
+            ),
+            RefOp::Swap => doc(
+                "(फेर सन्दर्भ फ तर्क ...)",
+                "सन्दर्भको मानलाई (फ मान तर्क ...) ले बदल्ने र नयाँ मान फर्काउने",
+                &["(फेर गन्ने + १) ; ११", "(फेर (सन्दर्भ '(१)) एकत्र '(२)) ; '(१ २)"],
+            ),
+        },
+        Symbol::NumOp(op) => match op {
+            NumOp::Add => doc(
+                "(+ क ख ...)",
+                "संख्याहरु जोड्ने",
+                &["(+ १ २ ३) ; ६"],
+            ),
+            NumOp::Subtract => doc(
+                "(- क ख ...)",
+                "पहिलो संख्याबाट बाँकी संख्याहरु घटाउने",
+                &["(- १० ३ २) ; ५"],
+            ),
+            NumOp::Multiply => doc(
+                "(* क ख ...)",
+                "संख्याहरु गुणन गर्ने",
+                &["(* २ ३ ४) ; २४"],
+            ),
+            NumOp::Divide => doc(
+                "(/ क ख ...)",
+                "पहिलो संख्यालाई बाँकी संख्याहरुले भाग गर्ने (शुन्यले भाग गर्न मिल्दैन)",
+                &["(/ २० २ ५) ; २"],
+            ),
+            NumOp::GreaterThan => doc(
+                "(> क ख)",
+                "क ख भन्दा ठुलो भए सत्य फर्काउने; संख्या, शब्द, बुलियन र सुचीहरु सबै तुलना गर्न मिल्छ",
+                &["(> ३ २) ; बुलियन(सत्य)"],
+            ),
+            NumOp::GreaterThanOrEqual => doc(
+                "(>= क ख)",
+                "क ख भन्दा ठुलो अथवा बराबर भए सत्य फर्काउने",
+                &["(>= २ २) ; बुलियन(सत्य)"],
+            ),
+            NumOp::LessThan => doc(
+                "(< क ख)",
+                "क ख भन्दा सानो भए सत्य फर्काउने",
+                &["(< २ ३) ; बुलियन(सत्य)", "(< \"क\" \"ख\") ; बुलियन(सत्य)"],
+            ),
+            NumOp::LessThanOrEqual => doc(
+                "(<= क ख)",
+                "क ख भन्दा सानो अथवा बराबर भए सत्य फर्काउने",
+                &["(<= ३ २) ; बुलियन(गलत)"],
+            ),
+        },
+        Symbol::QExprOp(op) => match op {
+            QExprOp::Head => doc(
+                "(शिर '(क ख ...))",
+                "क्यु-एक्सपर्को पहिलो बुँदा मात्र भएको क्यु-एक्सपर् फर्काउने",
+                &["(शिर '(१ २ ३)) ; '(१)"],
+            ),
+            QExprOp::Tail => doc(
+                "(पुच्छर '(क ख ...))",
+                "पहिलो बुँदा हटाएर बाँकी क्यु-एक्सपर् फर्काउने",
+                &["(पुच्छर '(१ २ ३)) ; '(२ ३)"],
+            ),
+            QExprOp::Len => doc(
+                "(वटा '(क ख ...))",
+                "क्यु-एक्सपर् वा भेक्टरमा भएका बुँदाहरुको संख्या फर्काउने",
+                &["(वटा '(१ २ ३)) ; ३", "(वटा [१ २]) ; २"],
+            ),
+            QExprOp::Eval => doc(
+                "(बिस्तार '(फ क ...))",
+                "क्यु-एक्सपर्लाई एस्-एक्सपर्को रुपमा बिस्तार गर्ने",
+                &["(बिस्तार '(+ १ २)) ; ३"],
+            ),
+            QExprOp::Trace => doc(
+                "(ट्रेस '(फ क ...) '(नाम ...))",
+                "क्यु-एक्सपर् बिस्तार गर्दा हरेक फलनको प्रयोग, तर्कहरु र नतिजा stderr मा छाप्ने, दोस्रो क्यु-एक्सपर् दिए ती फलनहरु मात्र",
+                &["(ट्रेस '(लहरै दोब्बर '(१ २))) ; '(२ ४)", "(ट्रेस '(फिब ५) '(फिब))"],
+            ),
+        },
+        Symbol::QExprsOp(op) => match op {
+            QExprsOp::Cons => doc(
+                "(निर्माण क '(ख ...))",
+                "क्यु-एक्सपर्को सुरुमा नयाँ बुँदा थप्ने",
+                &["(निर्माण १ '(२ ३)) ; '(१ २ ३)"],
+            ),
+            QExprsOp::Join => doc(
+                "(एकत्र '(क ...) '(ख ...) ...)",
+                "क्यु-एक्सपर्हरु जोडेर एउटै बनाउने",
+                &["(एकत्र '(१) '(२ ३)) ; '(१ २ ३)"],
+            ),
+            QExprsOp::Def => doc(
+                "(नामक '(नाम ...) मान ...)",
+                "नामहरुलाई मानहरु ग्लोबल वातावरणमा दिने",
+                &["(नामक '(क ख) १ २)"],
+            ),
+            QExprsOp::Put => doc(
+                "(= '(नाम ...) मान ...)",
+                "नामहरुलाई मानहरु हालको (लोकल) वातावरणमा दिने",
+                &["(= '(क) १)"],
+            ),
+            QExprsOp::Lambda => doc(
+                "(\\ '(बुँदाहरु ...) \"विवरण\" '(शरीर))",
+                "ल्याम्बडा फलन बनाउने, विवरण (डकस्ट्रिङ) ऐच्छिक हो",
+                &["((\\ '(क) '(* २ क)) ४) ; ८"],
+            ),
+            QExprsOp::Let => doc(
+                "(स्थानीय '((नाम मान) ...) '(शरीर))",
+                "मानहरु बाहिरकै वातावरणमा निकालेर नयाँ वातावरणमा नामसँग बाँध्ने अनि त्यहाँ शरीर बिस्तार गर्ने, शरीरले फर्काएको फलनले यी नामहरु साथै लैजान्छ",
+                &[
+                    "(स्थानीय '((क १) (ख २)) '(+ क ख)) ; ३",
+                    "((स्थानीय '((क ५)) '(\\ '(ख) '(+ क ख))) १) ; ६",
+                ],
+            ),
+            QExprsOp::LetRec => doc(
+                "(पुनःस्थानीय '((नाम मान) ...) '(शरीर))",
+                "स्थानीय जस्तै, तर मानहरु नयाँ वातावरणमै क्रमैसँग निकाल्ने, त्यसैले एकअर्कालाई बोलाउने सहायक फलनहरु बनाउन मिल्छ",
+                &["(पुनःस्थानीय '((दोब्बर (\\ '(क) '(* २ क))) (चार (दोब्बर २))) '(दोब्बर चार)) ; ८"],
+            ),
+        },
+        Symbol::SExprOp(op) => match op {
+            SExprOp::PrintEnv => doc(
+                "(वातावरण)",
+                "हालको वातावरण छाप्ने",
+                &["(वातावरण)"],
+            ),
+        },
+        Symbol::TestOp(op) => match op {
+            TestOp::Assert => doc(
+                "(दाबी शर्त)",
+                "शर्त सत्य नभए परीक्षण असफल गराउने",
+                &["(दाबी (> ३ २))"],
+            ),
+            TestOp::AssertEqual => doc(
+                "(दाबीबराबर अपेक्षित वास्तविक)",
+                "दुई मान बराबर नभए परीक्षण असफल गराउने",
+                &["(दाबीबराबर ८ (फिब ६))"],
+            ),
+            TestOp::AssertThrows => doc(
+                "(दाबीसमस्या '(एक्सपर्) \"सन्देश\")",
+                "एक्सपर् बिस्तार गर्दा समस्या ननिस्किए, वा सन्देश दिइएकोमा समस्याको सन्देश नमिले परीक्षण असफल गराउने",
+                &["(दाबीसमस्या '(/ १ ०))", "(दाबीसमस्या '(समस्या \"हो\") \"हो\")"],
+            ),
+            TestOp::Test => doc(
+                "(परीक्षण \"नाम\" '(एक्सपर्) ...)",
+                "नाम दिइएको परीक्षण, हरेक क्यु-एक्सपर् लहरै बिस्तार गर्ने",
+                &["(परीक्षण \"जोड\" '(दाबीबराबर ३ (+ १ २)))"],
+            ),
+        },
+        Symbol::Identifier(_) => doc("", "विवरण उपलब्ध छैन", &[]),
+    }
+}
+
+pub fn signature(name: &Symbol, fun: &Function) -> String {
+    match fun {
+        Function::Core(_, _, doc) => doc.signature.to_string(),
+        Function::Lambda(formals, _, _, _) => {
+            let mut parts = vec![Expr::Sym(name.clone()).to_string()];
+            parts.extend(formals.iter().map(|f| Expr::Sym(f.clone()).to_string()));
+            format!("({})", parts.join(" "))
+        }
+    }
+}
+
+pub fn render(name: &Symbol, fun: &Function) -> String {
+    let mut lines = vec![format!("प्रयोग : {}", signature(name, fun))];
+
+    match fun {
+        Function::Core(_, _, doc) => {
+            lines.push(format!("विवरण : {}", doc.description));
+            if !doc.examples.is_empty() {
+                lines.push(String::from("उदाहरण :"));
+                lines.extend(doc.examples.iter().map(|e| format!("  {}", e)));
+            }
+        }
+        Function::Lambda(_, _, _, Some(docstring)) => {
+            lines.push(format!("विवरण : {}", docstring));
+        }
+        Function::Lambda(_, _, _, None) => {
+            lines.push(String::from("विवरण : विवरण उपलब्ध छैन"));
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core;
+    use crate::environment::{self, Env};
+
+    fn lookup_fun(env: &Env, name: &str) -> Function {
+        match environment::lookup(env, &Symbol::Identifier(name.to_string())) {
+            Ok(Expr::Fun(fun)) => fun,
+            x => panic!("{} is not a function : {:?}", name, x),
+        }
+    }
+
+    #[test]
+    fn test_core_fns_have_docs() {
+        let env = environment::new_with_prelude().unwrap();
+        for (sym, expr) in environment::bindings(&env) {
+            if let Expr::Fun(Function::Core(_, _, doc)) = expr {
+                assert!(!doc.signature.is_empty(), "{:?} has no signature", sym);
+                assert!(!doc.examples.is_empty(), "{:?} has no examples", sym);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lambda_docstring() {
+        let mut env = environment::new_with_prelude().unwrap();
+        core::parse_and_eval_str(&mut env, "(फलन '(दोब्बर अ) \"अलाई दोब्बर गर्ने\" '(* २ अ))")
+            .unwrap();
+        core::parse_and_eval_str(&mut env, "(फलन '(तेब्बर अ) '(* ३ अ))").unwrap();
+
+        let name = Symbol::Identifier("दोब्बर".to_string());
+        assert_eq!(
+            render(&name, &lookup_fun(&env, "दोब्बर")),
+            "प्रयोग : (दोब्बर अ)\nविवरण : अलाई दोब्बर गर्ने"
+        );
+        assert_eq!(
+            core::parse_and_eval_str(&mut env, "(दोब्बर ४) (तेब्बर ४)"),
+            Ok(Expr::QExpr(vec![
+                Box::new(Expr::Num(crate::ntypes::Sankhya(8))),
+                Box::new(Expr::Num(crate::ntypes::Sankhya(12))),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_prelude_docstrings() {
+        let env = environment::new_with_prelude().unwrap();
+        match lookup_fun(&env, "फोर") {
+            Function::Lambda(_, _, _, doc) => assert!(doc.is_some()),
+            x => panic!("expected a lambda : {:?}", x),
+        }
+        match lookup_fun(&env, "लहरै") {
+            Function::Core(_, _, doc) => assert_eq!(doc.signature, "(लहरै फ कहरु)"),
+            x => panic!("expected a core function : {:?}", x),
+        }
+    }
+}
@@ -1,19 +1,44 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
 use crate::core::{self, CoreFn};
-use crate::types::{Error, Expr, ExprsOp, Function, NumOp, QExprOp, QExprsOp, SExprOp, Symbol};
+use crate::docs;
+use crate::types::{
+    BoolOp, Error, Expr, ExprsOp, Function, ListOp, LoopOp, NumOp, QExprOp, QExprsOp, RefOp,
+    SExprOp, Symbol, TestOp, TimeOp, VectorOp,
+};
 
 #[derive(Debug)]
 pub struct EnvCtx {
     data: RefCell<HashMap<Symbol, Expr>>,
+    /// Every name ever bound outside the root, shared by the whole tree. Any
+    /// other name can only be in the root, so looking it up skips the chain
+    /// of callers, however deep the recursion.
+    locals: Rc<RefCell<HashSet<Symbol>>>,
+    /// `None` for the root itself.
+    root: Option<Env>,
     pub parent: Option<Env>,
 }
 
 pub type Env = Rc<EnvCtx>;
 
+pub(crate) const PRELUDE: &str = include_str!("प्रस्तावना.अ");
+
 pub fn new(hmap: HashMap<Symbol, Expr>, parent: Option<Env>) -> Env {
+    let (locals, root) = match &parent {
+        None => (Rc::new(RefCell::new(HashSet::new())), None),
+        Some(parent) => {
+            parent.locals.borrow_mut().extend(hmap.keys().cloned());
+            (Rc::clone(&parent.locals), Some(Rc::clone(root(parent))))
+        }
+    };
     Rc::new(EnvCtx {
         data: RefCell::new(hmap),
+        locals,
+        root,
         parent,
     })
 }
@@ -21,14 +46,22 @@ pub fn new(hmap: HashMap<Symbol, Expr>, parent: Option<Env>) -> Env {
 pub fn lookup(env: &Env, symbol: &Symbol) -> Result<Expr, Error> {
     match env.data.borrow().get(symbol) {
         Some(expr) => Ok(expr.clone()),
-        None => match &env.parent {
-            None => Err(Error::UnboundSymbol(symbol.clone())),
-            Some(parent) => lookup(&parent, symbol),
+        None => match (&env.parent, &env.root) {
+            (None, _) => Err(Error::UnboundSymbol(symbol.clone())),
+            (Some(_), Some(root)) if !env.locals.borrow().contains(symbol) => lookup(root, symbol),
+            (Some(parent), _) => lookup(parent, symbol),
         },
     }
 }
 
+pub fn bindings(env: &Env) -> HashMap<Symbol, Expr> {
+    env.data.borrow().clone()
+}
+
 fn insert(env: &Env, symbol: &Symbol, expr: &Expr) {
+    if env.parent.is_some() {
+        env.locals.borrow_mut().insert(symbol.clone());
+    }
     env.data.borrow_mut().insert(symbol.clone(), expr.clone());
 }
 
@@ -37,10 +70,7 @@ pub fn bind_local_symbol(env: &Env, symbol: &Symbol, expr: &Expr) {
 }
 
 fn root(env: &Env) -> &Env {
-    match &env.parent {
-        None => env,
-        Some(parenv) => root(&parenv),
-    }
+    env.root.as_ref().unwrap_or(env)
 }
 
 pub fn bind_global_symbol(env: &Env, symbol: &Symbol, expr: &Expr) {
@@ -51,14 +81,28 @@ pub fn bind_global_symbol(env: &Env, symbol: &Symbol, expr: &Expr) {
 }
 
 fn bind_global_core_fn(env: &Env, symbol: Symbol, func: CoreFn) {
+    let doc = docs::core_doc(&symbol);
     root(env)
         .data
         .borrow_mut()
-        .insert(symbol.clone(), Expr::Fun(Function::Core(symbol, func)));
+        .insert(symbol.clone(), Expr::Fun(Function::Core(symbol, func, doc)));
+}
+
+/// Binds a core function to its name as an identifier, like the prelude's
+/// functions, instead of to a keyword.
+fn bind_global_list_fn(env: &Env, op: ListOp, func: CoreFn) {
+    let name = Symbol::Identifier(op.to_string());
+    let symbol = Symbol::ListOp(op);
+    let doc = docs::core_doc(&symbol);
+    root(env)
+        .data
+        .borrow_mut()
+        .insert(name, Expr::Fun(Function::Core(symbol, func, doc)));
 }
 
 pub fn load_core_fns(env: &Env) {
     bind_global_core_fn(env, Symbol::ExprsOp(ExprsOp::If), core::exprs_if);
+    bind_global_core_fn(env, Symbol::ExprsOp(ExprsOp::Match), core::exprs_match);
     bind_global_core_fn(env, Symbol::ExprsOp(ExprsOp::List), core::exprs_list);
     bind_global_core_fn(env, Symbol::ExprsOp(ExprsOp::Equal), core::exprs_equal);
     bind_global_core_fn(
@@ -69,6 +113,68 @@ pub fn load_core_fns(env: &Env) {
     bind_global_core_fn(env, Symbol::ExprsOp(ExprsOp::Import), core::exprs_import);
     bind_global_core_fn(env, Symbol::ExprsOp(ExprsOp::Print), core::exprs_print);
     bind_global_core_fn(env, Symbol::ExprsOp(ExprsOp::Error), core::exprs_error);
+    bind_global_core_fn(env, Symbol::ExprsOp(ExprsOp::Help), core::exprs_help);
+    bind_global_core_fn(env, Symbol::ExprsOp(ExprsOp::Exit), core::exprs_exit);
+    bind_global_core_fn(env, Symbol::BoolOp(BoolOp::Not), core::bool_not);
+    bind_global_core_fn(env, Symbol::BoolOp(BoolOp::Or), core::bools_or);
+    bind_global_core_fn(env, Symbol::BoolOp(BoolOp::And), core::bools_and);
+    bind_global_core_fn(env, Symbol::LoopOp(LoopOp::While), core::loop_while);
+    bind_global_core_fn(env, Symbol::LoopOp(LoopOp::ForEach), core::loop_for_each);
+    bind_global_core_fn(env, Symbol::LoopOp(LoopOp::Count), core::loop_count);
+    bind_global_core_fn(env, Symbol::LoopOp(LoopOp::Range), core::loop_range);
+    bind_global_core_fn(env, Symbol::LoopOp(LoopOp::Break), core::loop_break);
+    bind_global_core_fn(env, Symbol::LoopOp(LoopOp::Continue), core::loop_continue);
+    bind_global_core_fn(env, Symbol::VectorOp(VectorOp::New), core::vector_new);
+    bind_global_core_fn(env, Symbol::VectorOp(VectorOp::Get), core::vector_get);
+    bind_global_core_fn(env, Symbol::VectorOp(VectorOp::Set), core::vector_set);
+    bind_global_core_fn(env, Symbol::VectorOp(VectorOp::Push), core::vector_push);
+    bind_global_core_fn(env, Symbol::VectorOp(VectorOp::Slice), core::vector_slice);
+    bind_global_core_fn(
+        env,
+        Symbol::VectorOp(VectorOp::FromQExpr),
+        core::vector_from_qexpr,
+    );
+    bind_global_core_fn(
+        env,
+        Symbol::VectorOp(VectorOp::ToQExpr),
+        core::vector_to_qexpr,
+    );
+    bind_global_core_fn(env, Symbol::TimeOp(TimeOp::Now), core::time_now);
+    bind_global_core_fn(env, Symbol::TimeOp(TimeOp::FromBs), core::time_from_bs);
+    bind_global_core_fn(env, Symbol::TimeOp(TimeOp::ToBs), core::time_to_bs);
+    bind_global_core_fn(
+        env,
+        Symbol::TimeOp(TimeOp::FromGregorian),
+        core::time_from_gregorian,
+    );
+    bind_global_core_fn(
+        env,
+        Symbol::TimeOp(TimeOp::ToGregorian),
+        core::time_to_gregorian,
+    );
+    bind_global_core_fn(env, Symbol::TimeOp(TimeOp::Add), core::time_add);
+    bind_global_core_fn(env, Symbol::TimeOp(TimeOp::Diff), core::time_diff);
+    bind_global_core_fn(env, Symbol::TimeOp(TimeOp::Format), core::time_format);
+    bind_global_list_fn(env, ListOp::Map, core::list_map);
+    bind_global_list_fn(env, ListOp::Filter, core::list_filter);
+    bind_global_list_fn(env, ListOp::Fold, core::list_fold);
+    bind_global_list_fn(env, ListOp::Length, core::list_length);
+    bind_global_list_fn(env, ListOp::Nth, core::list_nth);
+    bind_global_list_fn(env, ListOp::Take, core::list_take);
+    bind_global_list_fn(env, ListOp::Drop, core::list_drop);
+    bind_global_list_fn(env, ListOp::Elem, core::list_elem);
+    bind_global_list_fn(env, ListOp::Last, core::list_last);
+    bind_global_list_fn(env, ListOp::Sort, core::list_sort);
+    bind_global_list_fn(env, ListOp::Reverse, core::list_reverse);
+    bind_global_list_fn(env, ListOp::Zip, core::list_zip);
+    bind_global_list_fn(env, ListOp::Enumerate, core::list_enumerate);
+    bind_global_list_fn(env, ListOp::Any, core::list_any);
+    bind_global_list_fn(env, ListOp::All, core::list_all);
+    bind_global_list_fn(env, ListOp::FlatMap, core::list_flat_map);
+    bind_global_core_fn(env, Symbol::RefOp(RefOp::New), core::ref_new);
+    bind_global_core_fn(env, Symbol::RefOp(RefOp::Get), core::ref_get);
+    bind_global_core_fn(env, Symbol::RefOp(RefOp::Set), core::ref_set);
+    bind_global_core_fn(env, Symbol::RefOp(RefOp::Swap), core::ref_swap);
     bind_global_core_fn(env, Symbol::NumOp(NumOp::Add), core::nums_add);
     bind_global_core_fn(env, Symbol::NumOp(NumOp::Subtract), core::nums_subtract);
     bind_global_core_fn(env, Symbol::NumOp(NumOp::Multiply), core::nums_multiply);
@@ -86,22 +192,55 @@ pub fn load_core_fns(env: &Env) {
     bind_global_core_fn(env, Symbol::QExprOp(QExprOp::Tail), core::qexpr_tail);
     bind_global_core_fn(env, Symbol::QExprOp(QExprOp::Len), core::qexpr_len);
     bind_global_core_fn(env, Symbol::QExprOp(QExprOp::Eval), core::qexpr_eval);
+    bind_global_core_fn(env, Symbol::QExprOp(QExprOp::Trace), core::qexpr_trace);
     bind_global_core_fn(env, Symbol::QExprsOp(QExprsOp::Cons), core::qexprs_cons);
     bind_global_core_fn(env, Symbol::QExprsOp(QExprsOp::Join), core::qexprs_join);
     bind_global_core_fn(env, Symbol::QExprsOp(QExprsOp::Def), core::qexprs_def);
     bind_global_core_fn(env, Symbol::QExprsOp(QExprsOp::Put), core::qexprs_put);
+    bind_global_core_fn(env, Symbol::QExprsOp(QExprsOp::Let), core::qexprs_let);
+    bind_global_core_fn(env, Symbol::QExprsOp(QExprsOp::LetRec), core::qexprs_letrec);
     bind_global_core_fn(env, Symbol::QExprsOp(QExprsOp::Lambda), core::qexprs_lambda);
     bind_global_core_fn(
         env,
         Symbol::SExprOp(SExprOp::PrintEnv),
         core::sexpr_printenv,
     );
+    bind_global_core_fn(env, Symbol::TestOp(TestOp::Assert), core::test_assert);
+    bind_global_core_fn(
+        env,
+        Symbol::TestOp(TestOp::AssertEqual),
+        core::test_assert_equal,
+    );
+    bind_global_core_fn(
+        env,
+        Symbol::TestOp(TestOp::AssertThrows),
+        core::test_assert_throws,
+    );
+    bind_global_core_fn(env, Symbol::TestOp(TestOp::Test), core::test_define);
+}
+
+pub fn load_prelude(env: &mut Env) -> Result<Expr, Error> {
+    core::parse_and_eval_str(env, PRELUDE)
+}
+
+pub fn new_with_core_fns() -> Env {
+    let env = new(HashMap::new(), None);
+    load_core_fns(&env);
+
+    env
+}
+
+pub fn new_with_prelude() -> Result<Env, Error> {
+    let mut env = new_with_core_fns();
+    load_prelude(&mut env)?;
+
+    Ok(env)
 }
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
-    // use crate::ntypes::Sankhya;
+    use super::*;
+    use crate::ntypes::Sankhya;
 
     #[test]
     fn test_env() {
@@ -114,4 +253,25 @@ mod tests {
         // assert_eq!(None, env.get(&ek));
         // assert_eq!(Some(&Expr::Num(Sankhya(10))), env.get(&dus));
     }
+
+    #[test]
+    fn test_lookup_through_locals_and_root() {
+        let ek = Symbol::Identifier("एक".to_string());
+        let dui = Symbol::Identifier("दुई".to_string());
+        let root = new(HashMap::new(), None);
+        bind_global_symbol(&root, &ek, &Expr::Num(Sankhya(1)));
+
+        let caller = new(HashMap::new(), Some(Rc::clone(&root)));
+        let callee = new(HashMap::new(), Some(Rc::clone(&caller)));
+        bind_local_symbol(&caller, &ek, &Expr::Num(Sankhya(10)));
+        bind_local_symbol(&caller, &dui, &Expr::Num(Sankhya(2)));
+
+        assert_eq!(lookup(&callee, &ek), Ok(Expr::Num(Sankhya(10))));
+        assert_eq!(lookup(&callee, &dui), Ok(Expr::Num(Sankhya(2))));
+        assert_eq!(lookup(&root, &ek), Ok(Expr::Num(Sankhya(1))));
+        assert_eq!(
+            lookup(&callee, &Symbol::Identifier("तीन".to_string())),
+            Err(Error::UnboundSymbol(Symbol::Identifier("तीन".to_string())))
+        );
+    }
 }
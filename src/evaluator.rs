@@ -1,15 +1,95 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::ptr;
 use std::rc::Rc;
 
+use crate::core;
 use crate::environment::{self, Env};
+use crate::sandbox;
 use crate::types::{Error, Expr, Function, Symbol};
 
+/// A function application in progress, as seen by a `Hook`.
+pub struct Frame {
+    /// The name the function was called by, if it was called by name.
+    pub function: Option<Symbol>,
+    /// The function being applied.
+    pub fun: Function,
+    /// The whole application, function first.
+    pub form: Vec<Box<Expr>>,
+    /// Values of the arguments, as the function evaluates them.
+    pub values: Vec<Option<Expr>>,
+    pub env: Env,
+    /// The environment a lambda's body runs in, once its arguments are bound.
+    pub locals: Option<Env>,
+    args: Vec<*const Expr>,
+}
+
+/// Observes function applications, for the debugger, tracer and profiler.
+/// A hook is detached while it runs, so whatever it evaluates itself is not
+/// observed.
+pub trait Hook {
+    fn enter(&mut self, _frames: &[Frame]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called when a lambda has bound its arguments and is about to run its
+    /// body, with `locals` of the innermost frame set.
+    fn body(&mut self, _frames: &[Frame]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn exit(&mut self, _frames: &[Frame], _result: &Result<Expr, Error>) {}
+}
+
+pub type SharedHook = Rc<RefCell<dyn Hook>>;
+
+thread_local! {
+    static HOOK: RefCell<Option<SharedHook>> = RefCell::new(None);
+    static FRAMES: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Installs `hook`, returning the one it replaces.
+pub fn set_hook(hook: Option<SharedHook>) -> Option<SharedHook> {
+    HOOK.with(|cell| cell.replace(hook))
+}
+
+fn notify<T>(hook: &SharedHook, f: impl FnOnce(&mut dyn Hook, &[Frame]) -> T) -> T {
+    let detached = set_hook(None);
+    let result = FRAMES.with(|frames| f(&mut *hook.borrow_mut(), &frames.borrow()));
+    set_hook(detached);
+
+    result
+}
+
+/// Remembers `value` if `expr` is an argument of the innermost application.
+fn record(expr: &Expr, value: &Expr) {
+    FRAMES.with(|frames| {
+        if let Ok(mut frames) = frames.try_borrow_mut() {
+            if let Some(frame) = frames.last_mut() {
+                if let Some(idx) = frame.args.iter().position(|arg| ptr::eq(*arg, expr)) {
+                    frame.values[idx] = Some(value.clone());
+                }
+            }
+        }
+    })
+}
+
 pub fn eval(env: &mut Env, expr: &Expr) -> Result<Expr, Error> {
+    sandbox::step()?;
+    let result = eval_expr(env, expr);
+    if let Ok(value) = &result {
+        record(expr, value);
+    }
+
+    result
+}
+
+fn eval_expr(env: &mut Env, expr: &Expr) -> Result<Expr, Error> {
     match expr {
         Expr::Bool(_) => Ok(expr.clone()),
         Expr::Num(_) => Ok(expr.clone()),
         Expr::Str(_) => Ok(expr.clone()),
-        Expr::Sym(sym) => environment::lookup(env, &sym),
+        Expr::Sym(sym) => environment::lookup(env, sym),
         Expr::SExpr(sexpr) => match &**sexpr {
             [] => Ok(expr.clone()),
             [expr] => eval(env, expr),
@@ -20,15 +100,90 @@ pub fn eval(env: &mut Env, expr: &Expr) -> Result<Expr, Error> {
                     n_exprs.extend_from_slice(exprs);
                     eval(env, &Expr::SExpr(n_exprs))
                 }
-                Ok(Expr::Fun(Function::Core(_, cf))) => cf(env, exprs),
-                Ok(Expr::Fun(Function::Lambda(syms, body, mut hmap))) => {
-                    eval_lambda(env, syms, body, &mut hmap, exprs)
-                }
+                Ok(Expr::Fun(fun)) => apply(env, oper, exprs, fun),
                 _ => Err(Error::InvalidOp(*oper.clone())),
             },
         },
         Expr::QExpr(_) => Ok(expr.clone()),
+        Expr::Vector(_) => Ok(expr.clone()),
         Expr::Fun(_) => Ok(expr.clone()),
+        Expr::Ref(_) => Ok(expr.clone()),
+        Expr::Time(_) => Ok(expr.clone()),
+    }
+}
+
+fn call(env: &mut Env, fun: Function, exprs: &[Box<Expr>]) -> Result<Expr, Error> {
+    sandbox::enter(&fun)?;
+    let result = match fun {
+        Function::Core(_, cf, _) => cf(env, exprs).and_then(|value| {
+            sandbox::allocate(&value)?;
+            Ok(value)
+        }),
+        Function::Lambda(syms, body, mut hmap, doc) => {
+            eval_lambda(env, syms, body, &mut hmap, doc, exprs)
+        }
+    };
+    sandbox::leave();
+
+    result
+}
+
+fn apply(env: &mut Env, oper: &Expr, exprs: &[Box<Expr>], fun: Function) -> Result<Expr, Error> {
+    let hook = match HOOK.with(|cell| cell.borrow().clone()) {
+        Some(hook) => hook,
+        None => return call(env, fun, exprs),
+    };
+
+    let mut form = vec![Box::new(oper.clone())];
+    form.extend_from_slice(exprs);
+    let frame = Frame {
+        function: match oper {
+            Expr::Sym(sym) => Some(sym.clone()),
+            _ => None,
+        },
+        fun: fun.clone(),
+        form,
+        values: vec![None; exprs.len()],
+        env: Rc::clone(env),
+        locals: None,
+        args: exprs.iter().map(|expr| &**expr as *const Expr).collect(),
+    };
+    let pushed = FRAMES.with(|frames| match frames.try_borrow_mut() {
+        Ok(mut frames) => {
+            frames.push(frame);
+            true
+        }
+        Err(_) => false,
+    });
+    if !pushed {
+        return call(env, fun, exprs);
+    }
+
+    let result =
+        notify(&hook, |hook, frames| hook.enter(frames)).and_then(|_| call(env, fun, exprs));
+    notify(&hook, |hook, frames| hook.exit(frames, &result));
+    FRAMES.with(|frames| frames.borrow_mut().pop());
+
+    result
+}
+
+/// Shows `env`, where a lambda's body is about to run, to the hook.
+fn enter_body(env: &Env) -> Result<(), Error> {
+    let hook = match HOOK.with(|cell| cell.borrow().clone()) {
+        Some(hook) => hook,
+        None => return Ok(()),
+    };
+    let bound = FRAMES.with(|frames| match frames.try_borrow_mut() {
+        Ok(mut frames) => frames
+            .last_mut()
+            .map(|frame| frame.locals = Some(Rc::clone(env)))
+            .is_some(),
+        Err(_) => false,
+    });
+
+    match bound {
+        true => notify(&hook, |hook, frames| hook.body(frames)),
+        false => Ok(()),
     }
 }
 
@@ -37,6 +192,7 @@ fn eval_lambda(
     formals: Vec<Symbol>,
     body: Box<Expr>,
     hmap: &mut HashMap<Symbol, Expr>,
+    doc: Option<String>,
     args: &[Box<Expr>],
 ) -> Result<Expr, Error> {
     let rest_sym = Symbol::Identifier("ऽ".to_string());
@@ -47,24 +203,20 @@ fn eval_lambda(
                     let (args_to_bind, rest_args) = &args.split_at(head_formals.len());
                     for z in head_formals.iter().zip(args_to_bind.iter()) {
                         let mut f_env = environment::new(hmap.clone(), Some(Rc::clone(parent)));
-                        let val = eval(&mut f_env, &*z.1)?;
+                        let val = eval(&mut f_env, z.1)?;
                         hmap.insert(z.0.clone(), val);
                     }
                     hmap.insert(rest_formal.clone(), Expr::QExpr(rest_args.to_vec()));
 
                     let mut env = environment::new(hmap.clone(), Some(Rc::clone(parent)));
-                    let expr: Expr = Expr::SExpr(vec![
-                        Box::new(Expr::Sym(Symbol::QExprOp(crate::types::QExprOp::Eval))),
-                        body,
-                    ]);
-
-                    eval(&mut env, &expr)
+                    enter_body(&env)?;
+                    core::qexpr_eval(&mut env, &[body])
                 }
                 false => {
                     let (formals_to_bind, rest_formals) = &head_formals.split_at(args.len());
                     for z in formals_to_bind.iter().zip(args.iter()) {
                         let mut f_env = environment::new(hmap.clone(), Some(Rc::clone(parent)));
-                        let val = eval(&mut f_env, &*z.1)?;
+                        let val = eval(&mut f_env, z.1)?;
                         hmap.insert(z.0.clone(), val);
                     }
 
@@ -76,6 +228,7 @@ fn eval_lambda(
                         unbound_formals.to_vec(),
                         body,
                         hmap.clone(),
+                        doc,
                     )))
                 }
             }
@@ -85,24 +238,20 @@ fn eval_lambda(
                 let (args_to_bind, rest_args) = &args.split_at(formals.len());
                 for z in formals.iter().zip(args_to_bind.iter()) {
                     let mut f_env = environment::new(hmap.clone(), Some(Rc::clone(parent)));
-                    let val = eval(&mut f_env, &*z.1)?;
+                    let val = eval(&mut f_env, z.1)?;
                     hmap.insert(z.0.clone(), val);
                 }
                 hmap.insert(rest_sym.clone(), Expr::QExpr(rest_args.to_vec()));
 
                 let mut env = environment::new(hmap.clone(), Some(Rc::clone(parent)));
-                let expr: Expr = Expr::SExpr(vec![
-                    Box::new(Expr::Sym(Symbol::QExprOp(crate::types::QExprOp::Eval))),
-                    body,
-                ]);
-
-                eval(&mut env, &expr)
+                enter_body(&env)?;
+                core::qexpr_eval(&mut env, &[body])
             }
             false => {
                 let (formals_to_bind, rest_formals) = &formals.split_at(args.len());
                 for z in formals_to_bind.iter().zip(args.iter()) {
                     let mut f_env = environment::new(hmap.clone(), Some(Rc::clone(parent)));
-                    let val = eval(&mut f_env, &*z.1)?;
+                    let val = eval(&mut f_env, z.1)?;
                     hmap.insert(z.0.clone(), val);
                 }
 
@@ -110,6 +259,7 @@ fn eval_lambda(
                     rest_formals.to_vec(),
                     body,
                     hmap.clone(),
+                    doc,
                 )))
             }
         },
@@ -0,0 +1,364 @@
+use pest::iterators::Pair;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::parser::{self, Rule};
+use crate::types::Error;
+
+const WIDTH: usize = 80;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Delimiter {
+    Paren,
+    Quote,
+    Bracket,
+}
+
+impl Delimiter {
+    fn open(self) -> &'static str {
+        match self {
+            Delimiter::Paren => "(",
+            Delimiter::Quote => "'(",
+            Delimiter::Bracket => "[",
+        }
+    }
+
+    fn close(self) -> char {
+        match self {
+            Delimiter::Paren | Delimiter::Quote => ')',
+            Delimiter::Bracket => ']',
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Node {
+    Atom(String),
+    List {
+        delimiter: Delimiter,
+        items: Vec<Item>,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Item {
+    Node(Node),
+    Comment { text: String, trailing: bool },
+    Blank,
+}
+
+fn width(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+fn build_node(pair: Pair<Rule>) -> Node {
+    match pair.as_rule() {
+        Rule::fmt_atom => Node::Atom(pair.as_str().to_string()),
+        Rule::fmt_sexpr | Rule::fmt_qexpr | Rule::fmt_vector => Node::List {
+            delimiter: match pair.as_rule() {
+                Rule::fmt_qexpr => Delimiter::Quote,
+                Rule::fmt_vector => Delimiter::Bracket,
+                _ => Delimiter::Paren,
+            },
+            items: build_items(pair.into_inner().next().unwrap()),
+        },
+        _ => unreachable!(),
+    }
+}
+
+fn build_items(pair: Pair<Rule>) -> Vec<Item> {
+    let mut items = vec![];
+    let mut newlines = 0;
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::fmt_space => {
+                newlines = pair.as_str().matches('\n').count();
+                if newlines > 1 && !items.is_empty() {
+                    items.push(Item::Blank);
+                }
+            }
+            Rule::fmt_comment => {
+                let trailing = newlines == 0 && matches!(items.last(), Some(Item::Node(_)));
+                items.push(Item::Comment {
+                    text: pair.as_str().trim_end().to_string(),
+                    trailing,
+                });
+                newlines = 0;
+            }
+            _ => {
+                items.push(Item::Node(build_node(pair)));
+                newlines = 0;
+            }
+        }
+    }
+    if let Some(Item::Blank) = items.last() {
+        items.pop();
+    }
+
+    items
+}
+
+fn nodes(items: &[Item]) -> impl Iterator<Item = &Node> {
+    items.iter().filter_map(|item| match item {
+        Item::Node(node) => Some(node),
+        _ => None,
+    })
+}
+
+/// `फलन` and `\` with a docstring, `यदि` with both branches, `छनौट` with
+/// more than one clause and `परीक्षण` with more than one body always go on
+/// several lines, even when they would fit on one. Quoted bodies count too,
+/// as that is how they are written.
+fn forces_break(items: &[Item]) -> bool {
+    let args = nodes(items).count().saturating_sub(1);
+    let clauses = nodes(items)
+        .filter(|node| matches!(node, Node::List { .. }))
+        .count();
+    match nodes(items).next() {
+        Some(Node::Atom(head)) => match head.as_str() {
+            "फलन" | "\\" | "यदि" | "परीक्षण" => args > 2,
+            "छनौट" => clauses > 1,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Forms whose first few arguments stay on the head line while the rest are
+/// indented by two, instead of being aligned with the first argument.
+fn header_args(head: &str) -> Option<usize> {
+    match head {
+        "छनौट" => Some(0),
+        "परीक्षण" => Some(1),
+        _ => None,
+    }
+}
+
+fn flat(node: &Node) -> Option<String> {
+    match node {
+        Node::Atom(text) => Some(text.clone()),
+        Node::List { delimiter, items } => {
+            if forces_break(items) {
+                return None;
+            }
+
+            let mut parts = vec![];
+            for item in items {
+                match item {
+                    Item::Node(node) => parts.push(flat(node)?),
+                    Item::Comment { .. } => return None,
+                    Item::Blank => {}
+                }
+            }
+            Some(format!(
+                "{}{}{}",
+                delimiter.open(),
+                parts.join(" "),
+                delimiter.close()
+            ))
+        }
+    }
+}
+
+fn column(out: &str) -> usize {
+    width(&out[out.rfind('\n').map_or(0, |idx| idx + 1)..])
+}
+
+fn newline(out: &mut String, blank: bool, indent: usize) {
+    if blank {
+        out.push('\n');
+    }
+    out.push('\n');
+    out.push_str(&" ".repeat(indent));
+}
+
+/// `tail` is the number of closing parens that will follow on the same line.
+fn render(node: &Node, col: usize, tail: usize, out: &mut String) {
+    match flat(node) {
+        Some(text) if col + width(&text) + tail <= WIDTH => out.push_str(&text),
+        _ => match node {
+            Node::Atom(text) => out.push_str(text),
+            Node::List { delimiter, items } => render_list(*delimiter, items, col, tail, out),
+        },
+    }
+}
+
+#[derive(PartialEq)]
+enum Position {
+    Open,
+    AfterHead,
+    Line,
+}
+
+/// The head stays next to the paren and the first argument next to the
+/// head, every other argument goes on its own line aligned with the first.
+/// Forms listed in `header_args` keep their header arguments on the head
+/// line and indent the rest by two.
+fn render_list(delimiter: Delimiter, items: &[Item], col: usize, tail: usize, out: &mut String) {
+    out.push_str(delimiter.open());
+    let last = match items.last() {
+        Some(Item::Node(_)) => items.len() - 1,
+        _ => items.len(),
+    };
+    let inner = col + width(delimiter.open());
+    let (align, mut headers) = match nodes(items).next() {
+        Some(Node::Atom(head)) => match header_args(head) {
+            Some(count) => (col + 2, count),
+            None if nodes(items).nth(1).is_some() => (inner + width(head) + 1, 1),
+            None => (inner, 0),
+        },
+        _ => (inner, 0),
+    };
+
+    let mut position = Position::Open;
+    let mut indent = inner;
+    let mut blank = false;
+    for (idx, item) in items.iter().enumerate() {
+        let tail = match idx == last {
+            true => tail + 1,
+            false => 0,
+        };
+        match item {
+            Item::Blank => blank = position != Position::Open,
+            Item::Comment { text, trailing } => {
+                match (trailing, &position) {
+                    (_, Position::Open) => {}
+                    (true, _) => out.push(' '),
+                    (false, _) => newline(out, blank, indent),
+                }
+                out.push_str(text);
+                position = Position::Line;
+                blank = false;
+            }
+            Item::Node(node) => {
+                match position {
+                    Position::Open => {
+                        render(node, inner, tail, out);
+                        indent = align;
+                        position = match headers {
+                            0 => Position::Line,
+                            _ => Position::AfterHead,
+                        };
+                        continue;
+                    }
+                    Position::AfterHead => {
+                        out.push(' ');
+                        render(node, column(out), tail, out);
+                        headers -= 1;
+                        if headers == 0 {
+                            position = Position::Line;
+                        }
+                        blank = false;
+                        continue;
+                    }
+                    Position::Line => newline(out, blank, indent),
+                }
+                render(node, indent, tail, out);
+                position = Position::Line;
+                blank = false;
+            }
+        }
+    }
+
+    if let Some(Item::Comment { .. }) = items.last() {
+        newline(out, false, col);
+    }
+    out.push(delimiter.close());
+}
+
+pub fn format(source: &str) -> Result<String, Error> {
+    parser::parse(source).map_err(|pe| Error::ParseError(pe.to_string()))?;
+    let pairs = parser::parse_layout(source).map_err(|pe| Error::ParseError(pe.to_string()))?;
+
+    let mut out = String::new();
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::shebang => {
+                out.push_str(pair.as_str().trim_end());
+                out.push('\n');
+            }
+            Rule::fmt_items => render_top_level(&build_items(pair), &mut out),
+            _ => {}
+        }
+    }
+
+    Ok(out)
+}
+
+fn render_top_level(items: &[Item], out: &mut String) {
+    let mut first = true;
+    let mut blank = false;
+    for item in items {
+        match item {
+            Item::Blank => blank = !first,
+            Item::Comment {
+                text,
+                trailing: true,
+            } => {
+                out.push(' ');
+                out.push_str(text);
+            }
+            Item::Comment { text, .. } => {
+                if !first {
+                    newline(out, blank, 0);
+                }
+                out.push_str(text);
+                first = false;
+                blank = false;
+            }
+            Item::Node(node) => {
+                if !first {
+                    newline(out, blank, 0);
+                }
+                render(node, 0, 0, out);
+                first = false;
+                blank = false;
+            }
+        }
+    }
+
+    if !first {
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let source = "; टिप्पणी\n\n\n(फलन '(दोब्बर अ) \"दोब्बर\"\n\t'(* २ अ))   ; पछाडि\n(यदि   सत्य\n '(१)  '(२))\n";
+        let expected = "; टिप्पणी\n\n(फलन '(दोब्बर अ)\n     \"दोब्बर\"\n     '(* २ अ)) ; पछाडि\n(यदि सत्य\n    '(१)\n    '(२))\n";
+        assert_eq!(format(source), Ok(expected.to_string()));
+    }
+
+    #[test]
+    fn test_format_long_and_commented() {
+        let source = "(छाप \"एउटा लामो स्ट्रिङ जुन एक लाइनमा कुनै हालतमा पनि अटाउँदैन, अटाउनै सक्दैन\" \"अर्को लामो स्ट्रिङ पनि यहाँ छ\" (+ १ २))\n(सुची १ ; एक\n २)";
+        let expected = "(छाप \"एउटा लामो स्ट्रिङ जुन एक लाइनमा कुनै हालतमा पनि अटाउँदैन, अटाउनै सक्दैन\"\n    \"अर्को लामो स्ट्रिङ पनि यहाँ छ\"\n    (+ १ २))\n(सुची १ ; एक\n    २)\n";
+        assert_eq!(format(source), Ok(expected.to_string()));
+    }
+
+    #[test]
+    fn test_format_shebang_and_errors() {
+        assert_eq!(
+            format("#!/usr/bin/env alpa\n(छाप   १)"),
+            Ok(String::from("#!/usr/bin/env alpa\n(छाप १)\n"))
+        );
+        assert!(matches!(format("(छाप"), Err(Error::ParseError(_))));
+        assert_eq!(
+            format("(तान   [१  २ ; दुई\n ३] ०)"),
+            Ok(String::from("(तान [१ २ ; दुई\n       ३]\n    ०)\n"))
+        );
+    }
+
+    #[test]
+    fn test_format_prelude() {
+        let prelude = include_str!("प्रस्तावना.अ");
+        let formatted = format(prelude).unwrap();
+
+        assert_eq!(formatted, prelude);
+        assert_eq!(format(&formatted).unwrap(), formatted);
+        assert_eq!(parser::parse(&formatted), parser::parse(prelude));
+    }
+}
@@ -1,6 +1,20 @@
+pub mod calendar;
+pub mod cli;
+pub mod collation;
 pub mod core;
+pub mod debugger;
+pub mod docgen;
+pub mod docs;
 pub mod environment;
 pub mod evaluator;
+pub mod formatter;
+pub mod linter;
+pub mod lsp;
 pub mod ntypes;
 pub mod parser;
+pub mod profiler;
+pub mod repl;
+pub mod sandbox;
+pub mod testing;
+pub mod tracer;
 pub mod types;
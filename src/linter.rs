@@ -0,0 +1,693 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::environment;
+use crate::ntypes::Sankhya;
+use crate::parser::{self, Span};
+use crate::types::{
+    BoolOp, Error, Expr, ExprsOp, Function, ListOp, LoopOp, NumOp, QExprOp, QExprsOp, RefOp,
+    SExprOp, Symbol, TestOp, TimeOp, VectorOp,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Lint {
+    pub line: usize,
+    pub col: usize,
+    /// Byte range of the offending expression in the source.
+    pub start: usize,
+    pub end: usize,
+    pub message: String,
+}
+
+/// Smallest and largest number of arguments a function accepts, `None` when
+/// there is no upper limit.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Arity(pub usize, pub Option<usize>);
+
+/// Argument counts between a core function's smallest and largest that it
+/// still rejects.
+fn skipped_counts(symbol: &Symbol) -> &'static [usize] {
+    match symbol {
+        Symbol::TimeOp(TimeOp::FromBs) | Symbol::TimeOp(TimeOp::FromGregorian) => &[4, 5],
+        _ => &[],
+    }
+}
+
+pub fn core_arity(symbol: &Symbol) -> Arity {
+    match symbol {
+        Symbol::ExprsOp(op) => match op {
+            ExprsOp::Equal | ExprsOp::NotEqual => Arity(2, Some(2)),
+            ExprsOp::If => Arity(3, Some(3)),
+            ExprsOp::Match => Arity(2, None),
+            ExprsOp::List | ExprsOp::Print => Arity(0, None),
+            ExprsOp::Import | ExprsOp::Error | ExprsOp::Help | ExprsOp::Exit => Arity(1, Some(1)),
+        },
+        Symbol::BoolOp(op) => match op {
+            BoolOp::Not => Arity(1, Some(1)),
+            BoolOp::Or | BoolOp::And => Arity(0, None),
+        },
+        Symbol::LoopOp(op) => match op {
+            LoopOp::While => Arity(2, Some(2)),
+            LoopOp::ForEach => Arity(3, Some(3)),
+            LoopOp::Count => Arity(4, Some(5)),
+            LoopOp::Range => Arity(2, Some(3)),
+            LoopOp::Break | LoopOp::Continue => Arity(1, Some(1)),
+        },
+        Symbol::VectorOp(op) => match op {
+            VectorOp::New => Arity(0, None),
+            VectorOp::Get => Arity(2, Some(2)),
+            VectorOp::Set => Arity(3, Some(3)),
+            VectorOp::Push => Arity(2, None),
+            VectorOp::Slice => Arity(2, Some(3)),
+            VectorOp::FromQExpr | VectorOp::ToQExpr => Arity(1, Some(1)),
+        },
+        Symbol::TimeOp(op) => match op {
+            TimeOp::Now | TimeOp::ToBs | TimeOp::ToGregorian => Arity(1, Some(1)),
+            TimeOp::FromBs | TimeOp::FromGregorian => Arity(3, Some(6)),
+            TimeOp::Add | TimeOp::Diff | TimeOp::Format => Arity(2, Some(2)),
+        },
+        Symbol::ListOp(op) => match op {
+            ListOp::Length | ListOp::Last | ListOp::Reverse | ListOp::Enumerate => {
+                Arity(1, Some(1))
+            }
+            ListOp::Sort => Arity(1, Some(3)),
+            ListOp::Fold => Arity(3, Some(3)),
+            ListOp::Zip => Arity(2, None),
+            _ => Arity(2, Some(2)),
+        },
+        Symbol::RefOp(op) => match op {
+            RefOp::New | RefOp::Get => Arity(1, Some(1)),
+            RefOp::Set => Arity(2, Some(2)),
+            RefOp::Swap => Arity(2, None),
+        },
+        Symbol::NumOp(op) => match op {
+            NumOp::Add | NumOp::Subtract | NumOp::Multiply | NumOp::Divide => Arity(1, None),
+            NumOp::GreaterThan
+            | NumOp::GreaterThanOrEqual
+            | NumOp::LessThan
+            | NumOp::LessThanOrEqual => Arity(2, Some(2)),
+        },
+        Symbol::QExprOp(op) => match op {
+            QExprOp::Head | QExprOp::Tail | QExprOp::Len | QExprOp::Eval => Arity(1, Some(1)),
+            QExprOp::Trace => Arity(1, Some(2)),
+        },
+        Symbol::QExprsOp(op) => match op {
+            QExprsOp::Cons => Arity(2, Some(2)),
+            QExprsOp::Join | QExprsOp::Def | QExprsOp::Put => Arity(1, None),
+            QExprsOp::Lambda => Arity(2, Some(3)),
+            QExprsOp::Let | QExprsOp::LetRec => Arity(2, Some(2)),
+        },
+        Symbol::SExprOp(op) => match op {
+            SExprOp::PrintEnv => Arity(0, None),
+        },
+        Symbol::TestOp(op) => match op {
+            TestOp::Assert => Arity(1, Some(1)),
+            TestOp::AssertEqual => Arity(2, Some(2)),
+            TestOp::AssertThrows => Arity(1, Some(2)),
+            TestOp::Test => Arity(1, None),
+        },
+        Symbol::Identifier(_) => Arity(0, None),
+    }
+}
+
+/// Lambdas can be partially applied, so only the upper limit is known.
+fn lambda_arity(formals: &[Symbol]) -> Arity {
+    match formals.contains(&identifier("ऽ")) {
+        true => Arity(0, None),
+        false => Arity(0, Some(formals.len())),
+    }
+}
+
+fn identifier(name: &str) -> Symbol {
+    Symbol::Identifier(name.to_string())
+}
+
+fn name(symbol: &Symbol) -> String {
+    Expr::Sym(symbol.clone()).to_string()
+}
+
+fn symbols(expr: &Expr) -> Vec<Symbol> {
+    match expr {
+        Expr::QExpr(exprs) => exprs
+            .iter()
+            .filter_map(|expr| match &**expr {
+                Expr::Sym(sym) => Some(sym.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn collect_identifiers(expr: &Expr, found: &mut HashSet<Symbol>) {
+    match expr {
+        Expr::Sym(sym) => {
+            found.insert(sym.clone());
+        }
+        Expr::SExpr(exprs) | Expr::QExpr(exprs) => exprs
+            .iter()
+            .for_each(|expr| collect_identifiers(expr, found)),
+        Expr::Vector(exprs) => exprs
+            .iter()
+            .for_each(|expr| collect_identifiers(expr, found)),
+        _ => {}
+    }
+}
+
+fn lambda_formals(expr: &Expr) -> Option<Vec<Symbol>> {
+    match expr {
+        Expr::SExpr(exprs) => match &exprs[..] {
+            [head, formals, ..] if **head == Expr::Sym(Symbol::QExprsOp(QExprsOp::Lambda)) => {
+                Some(symbols(formals))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Names a source defines with `फलन`, `नामक` and `=`, anywhere in the file,
+/// as scoping is dynamic, together with the arity of the functions among them.
+fn collect_definitions(exprs: &[Box<Expr>], defs: &mut HashMap<Symbol, Option<Arity>>) {
+    for expr in exprs {
+        if let Expr::SExpr(items) | Expr::QExpr(items) = &**expr {
+            match &items[..] {
+                [head, signature, ..] if **head == Expr::Sym(identifier("फलन")) => {
+                    if let [fname, formals @ ..] = &symbols(signature)[..] {
+                        defs.insert(fname.clone(), Some(lambda_arity(formals)));
+                    }
+                }
+                [head, names, values @ ..]
+                    if **head == Expr::Sym(Symbol::QExprsOp(QExprsOp::Def))
+                        || **head == Expr::Sym(Symbol::QExprsOp(QExprsOp::Put)) =>
+                {
+                    for (idx, sym) in symbols(names).into_iter().enumerate() {
+                        let formals = values.get(idx).and_then(|value| lambda_formals(value));
+                        defs.insert(sym, formals.map(|formals| lambda_arity(&formals)));
+                    }
+                }
+                _ => {}
+            }
+            collect_definitions(items, defs);
+        }
+    }
+}
+
+struct Linter {
+    known: HashMap<Symbol, Option<Arity>>,
+    prelude: HashSet<Symbol>,
+    scopes: Vec<HashSet<Symbol>>,
+    lints: Vec<Lint>,
+}
+
+impl Linter {
+    fn warn(&mut self, span: &Span, message: String) {
+        self.lints.push(Lint {
+            line: span.line,
+            col: span.col,
+            start: span.start,
+            end: span.end,
+            message,
+        });
+    }
+
+    fn is_bound(&self, sym: &Symbol) -> bool {
+        self.known.contains_key(sym) || self.scopes.iter().any(|scope| scope.contains(sym))
+    }
+
+    fn arity(&self, sym: &Symbol) -> Option<Arity> {
+        match sym {
+            Symbol::Identifier(_) if self.scopes.iter().any(|scope| scope.contains(sym)) => None,
+            Symbol::Identifier(_) => self.known.get(sym).cloned().flatten(),
+            op => Some(core_arity(op)),
+        }
+    }
+
+    fn check_shadowing(&mut self, names: &[Symbol], span: &Span) {
+        for sym in names {
+            if self.prelude.contains(sym) {
+                self.warn(
+                    span,
+                    format!("प्रस्तावनाको नाम {} फेरि परिभाषित गरियो", name(sym)),
+                );
+            }
+        }
+    }
+
+    fn check_code(&mut self, expr: &Expr, span: &Span) {
+        match expr {
+            Expr::Sym(sym @ Symbol::Identifier(_)) if !self.is_bound(sym) => {
+                self.warn(span, format!("नाम भेटिएन : {}", name(sym)))
+            }
+            Expr::SExpr(items) => self.check_sexpr(items, span),
+            _ => {}
+        }
+    }
+
+    /// Checks the contents of a Q-expression that will be evaluated as code.
+    fn check_body(&mut self, expr: &Expr, span: &Span) {
+        match expr {
+            Expr::QExpr(items) => self.check_sexpr(items, span),
+            x => self.check_code(x, span),
+        }
+    }
+
+    fn check_lambda(&mut self, formals: &[Symbol], body: &Expr, span: &Span) {
+        let mut used = HashSet::new();
+        collect_identifiers(body, &mut used);
+        for formal in formals {
+            let unused = !used.contains(formal) && *formal != identifier("ऽ");
+            if unused && !name(formal).starts_with('_') {
+                self.warn(span, format!("प्रयोग नगरिएको प्यारामिटर : {}", name(formal)));
+            }
+        }
+
+        self.scopes.push(formals.iter().cloned().collect());
+        self.check_body(body, &span.children[span.children.len() - 1]);
+        self.scopes.pop();
+    }
+
+    /// Values are checked outside the new scope, or inside it when
+    /// `recursive`, and the body inside it.
+    fn check_let(&mut self, bindings: &Expr, body: &Expr, span: &Span, recursive: bool) {
+        let pairs = match bindings {
+            Expr::QExpr(pairs) => pairs,
+            x => {
+                self.check_code(x, &span.children[1]);
+                return;
+            }
+        };
+
+        let mut names = HashSet::new();
+        let mut values = vec![];
+        for (pair, child) in pairs.iter().zip(span.children[1].children.iter()) {
+            let binding = match &**pair {
+                Expr::SExpr(items) | Expr::QExpr(items) => match &items[..] {
+                    [name, value] => match &**name {
+                        Expr::Sym(sym @ Symbol::Identifier(_)) => Some((sym, value)),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                _ => None,
+            };
+            match binding {
+                Some((sym, value)) => {
+                    names.insert(sym.clone());
+                    values.push((value, &child.children[1]));
+                }
+                None => self.warn(child, format!("नाम र मानको जोडी होइन : {}", pair)),
+            }
+        }
+
+        if recursive {
+            self.scopes.push(names.clone());
+        }
+        for (value, child) in values {
+            self.check_code(value, child);
+        }
+        if !recursive {
+            self.scopes.push(names);
+        }
+        self.check_body(body, &span.children[2]);
+        self.scopes.pop();
+    }
+
+    /// The names a pattern binds are only in scope in the guard and result of
+    /// its own clause.
+    fn check_match(&mut self, args: &[Box<Expr>], spans: &[Span]) {
+        self.check_code(&args[0], &spans[1]);
+        let mut catch_all = false;
+        for (clause, child) in args.iter().zip(spans.iter().skip(1)).skip(1) {
+            let parts = match &**clause {
+                Expr::QExpr(parts) if !parts.is_empty() => parts,
+                x => {
+                    self.warn(child, format!("मिलाउको अवस्था क्यु-एक्सपर् हुनुपर्छ : {}", x));
+                    continue;
+                }
+            };
+            if catch_all {
+                self.warn(child, String::from("मिलाउको यो अवस्थासम्म कहिल्यै पुगिँदैन"));
+            }
+
+            let mut names = HashSet::new();
+            collect_identifiers(&parts[0], &mut names);
+            self.scopes.push(names);
+            for (part, part_span) in parts.iter().zip(child.children.iter()).skip(1) {
+                self.check_code(part, part_span);
+            }
+            self.scopes.pop();
+
+            let unguarded = parts.len() == 2;
+            catch_all |= unguarded && matches!(*parts[0], Expr::Sym(Symbol::Identifier(_)));
+        }
+    }
+
+    /// The loop variable is only in scope in the body, which comes last.
+    fn check_loop(&mut self, args: &[Box<Expr>], spans: &[Span]) {
+        let (body, values) = match args.split_last() {
+            Some((body, [_, values @ ..])) => (body, values),
+            _ => return,
+        };
+        for (value, child) in values.iter().zip(spans.iter().skip(2)) {
+            self.check_code(value, child);
+        }
+
+        self.scopes.push(symbols(&args[0]).into_iter().collect());
+        self.check_body(body, &spans[spans.len() - 1]);
+        self.scopes.pop();
+    }
+
+    fn check_sexpr(&mut self, items: &[Box<Expr>], span: &Span) {
+        let (head, args) = match items.split_first() {
+            Some((head, args)) if !args.is_empty() => (head, args),
+            _ => {
+                for (item, child) in items.iter().zip(span.children.iter()) {
+                    self.check_code(item, child);
+                }
+                return;
+            }
+        };
+        let spans = &span.children;
+
+        if let Expr::Sym(sym) = &**head {
+            if let Some(Arity(min, max)) = self.arity(sym) {
+                let skipped = skipped_counts(sym);
+                if args.len() < min
+                    || max.is_some_and(|max| args.len() > max)
+                    || skipped.contains(&args.len())
+                {
+                    let expected = match max {
+                        Some(max) if max == min => format!("{}", Sankhya(min as i32)),
+                        Some(max) if !skipped.is_empty() => (min..=max)
+                            .filter(|n| !skipped.contains(n))
+                            .map(|n| Sankhya(n as i32).to_string())
+                            .collect::<Vec<String>>()
+                            .join(" वा "),
+                        Some(max) => {
+                            format!("{} देखि {}", Sankhya(min as i32), Sankhya(max as i32))
+                        }
+                        None => format!("कम्तिमा {}", Sankhya(min as i32)),
+                    };
+                    self.warn(
+                        span,
+                        format!(
+                            "{} लाई {} तर्क चाहिन्छ, {} दिइयो",
+                            name(sym),
+                            expected,
+                            Sankhya(args.len() as i32)
+                        ),
+                    );
+                }
+            }
+        }
+
+        let fun = match &**head {
+            Expr::Sym(Symbol::Identifier(fun)) => fun.as_str(),
+            Expr::Sym(Symbol::ExprsOp(ExprsOp::If)) => {
+                self.check_code(&args[0], &spans[1]);
+                for (branch, child) in args.iter().zip(spans.iter().skip(1)).skip(1) {
+                    match &**branch {
+                        Expr::QExpr(_) => self.check_body(branch, child),
+                        x => self.warn(child, format!("यदिको शाखा क्यु-एक्सपर् हुनुपर्छ : {}", x)),
+                    }
+                }
+                return;
+            }
+            Expr::Sym(Symbol::QExprsOp(QExprsOp::Lambda)) => {
+                if args.len() >= 2 {
+                    self.check_lambda(&symbols(&args[0]), &args[args.len() - 1], span);
+                }
+                return;
+            }
+            Expr::Sym(Symbol::QExprsOp(op @ (QExprsOp::Let | QExprsOp::LetRec))) => {
+                if args.len() == 2 {
+                    self.check_let(&args[0], &args[1], span, *op == QExprsOp::LetRec);
+                }
+                return;
+            }
+            Expr::Sym(Symbol::ExprsOp(ExprsOp::Match)) => {
+                self.check_match(args, spans);
+                return;
+            }
+            Expr::Sym(Symbol::LoopOp(LoopOp::While)) => {
+                for (arg, child) in args.iter().zip(spans.iter().skip(1)) {
+                    self.check_body(arg, child);
+                }
+                return;
+            }
+            Expr::Sym(Symbol::LoopOp(LoopOp::ForEach | LoopOp::Count)) => {
+                self.check_loop(args, spans);
+                return;
+            }
+            Expr::Sym(Symbol::QExprsOp(QExprsOp::Def))
+            | Expr::Sym(Symbol::QExprsOp(QExprsOp::Put)) => {
+                self.check_shadowing(&symbols(&args[0]), &spans[1]);
+                for (value, child) in args.iter().zip(spans.iter().skip(1)).skip(1) {
+                    self.check_code(value, child);
+                }
+                return;
+            }
+            Expr::Sym(Symbol::QExprOp(QExprOp::Eval))
+            | Expr::Sym(Symbol::QExprOp(QExprOp::Trace))
+            | Expr::Sym(Symbol::TestOp(TestOp::AssertThrows)) => {
+                self.check_body(&args[0], &spans[1]);
+                for (arg, child) in args.iter().zip(spans.iter().skip(1)).skip(1) {
+                    self.check_code(arg, child);
+                }
+                return;
+            }
+            Expr::Sym(Symbol::TestOp(TestOp::Test)) => {
+                self.check_code(&args[0], &spans[1]);
+                for (body, child) in args.iter().zip(spans.iter().skip(1)).skip(1) {
+                    self.check_body(body, child);
+                }
+                return;
+            }
+            Expr::Sym(Symbol::ExprsOp(ExprsOp::Import))
+            | Expr::Sym(Symbol::ExprsOp(ExprsOp::Help)) => {
+                return;
+            }
+            _ => "",
+        };
+
+        match fun {
+            "फलन" => {
+                let signature = symbols(&args[0]);
+                self.check_shadowing(&signature[..signature.len().min(1)], &spans[1]);
+                if let ([_, formals @ ..], Some(_)) = (&signature[..], args.get(1)) {
+                    self.check_lambda(formals, &args[args.len() - 1], span);
+                }
+            }
+            "छनौट" | "अवस्था" => {
+                let skip = match fun {
+                    "अवस्था" => {
+                        self.check_code(&args[0], &spans[1]);
+                        1
+                    }
+                    _ => 0,
+                };
+                let mut seen: Vec<&Expr> = vec![];
+                let mut catch_all = false;
+                for (clause, child) in args.iter().zip(spans.iter().skip(1)).skip(skip) {
+                    let parts = match &**clause {
+                        Expr::QExpr(parts) => parts,
+                        x => {
+                            self.warn(child, format!("{}को अवस्था क्यु-एक्सपर् हुनुपर्छ : {}", fun, x));
+                            continue;
+                        }
+                    };
+                    for (part, part_span) in parts.iter().zip(child.children.iter()) {
+                        self.check_code(part, part_span);
+                    }
+
+                    let test = parts.first().map(|part| &**part);
+                    let repeated = test.is_some_and(|test| seen.contains(&test));
+                    if catch_all || (fun == "अवस्था" && repeated) {
+                        self.warn(child, format!("{}को यो अवस्थासम्म कहिल्यै पुगिँदैन", fun));
+                    }
+                    match test {
+                        Some(Expr::Bool(b)) if fun == "छनौट" => catch_all |= b.0,
+                        Some(Expr::Sym(sym)) if fun == "छनौट" => {
+                            catch_all |= *sym == identifier("नत्र")
+                        }
+                        Some(test @ (Expr::Num(_) | Expr::Str(_) | Expr::Bool(_))) => {
+                            seen.push(test)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {
+                for (item, child) in items.iter().zip(spans.iter()) {
+                    self.check_code(item, child);
+                }
+            }
+        }
+    }
+}
+
+fn prelude_names() -> (HashMap<Symbol, Option<Arity>>, HashSet<Symbol>) {
+    let env = environment::new_with_prelude().expect("the prelude always loads");
+    let mut known = HashMap::new();
+    let mut prelude = HashSet::new();
+    for (sym, expr) in environment::bindings(&env) {
+        let arity = match &expr {
+            Expr::Fun(Function::Core(op, _, _)) => Some(core_arity(op)),
+            Expr::Fun(Function::Lambda(formals, _, _, _)) => Some(lambda_arity(formals)),
+            _ => None,
+        };
+        if let Symbol::Identifier(_) = sym {
+            prelude.insert(sym.clone());
+        }
+        known.insert(sym, arity);
+    }
+
+    (known, prelude)
+}
+
+/// The file `(आयात मोड्युल)` reads, looked up the way `आयात` does, next to
+/// the importing file in `dir`.
+pub(crate) fn import_path(expr: &Expr, dir: &Path) -> Option<PathBuf> {
+    let module = match expr {
+        Expr::SExpr(items) => match &items[..] {
+            [head, module] if **head == Expr::Sym(Symbol::ExprsOp(ExprsOp::Import)) => {
+                match &**module {
+                    Expr::Sym(Symbol::Identifier(module)) => format!("{}.अ", module),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(dir.join(module))
+}
+
+/// Definitions brought in by `(आयात मोड्युल)`, and by the modules it imports.
+fn collect_imports(
+    exprs: &[Box<Expr>],
+    dir: &Path,
+    visited: &mut HashSet<PathBuf>,
+    defs: &mut HashMap<Symbol, Option<Arity>>,
+) {
+    for path in exprs.iter().filter_map(|expr| import_path(expr, dir)) {
+        if !visited.insert(path.clone()) {
+            continue;
+        }
+        if let Ok(Expr::SExpr(imported)) = fs::read_to_string(&path)
+            .map_err(|_| ())
+            .and_then(|source| parser::parse(&source).map_err(|_| ()))
+        {
+            collect_definitions(&imported, defs);
+            let dir = path.parent().unwrap_or(dir);
+            collect_imports(&imported, dir, visited, defs);
+        }
+    }
+}
+
+pub fn lint_source(source: &str, path: &Path) -> Result<Vec<Lint>, Error> {
+    let (exprs, spans) = parser::parse_spanned(source)
+        .map_err(|pe| Error::ParseError(pe.with_path(&path.display().to_string()).to_string()))?;
+
+    let (mut known, mut prelude) = prelude_names();
+    if source == environment::PRELUDE {
+        prelude.clear();
+    }
+    let mut defs = HashMap::new();
+    collect_definitions(&exprs, &mut defs);
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    collect_imports(&exprs, dir, &mut HashSet::new(), &mut defs);
+    known.extend(defs);
+
+    let mut linter = Linter {
+        known,
+        prelude,
+        scopes: vec![],
+        lints: vec![],
+    };
+    for (expr, span) in exprs.iter().zip(spans.iter()) {
+        linter.check_code(expr, span);
+    }
+
+    let mut lints = linter.lints;
+    lints.sort_by_key(|lint| (lint.line, lint.col));
+    lints.dedup();
+
+    Ok(lints)
+}
+
+pub fn lint_file(path: &Path) -> Result<Vec<Lint>, Error> {
+    let source = fs::read_to_string(path)
+        .map_err(|_| Error::ImportError(Expr::Str(path.display().to_string())))?;
+
+    lint_source(&source, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(source: &str) -> Vec<(usize, usize, String)> {
+        lint_source(source, Path::new("परीक्षण.अ"))
+            .unwrap()
+            .into_iter()
+            .map(|lint| (lint.line, lint.col, lint.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_clean() {
+        assert_eq!(lint(environment::PRELUDE), vec![]);
+        assert_eq!(
+            lint("(फलन '(दोब्बर अ) '(* २ अ))\n(छाप (दोब्बर ४) (लहरै दोब्बर '(१ २)))"),
+            vec![]
+        );
+        assert_eq!(
+            lint("(पुनःस्थानीय '((फ (\\ '(क) '(ब क))) (ब (\\ '(क) '(फ क)))) '(फ १))"),
+            vec![]
+        );
+        assert_eq!(
+            lint("(गन्ती '(इ) ० १० २ '(छाप इ))\n(हरेक '(क) (दायरा १ ४) '(यदि (> क २) '((रोक क)) '(क)))"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_lint_mistakes() {
+        let source = "\
+(फलन '(जोड्ने क ख) '(+ क १))
+(यदि सत्य \"हो\" '(२))
+(शिर '(१) '(२))
+(जोड्ने १ २ ३)
+(छाप नभएको)
+(नामक '(लहरै) १)
+(छनौट '((== १ २) १) '(नत्र २) '(सत्य ३))
+(अवस्था १ '(१ \"क\") '(१ \"ख\"))
+(स्थानीय '((क १) (ख क)) '(+ क ख ग))
+(मिलाउ '(१) '(क \"सबै\") '((ख) ख) '((_ ऽ ग) (+ ग घ)))
+(मिति २०८२ १ १ ५)";
+
+        assert_eq!(
+            lint(source),
+            vec![
+                (1, 1, String::from("प्रयोग नगरिएको प्यारामिटर : ख")),
+                (2, 11, String::from("यदिको शाखा क्यु-एक्सपर् हुनुपर्छ : \"हो\"")),
+                (3, 1, String::from("शिर लाई १ तर्क चाहिन्छ, २ दिइयो")),
+                (4, 1, String::from("जोड्ने लाई ० देखि २ तर्क चाहिन्छ, ३ दिइयो")),
+                (5, 6, String::from("नाम भेटिएन : नभएको")),
+                (6, 7, String::from("प्रस्तावनाको नाम लहरै फेरि परिभाषित गरियो")),
+                (7, 31, String::from("छनौटको यो अवस्थासम्म कहिल्यै पुगिँदैन")),
+                (8, 20, String::from("अवस्थाको यो अवस्थासम्म कहिल्यै पुगिँदैन")),
+                (9, 21, String::from("नाम भेटिएन : क")),
+                (9, 33, String::from("नाम भेटिएन : ग")),
+                (10, 24, String::from("मिलाउको यो अवस्थासम्म कहिल्यै पुगिँदैन")),
+                (10, 33, String::from("मिलाउको यो अवस्थासम्म कहिल्यै पुगिँदैन")),
+                (10, 48, String::from("नाम भेटिएन : घ")),
+                (11, 1, String::from("मिति लाई ३ वा ६ तर्क चाहिन्छ, ४ दिइयो")),
+            ]
+        );
+    }
+}
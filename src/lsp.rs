@@ -0,0 +1,838 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::docs;
+use crate::environment;
+use crate::linter;
+use crate::parser::{self, Span};
+use crate::types::{Expr, Function, QExprsOp, Symbol};
+
+const METHOD_NOT_FOUND: i32 = -32601;
+const PARSE_ERROR: i32 = -32700;
+
+const SEVERITY_ERROR: i32 = 1;
+const SEVERITY_WARNING: i32 = 2;
+
+const COMPLETION_FUNCTION: i32 = 3;
+const COMPLETION_VARIABLE: i32 = 6;
+const COMPLETION_CONSTANT: i32 = 21;
+
+const SYMBOL_FUNCTION: i32 = 12;
+const SYMBOL_VARIABLE: i32 = 13;
+
+/// The largest message body accepted, so a bad header can't make the server
+/// allocate without bound.
+const MAX_CONTENT_LENGTH: usize = 64 << 20;
+
+/// Reads one `Content-Length` framed message, `None` at the end of input.
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let length = length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Content-Length हराएको छ"))?;
+    if length > MAX_CONTENT_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Content-Length {} धेरै ठुलो छ", length),
+        ));
+    }
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+
+    Ok(Some(body))
+}
+
+pub fn write_message<W: Write>(output: &mut W, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// A `फलन`, `नामक` or `=` binding and where it is written.
+#[derive(Debug, PartialEq, Clone)]
+struct Definition {
+    name: Symbol,
+    params: Option<Vec<Symbol>>,
+    docstring: Option<String>,
+    start: usize,
+    end: usize,
+    name_start: usize,
+    name_end: usize,
+}
+
+impl Definition {
+    fn signature(&self) -> String {
+        let mut parts = vec![Expr::Sym(self.name.clone()).to_string()];
+        match &self.params {
+            Some(params) => {
+                parts.extend(params.iter().map(|p| Expr::Sym(p.clone()).to_string()));
+                format!("({})", parts.join(" "))
+            }
+            None => parts.join(" "),
+        }
+    }
+
+    fn render(&self) -> String {
+        let description = match &self.docstring {
+            Some(docstring) => docstring.as_str(),
+            None => "विवरण उपलब्ध छैन",
+        };
+        format!("प्रयोग : {}\nविवरण : {}", self.signature(), description)
+    }
+}
+
+fn symbols(expr: &Expr) -> Vec<Symbol> {
+    match expr {
+        Expr::QExpr(exprs) => exprs
+            .iter()
+            .filter_map(|expr| match &**expr {
+                Expr::Sym(sym) => Some(sym.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn docstring(rest: &[Box<Expr>]) -> Option<String> {
+    match rest {
+        [doc, _] => match &**doc {
+            Expr::Str(doc) => Some(doc.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Parameters and docstring of a `(\ '(...) "विवरण" '(...))` value.
+fn lambda_parts(expr: &Expr) -> Option<(Vec<Symbol>, Option<String>)> {
+    match expr {
+        Expr::SExpr(exprs) => match &exprs[..] {
+            [head, params, rest @ ..]
+                if **head == Expr::Sym(Symbol::QExprsOp(QExprsOp::Lambda)) =>
+            {
+                Some((symbols(params), docstring(rest)))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Definitions anywhere in the tree, as scoping is dynamic.
+fn collect_definitions(exprs: &[Box<Expr>], spans: &[Span], defs: &mut Vec<Definition>) {
+    for (expr, span) in exprs.iter().zip(spans.iter()) {
+        let items = match &**expr {
+            Expr::SExpr(items) | Expr::QExpr(items) => items,
+            _ => continue,
+        };
+        let children = &span.children;
+        match &items[..] {
+            [head, signature, rest @ ..]
+                if **head == Expr::Sym(Symbol::Identifier(String::from("फलन"))) =>
+            {
+                if let ([name, params @ ..], Some(name_span)) =
+                    (&symbols(signature)[..], children[1].children.first())
+                {
+                    defs.push(Definition {
+                        name: name.clone(),
+                        params: Some(params.to_vec()),
+                        docstring: docstring(rest),
+                        start: span.start,
+                        end: span.end,
+                        name_start: name_span.start,
+                        name_end: name_span.end,
+                    });
+                }
+            }
+            [head, names, values @ ..]
+                if **head == Expr::Sym(Symbol::QExprsOp(QExprsOp::Def))
+                    || **head == Expr::Sym(Symbol::QExprsOp(QExprsOp::Put)) =>
+            {
+                let names = match &**names {
+                    Expr::QExpr(names) => names,
+                    _ => continue,
+                };
+                for (idx, (name, name_span)) in
+                    names.iter().zip(children[1].children.iter()).enumerate()
+                {
+                    if let Expr::Sym(name) = &**name {
+                        let (params, docstring) =
+                            match values.get(idx).and_then(|v| lambda_parts(v)) {
+                                Some((params, docstring)) => (Some(params), docstring),
+                                None => (None, None),
+                            };
+                        defs.push(Definition {
+                            name: name.clone(),
+                            params,
+                            docstring,
+                            start: span.start,
+                            end: span.end,
+                            name_start: name_span.start,
+                            name_end: name_span.end,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+        collect_definitions(items, children, defs);
+    }
+}
+
+/// The symbol written at byte `offset`, if any.
+fn symbol_at(exprs: &[Box<Expr>], spans: &[Span], offset: usize) -> Option<Symbol> {
+    let (expr, span) = exprs
+        .iter()
+        .zip(spans.iter())
+        .find(|(_, span)| span.start <= offset && offset <= span.end)?;
+
+    match &**expr {
+        Expr::Sym(sym) => Some(sym.clone()),
+        Expr::SExpr(items) | Expr::QExpr(items) => symbol_at(items, &span.children, offset),
+        _ => None,
+    }
+}
+
+/// LSP positions count UTF-16 code units within a line.
+fn position(source: &str, offset: usize) -> Value {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    json!({
+        "line": before.matches('\n').count(),
+        "character": before[line_start..].encode_utf16().count(),
+    })
+}
+
+fn range(source: &str, start: usize, end: usize) -> Value {
+    json!({"start": position(source, start), "end": position(source, end)})
+}
+
+fn offset(source: &str, pos: &Value) -> usize {
+    let line = pos["line"].as_u64().unwrap_or(0) as usize;
+    let character = pos["character"].as_u64().unwrap_or(0) as usize;
+
+    let line_start = match line {
+        0 => 0,
+        _ => source
+            .match_indices('\n')
+            .nth(line - 1)
+            .map_or(source.len(), |(idx, _)| idx + 1),
+    };
+    let mut units = 0;
+    for (idx, ch) in source[line_start..].char_indices() {
+        if units >= character || ch == '\n' {
+            return line_start + idx;
+        }
+        units += ch.len_utf16();
+    }
+
+    source.len()
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte)
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let path = match path.is_absolute() {
+        true => path.to_path_buf(),
+        false => std::env::current_dir().unwrap_or_default().join(path),
+    };
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match is_unreserved(byte) {
+            true => uri.push(byte as char),
+            false => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = vec![];
+    let mut idx = 0;
+    while idx < encoded.len() {
+        let hex = encoded
+            .get(idx + 1..idx + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok());
+        match (
+            encoded[idx],
+            hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+        ) {
+            (b'%', Some(byte)) => {
+                bytes.push(byte);
+                idx += 3;
+            }
+            (byte, _) => {
+                bytes.push(byte);
+                idx += 1;
+            }
+        }
+    }
+
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+struct Document {
+    text: String,
+    /// Definitions from the last version that parsed, so completion keeps
+    /// working while an expression is half written.
+    definitions: Vec<Definition>,
+}
+
+pub struct Server {
+    documents: HashMap<String, Document>,
+    bindings: HashMap<Symbol, Expr>,
+    shutdown: bool,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        let env = environment::new_with_prelude().expect("the prelude always loads");
+        Server {
+            documents: HashMap::new(),
+            bindings: environment::bindings(&env),
+            shutdown: false,
+        }
+    }
+}
+
+fn response(id: &Value, result: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "result": result})
+}
+
+fn error_response(id: &Value, code: i32, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "method": method, "params": params})
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": 1,
+            "hoverProvider": true,
+            "definitionProvider": true,
+            "completionProvider": {},
+            "documentSymbolProvider": true,
+        },
+        "serverInfo": {
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+    })
+}
+
+/// The text of an open document, or of the file on disk otherwise.
+fn source_of(documents: &HashMap<String, Document>, uri: &str) -> Option<String> {
+    match documents.get(uri) {
+        Some(document) => Some(document.text.clone()),
+        None => fs::read_to_string(uri_to_path(uri)?).ok(),
+    }
+}
+
+impl Server {
+    /// Handles one incoming message, returning the messages to send back.
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let id = &message["id"];
+        let params = &message["params"];
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        match message["method"].as_str().unwrap_or_default() {
+            "initialize" => vec![response(id, capabilities())],
+            "shutdown" => {
+                self.shutdown = true;
+                vec![response(id, Value::Null)]
+            }
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.update(uri, text)
+            }
+            "textDocument/didChange" => {
+                match params["contentChanges"].as_array().and_then(|c| c.last()) {
+                    Some(change) => self.update(uri, change["text"].as_str().unwrap_or_default()),
+                    None => vec![],
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                vec![notification(
+                    "textDocument/publishDiagnostics",
+                    json!({"uri": uri, "diagnostics": []}),
+                )]
+            }
+            "textDocument/definition" => {
+                vec![response(id, self.definition(uri, &params["position"]))]
+            }
+            "textDocument/hover" => vec![response(id, self.hover(uri, &params["position"]))],
+            "textDocument/completion" => vec![response(id, self.completion(uri))],
+            "textDocument/documentSymbol" => vec![response(id, self.document_symbols(uri))],
+            _ if id.is_null() => vec![],
+            method => vec![error_response(
+                id,
+                METHOD_NOT_FOUND,
+                &format!("यो मेथड छैन : {}", method),
+            )],
+        }
+    }
+
+    fn update(&mut self, uri: &str, text: &str) -> Vec<Value> {
+        let definitions = match parser::parse_spanned(text) {
+            Ok((exprs, spans)) => {
+                let mut definitions = vec![];
+                collect_definitions(&exprs, &spans, &mut definitions);
+                definitions
+            }
+            Err(_) => self
+                .documents
+                .get(uri)
+                .map(|document| document.definitions.clone())
+                .unwrap_or_default(),
+        };
+        self.documents.insert(
+            uri.to_string(),
+            Document {
+                text: text.to_string(),
+                definitions,
+            },
+        );
+
+        vec![notification(
+            "textDocument/publishDiagnostics",
+            json!({"uri": uri, "diagnostics": diagnostics(uri, text)}),
+        )]
+    }
+
+    fn symbol_at(&self, uri: &str, pos: &Value) -> Option<Symbol> {
+        let text = &self.documents.get(uri)?.text;
+        let (exprs, spans) = parser::parse_spanned(text).ok()?;
+
+        symbol_at(&exprs, &spans, offset(text, pos))
+    }
+
+    /// Definitions of `symbol` in the document, or else in the files it
+    /// imports, with the URI they were found in.
+    fn find_definitions(&self, uri: &str, symbol: &Symbol) -> Vec<(String, String, Definition)> {
+        let mut found = vec![];
+        let mut visited = HashSet::new();
+        let mut pending = vec![uri.to_string()];
+        visited.insert(uri.to_string());
+
+        while !pending.is_empty() {
+            let uri = pending.remove(0);
+            let text = match source_of(&self.documents, &uri) {
+                Some(text) => text,
+                None => continue,
+            };
+            let (exprs, spans) = match parser::parse_spanned(&text) {
+                Ok(parsed) => parsed,
+                Err(_) => continue,
+            };
+
+            let mut defs = vec![];
+            collect_definitions(&exprs, &spans, &mut defs);
+            for def in defs.into_iter().filter(|def| def.name == *symbol) {
+                found.push((uri.clone(), text.clone(), def));
+            }
+            if !found.is_empty() {
+                break;
+            }
+
+            let dir = uri_to_path(&uri)
+                .and_then(|path| path.parent().map(Path::to_path_buf))
+                .unwrap_or_else(|| PathBuf::from("."));
+            for path in exprs
+                .iter()
+                .filter_map(|expr| linter::import_path(expr, &dir))
+            {
+                let import_uri = path_to_uri(&path);
+                if visited.insert(import_uri.clone()) {
+                    pending.push(import_uri);
+                }
+            }
+        }
+
+        found
+    }
+
+    fn definition(&self, uri: &str, pos: &Value) -> Value {
+        let symbol = match self.symbol_at(uri, pos) {
+            Some(symbol) => symbol,
+            None => return Value::Null,
+        };
+
+        let locations: Vec<Value> = self
+            .find_definitions(uri, &symbol)
+            .into_iter()
+            .map(|(uri, text, def)| {
+                json!({"uri": uri, "range": range(&text, def.name_start, def.name_end)})
+            })
+            .collect();
+        match locations.is_empty() {
+            true => Value::Null,
+            false => Value::Array(locations),
+        }
+    }
+
+    fn hover(&self, uri: &str, pos: &Value) -> Value {
+        let symbol = match self.symbol_at(uri, pos) {
+            Some(symbol) => symbol,
+            None => return Value::Null,
+        };
+
+        let text = match self.find_definitions(uri, &symbol).first() {
+            Some((_, _, def)) => def.render(),
+            None => match self.bindings.get(&symbol) {
+                Some(Expr::Fun(fun)) => docs::render(&symbol, fun),
+                Some(value) => format!("{} : {}", Expr::Sym(symbol.clone()), value),
+                None => return Value::Null,
+            },
+        };
+
+        json!({"contents": {"kind": "plaintext", "value": text}})
+    }
+
+    fn completion(&self, uri: &str) -> Value {
+        let mut items: HashMap<String, Value> = HashMap::new();
+        for (symbol, expr) in &self.bindings {
+            let label = Expr::Sym(symbol.clone()).to_string();
+            let item = match expr {
+                Expr::Fun(fun) => json!({
+                    "label": label,
+                    "kind": COMPLETION_FUNCTION,
+                    "detail": docs::signature(symbol, fun),
+                    "documentation": match fun {
+                        Function::Core(_, _, doc) => Some(doc.description.to_string()),
+                        Function::Lambda(_, _, _, doc) => doc.clone(),
+                    },
+                }),
+                _ => json!({"label": label, "kind": COMPLETION_VARIABLE}),
+            };
+            items.insert(label, item);
+        }
+        for label in &["सत्य", "गलत"] {
+            items.insert(
+                label.to_string(),
+                json!({"label": label, "kind": COMPLETION_CONSTANT}),
+            );
+        }
+        if let Some(document) = self.documents.get(uri) {
+            for def in &document.definitions {
+                let kind = match def.params {
+                    Some(_) => COMPLETION_FUNCTION,
+                    None => COMPLETION_VARIABLE,
+                };
+                let label = Expr::Sym(def.name.clone()).to_string();
+                items.insert(
+                    label.clone(),
+                    json!({
+                        "label": label,
+                        "kind": kind,
+                        "detail": def.signature(),
+                        "documentation": def.docstring,
+                    }),
+                );
+            }
+        }
+
+        let mut items: Vec<(String, Value)> = items.into_iter().collect();
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        Value::Array(items.into_iter().map(|(_, item)| item).collect())
+    }
+
+    fn document_symbols(&self, uri: &str) -> Value {
+        let text = match self.documents.get(uri) {
+            Some(document) => &document.text,
+            None => return Value::Null,
+        };
+        let (exprs, spans) = match parser::parse_spanned(text) {
+            Ok(parsed) => parsed,
+            Err(_) => return json!([]),
+        };
+
+        let mut defs = vec![];
+        collect_definitions(&exprs, &spans, &mut defs);
+        let symbols = defs
+            .iter()
+            .map(|def| {
+                let kind = match def.params {
+                    Some(_) => SYMBOL_FUNCTION,
+                    None => SYMBOL_VARIABLE,
+                };
+                json!({
+                    "name": Expr::Sym(def.name.clone()).to_string(),
+                    "detail": def.signature(),
+                    "kind": kind,
+                    "range": range(text, def.start, def.end),
+                    "selectionRange": range(text, def.name_start, def.name_end),
+                })
+            })
+            .collect();
+
+        Value::Array(symbols)
+    }
+}
+
+/// Parse errors, or the linter's warnings when the document parses.
+fn diagnostics(uri: &str, text: &str) -> Vec<Value> {
+    if let Err(pe) = parser::parse(text) {
+        let start = match pe.location {
+            pest::error::InputLocation::Pos(pos) => pos,
+            pest::error::InputLocation::Span((start, _)) => start,
+        };
+        // the last line of pest's report says what was expected
+        let detail = pe.to_string();
+        let detail = detail
+            .lines()
+            .last()
+            .unwrap_or_default()
+            .trim_start_matches(&[' ', '='][..]);
+        return vec![json!({
+            "range": range(text, start, start),
+            "severity": SEVERITY_ERROR,
+            "source": "alpa",
+            "message": format!("पार्स गर्न सकिएन : {}", detail),
+        })];
+    }
+
+    let path = uri_to_path(uri).unwrap_or_default();
+    linter::lint_source(text, &path)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|lint| {
+            json!({
+                "range": range(text, lint.start, lint.end),
+                "severity": SEVERITY_WARNING,
+                "source": "alpa",
+                "message": lint.message,
+            })
+        })
+        .collect()
+}
+
+/// Serves requests until `exit`, returning the exit code the protocol asks
+/// for: 0 after a `shutdown` request, 1 otherwise.
+pub fn serve<R: BufRead, W: Write>(mut input: R, mut output: W) -> io::Result<i32> {
+    let mut server = Server::default();
+
+    while let Some(body) = read_message(&mut input)? {
+        let message: Value = match serde_json::from_slice(&body) {
+            Ok(message) => message,
+            Err(err) => {
+                let reply = error_response(&Value::Null, PARSE_ERROR, &err.to_string());
+                write_message(&mut output, &reply)?;
+                continue;
+            }
+        };
+        if message["method"] == "exit" {
+            break;
+        }
+
+        for reply in server.handle(&message) {
+            write_message(&mut output, &reply)?;
+        }
+    }
+
+    Ok(match server.shutdown {
+        true => 0,
+        false => 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn frame(messages: &[Value]) -> Vec<u8> {
+        let mut input = vec![];
+        for message in messages {
+            write_message(&mut input, message).unwrap();
+        }
+        input
+    }
+
+    fn exchange(messages: &[Value]) -> (i32, Vec<Value>) {
+        let mut output = vec![];
+        let code = serve(Cursor::new(frame(messages)), &mut output).unwrap();
+
+        let mut replies = vec![];
+        let mut output = Cursor::new(output);
+        while let Some(body) = read_message(&mut output).unwrap() {
+            replies.push(serde_json::from_slice(&body).unwrap());
+        }
+        (code, replies)
+    }
+
+    fn request(id: i32, method: &str, params: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+    }
+
+    fn at(uri: &str, line: i32, character: i32) -> Value {
+        json!({"textDocument": {"uri": uri}, "position": {"line": line, "character": character}})
+    }
+
+    #[test]
+    fn test_uri() {
+        let path = Path::new("/tmp/अल्प फाइल.अ");
+        let uri = path_to_uri(path);
+        assert!(uri.starts_with("file:///tmp/%E0%A4%85"));
+        assert!(uri.contains("%20"));
+        assert_eq!(uri_to_path(&uri), Some(path.to_path_buf()));
+    }
+
+    #[test]
+    fn test_position() {
+        let source = "(छाप १)\n(+ क ख)";
+        let pos = position(source, source.find('क').unwrap());
+        assert_eq!(pos, json!({"line": 1, "character": 3}));
+        assert_eq!(offset(source, &pos), source.find('क').unwrap());
+        assert_eq!(
+            offset(source, &json!({"line": 0, "character": 99})),
+            source.find('\n').unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_message_limit() {
+        let mut input = Cursor::new(b"Content-Length: 99999999999\r\n\r\n{}".to_vec());
+        let error = read_message(&mut input).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let mut input = Cursor::new(b"Content-Length: 2\r\n\r\n{}".to_vec());
+        assert_eq!(read_message(&mut input).unwrap(), Some(b"{}".to_vec()));
+    }
+
+    #[test]
+    fn test_session() {
+        let uri = "file:///tmp/परीक्षण.अ";
+        let text = "(फलन '(दोब्बर क) \"दोब्बर गर्ने\" '(* २ क))\n(दोब्बर नभएको)";
+        let (code, replies) = exchange(&[
+            request(1, "initialize", json!({})),
+            notification("initialized", json!({})),
+            notification(
+                "textDocument/didOpen",
+                json!({"textDocument": {"uri": uri, "text": text}}),
+            ),
+            request(2, "textDocument/definition", at(uri, 1, 2)),
+            request(3, "textDocument/hover", at(uri, 1, 2)),
+            request(4, "textDocument/hover", at(uri, 0, 2)),
+            request(
+                5,
+                "textDocument/documentSymbol",
+                json!({"textDocument": {"uri": uri}}),
+            ),
+            request(6, "textDocument/completion", at(uri, 1, 0)),
+            notification(
+                "textDocument/didChange",
+                json!({"textDocument": {"uri": uri}, "contentChanges": [{"text": "(छाप"}]}),
+            ),
+            request(7, "shutdown", json!(null)),
+            notification("exit", json!(null)),
+        ]);
+
+        assert_eq!(code, 0);
+        assert_eq!(replies.len(), 9);
+        assert_eq!(
+            replies[0]["result"]["capabilities"]["hoverProvider"],
+            json!(true)
+        );
+
+        let diagnostics = &replies[1]["params"]["diagnostics"];
+        assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+        assert_eq!(diagnostics[0]["message"], json!("नाम भेटिएन : नभएको"));
+        assert_eq!(
+            diagnostics[0]["range"]["start"],
+            json!({"line": 1, "character": 8})
+        );
+
+        assert_eq!(
+            replies[2]["result"],
+            json!([{"uri": uri, "range": {
+                "start": {"line": 0, "character": 7},
+                "end": {"line": 0, "character": 13},
+            }}])
+        );
+        assert_eq!(
+            replies[3]["result"]["contents"]["value"],
+            json!("प्रयोग : (दोब्बर क)\nविवरण : दोब्बर गर्ने")
+        );
+        assert!(replies[4]["result"]["contents"]["value"]
+            .as_str()
+            .unwrap()
+            .starts_with("प्रयोग : (फलन बुँदाहरु ऽ बाँकी)"));
+
+        let symbols = replies[5]["result"].as_array().unwrap();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0]["name"], json!("दोब्बर"));
+        assert_eq!(symbols[0]["kind"], json!(SYMBOL_FUNCTION));
+
+        let labels: Vec<&str> = replies[6]["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["label"].as_str().unwrap())
+            .collect();
+        for label in &["दोब्बर", "लहरै", "शिर", "+", "सत्य"] {
+            assert!(labels.contains(label), "{}", label);
+        }
+
+        let diagnostics = &replies[7]["params"]["diagnostics"];
+        assert_eq!(diagnostics[0]["severity"], json!(SEVERITY_ERROR));
+        assert_eq!(replies[8], response(&json!(7), Value::Null));
+    }
+
+    #[test]
+    fn test_definition_across_imports() {
+        let dir = std::env::temp_dir().join(format!("alpa-lsp-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("एलएसपी_सहायक.अ"), "; सहायक\n(नामक '(साझा) ४२)\n").unwrap();
+        let uri = path_to_uri(&dir.join("मुख्य.अ"));
+        let text = "(आयात एलएसपी_सहायक)\n(छाप साझा)";
+
+        let (code, replies) = exchange(&[
+            notification(
+                "textDocument/didOpen",
+                json!({"textDocument": {"uri": uri, "text": text}}),
+            ),
+            request(1, "textDocument/definition", at(&uri, 1, 6)),
+            request(2, "textDocument/unknown", json!({})),
+            notification("exit", json!(null)),
+        ]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(code, 1);
+        assert_eq!(replies[0]["params"]["diagnostics"], json!([]));
+        assert_eq!(
+            replies[1]["result"][0]["uri"],
+            json!(path_to_uri(&dir.join("एलएसपी_सहायक.अ")))
+        );
+        assert_eq!(
+            replies[1]["result"][0]["range"]["start"],
+            json!({"line": 1, "character": 8})
+        );
+        assert_eq!(replies[2]["error"]["code"], json!(METHOD_NOT_FOUND));
+    }
+}
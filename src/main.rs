@@ -1,112 +1,453 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
 
-use rustyline::error::ReadlineError;
-use rustyline::Editor;
-
+use alpa::cli::{self, Command, RunOptions, Source};
 use alpa::core;
+use alpa::parser;
+use alpa::docgen;
 use alpa::environment::{self, Env};
-use alpa::types::{Expr, Symbol};
+use alpa::formatter;
+use alpa::linter;
+use alpa::lsp;
+use alpa::ntypes::Sankhya;
+use alpa::profiler::{self, Profiler};
+use alpa::repl;
+use alpa::sandbox;
+use alpa::testing;
+use alpa::tracer;
+use alpa::types::{Error, Expr, Symbol};
+
+const EXIT_USAGE: i32 = 64;
+const EXIT_IO: i32 = 74;
+
+/// Scripts, the REPL and tests run on a thread with this much stack, so deep
+/// recursion reaches the depth limit before it overflows.
+const RUN_STACK_SIZE: usize = 1 << 30;
+
+/// The depth limit when `--max-depth` is not given, and in the REPL and
+/// tests, so that runaway recursion fails early instead of filling the stack
+/// above.
+const DEFAULT_MAX_DEPTH: usize = 10_000;
+
+const SCRIPT_ARGS: &str = "तर्कहरु";
+const SCRIPT_PATH: &str = "लिपि";
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    match &args[..] {
-        [_] => {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let code = match cli::parse_args(&args) {
+        Ok(Command::Repl(options)) => {
             print_banner();
-            repl();
+            with_big_stack(move || {
+                sandbox::install(default_limits());
+                repl::run(&options)
+            })
+        }
+        Ok(Command::Run(mut options)) => {
+            options.limits.max_depth.get_or_insert(DEFAULT_MAX_DEPTH);
+            with_big_stack(move || run(&options))
+        }
+        Ok(Command::Doc(rest)) => doc(&rest),
+        Ok(Command::Fmt(rest)) => fmt(&rest),
+        Ok(Command::Lint(rest)) => lint(&rest),
+        Ok(Command::Test(rest)) => with_big_stack(move || {
+            sandbox::install(default_limits());
+            test(&rest)
+        }),
+        Ok(Command::Lsp) => lsp(),
+        Ok(Command::Version) => {
+            println!("{} version {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+            0
         }
-        [_, arg1] => {
-            let mut env = env_with_stdlib_and_core_fns();
-            eval_import(&mut env, arg1);
+        Ok(Command::Help) => {
+            println!("{}", cli::HELP);
+            0
         }
-        _ => {
-            eprintln!("Invalid number of arguments.");
+        Err(msg) => {
+            eprintln!("{}", msg);
+            eprintln!("थप जानकारीको लागि : alpa help");
+            EXIT_USAGE
         }
+    };
+    process::exit(code);
+}
+
+fn with_big_stack(f: impl FnOnce() -> i32 + Send + 'static) -> i32 {
+    thread::Builder::new()
+        .stack_size(RUN_STACK_SIZE)
+        .spawn(f)
+        .map_or(EXIT_IO, |handle| handle.join().unwrap_or(1))
+}
+
+fn default_limits() -> sandbox::Limits {
+    sandbox::Limits {
+        max_depth: Some(DEFAULT_MAX_DEPTH),
+        ..sandbox::Limits::default()
     }
 }
 
-fn print_banner() {
-    let name = env!("CARGO_PKG_NAME");
-    let version = env!("CARGO_PKG_VERSION");
-    println!("{} version {}", name, version);
-    println!("Use Ctrl-C, or Ctrl-D to exit prompt");
-    println!();
+fn run(options: &RunOptions) -> i32 {
+    let mut env = match options.prelude {
+        true => env_with_stdlib_and_core_fns(),
+        false => environment::new_with_core_fns(),
+    };
+    let args = options
+        .args
+        .iter()
+        .map(|arg| Box::new(Expr::Str(arg.clone())))
+        .collect();
+    environment::bind_global_symbol(
+        &env,
+        &Symbol::Identifier(String::from(SCRIPT_ARGS)),
+        &Expr::QExpr(args),
+    );
+    bind_script_path(&env, String::new());
+    if let Some(trace) = &options.trace {
+        tracer::install(trace.clone());
+    }
+    let profiler = options
+        .profile
+        .as_ref()
+        .map(|_| profiler::install(Profiler::new()));
+    sandbox::install(options.limits.clone());
+
+    let code = run_sources(&mut env, options, profiler.as_deref());
+    if let (Some(profiler), Some(profile)) = (&profiler, &options.profile) {
+        if let Err(e) = profiler::finish(&profiler.borrow(), profile) {
+            eprintln!("Error : {}", e);
+            if code == 0 {
+                return EXIT_IO;
+            }
+        }
+    }
+
+    code
 }
 
-fn env_with_stdlib_and_core_fns() -> Env {
-    let stdlib_content = include_str!("प्रस्तावना.अ");
-    let mut env = environment::new(HashMap::new(), None);
+fn run_sources(env: &mut Env, options: &RunOptions, profiler: Option<&RefCell<Profiler>>) -> i32 {
+    for source in &options.sources {
+        let code = match source {
+            Source::File(file) if options.check => check_file(file),
+            Source::File(file) => {
+                if let Some(profiler) = profiler {
+                    profiler.borrow_mut().add_file(Path::new(file));
+                }
+                eval_file(env, file)
+            }
+            Source::Stdin => {
+                let mut contents = String::new();
+                match io::stdin().read_to_string(&mut contents) {
+                    Ok(_) => {
+                        bind_script_path(env, String::from("-"));
+                        if let Some(profiler) = profiler {
+                            profiler.borrow_mut().add_source("-", &contents);
+                        }
+                        eval_source(env, &contents, options.check, false)
+                    }
+                    Err(e) => {
+                        eprintln!("Error : {}", e);
+                        EXIT_IO
+                    }
+                }
+            }
+            Source::Eval(contents) => {
+                if let Some(profiler) = profiler {
+                    profiler.borrow_mut().add_source("-e", contents);
+                }
+                eval_source(env, contents, options.check, true)
+            }
+        };
+        if code != 0 {
+            return code;
+        }
+    }
 
-    environment::load_core_fns(&env);
-    read_eval(&mut env, &stdlib_content);
+    0
+}
 
-    env
+fn check_file(file: &str) -> i32 {
+    match core::parse_file(Path::new(file)) {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Error : {}", e);
+            e.exit_code()
+        }
+    }
+}
+
+fn check_source(contents: &str) -> i32 {
+    match parser::parse(contents) {
+        Ok(_) => 0,
+        Err(pe) => {
+            let e = Error::ParseError(pe.to_string());
+            eprintln!("Error : {}", e);
+            e.exit_code()
+        }
+    }
 }
 
-fn repl() {
-    let mut rl = Editor::<()>::new();
-    let history_filename = "history.txt";
-    if rl.load_history(&history_filename).is_err() {
-        eprintln!("Could not find previous history.");
+fn eval_source(env: &mut Env, contents: &str, check: bool, print: bool) -> i32 {
+    if check {
+        return check_source(contents);
+    }
+
+    match core::parse_and_eval_str(env, contents) {
+        Ok(Expr::QExpr(results)) => {
+            if let (true, Some(last)) = (print, results.last()) {
+                println!("{}", last);
+            }
+            0
+        }
+        Ok(_) => 0,
+        Err(Error::Exit(code)) => code,
+        Err(e) => {
+            eprintln!("Error : {}", e);
+            e.exit_code()
+        }
+    }
+}
+
+fn doc(args: &[String]) -> i32 {
+    let mut format = docgen::Format::Markdown;
+    let mut out_dir = PathBuf::from("doc");
+    let mut files = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--html" => format = docgen::Format::Html,
+            "--out" => match args.next() {
+                Some(dir) => out_dir = PathBuf::from(dir),
+                None => {
+                    eprintln!("--out लाई फोल्डर चाहिन्छ");
+                    return EXIT_USAGE;
+                }
+            },
+            file => files.push(PathBuf::from(file)),
+        }
+    }
+
+    if files.is_empty() {
+        eprintln!("प्रयोग : alpa doc [--html] [--out <फोल्डर>] <फाइल.अ>...");
+        return EXIT_USAGE;
+    }
+
+    let mut modules = vec![];
+    for file in &files {
+        match docgen::module_from_file(file) {
+            Ok(module) => modules.push(module),
+            Err(e) => {
+                eprintln!("Error : {}", e);
+                return e.exit_code();
+            }
+        }
     }
-    let mut env = env_with_stdlib_and_core_fns();
-    loop {
-        let readline = rl.readline(">> ");
-        match readline {
-            Ok(line) => {
-                rl.add_history_entry(line.as_str());
 
-                read_eval_print(&mut env, &line);
+    match docgen::write(&modules, format, &out_dir) {
+        Ok(written) => {
+            written
+                .iter()
+                .for_each(|path| println!("{}", path.display()));
+            0
+        }
+        Err(e) => {
+            eprintln!("Error : {}", e);
+            EXIT_IO
+        }
+    }
+}
+
+fn fmt(args: &[String]) -> i32 {
+    let check = args.iter().any(|arg| arg == "--check");
+    let paths: Vec<PathBuf> = args
+        .iter()
+        .filter(|arg| *arg != "--check")
+        .map(PathBuf::from)
+        .collect();
+
+    if paths == [PathBuf::from("-")] {
+        let mut source = String::new();
+        if let Err(e) = io::stdin().read_to_string(&mut source) {
+            eprintln!("Error : {}", e);
+            return EXIT_IO;
+        }
+        return match formatter::format(&source) {
+            Ok(formatted) if check && formatted != source => 1,
+            Ok(_) if check => 0,
+            Ok(formatted) => {
+                print!("{}", formatted);
+                0
             }
-            Err(ReadlineError::Interrupted) => {
-                eprintln!("CTRL-C");
-                break;
+            Err(e) => {
+                eprintln!("Error : {}", e);
+                e.exit_code()
             }
-            Err(ReadlineError::Eof) => {
-                eprintln!("CTRL-D");
-                break;
+        };
+    }
+
+    let paths = match paths.is_empty() {
+        true => vec![PathBuf::from(".")],
+        false => paths,
+    };
+    let files = match testing::find_files(&paths, testing::is_alpa_file) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("Error : {}", e);
+            return EXIT_IO;
+        }
+    };
+
+    let mut code = 0;
+    for file in files {
+        let source = match fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("Error : {} : {}", file.display(), e);
+                code = EXIT_IO;
+                continue;
             }
-            Err(err) => {
-                eprintln!("Error: {:?}", err);
-                break;
+        };
+        let formatted = match formatter::format(&source) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                eprintln!("Error : {} : {}", file.display(), e);
+                code = e.exit_code();
+                continue;
             }
+        };
+
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("फर्म्याट नभएको : {}", file.display());
+            code = code.max(1);
+        } else if let Err(e) = fs::write(&file, formatted) {
+            eprintln!("Error : {} : {}", file.display(), e);
+            code = EXIT_IO;
+        } else {
+            println!("{}", file.display());
         }
     }
-    rl.save_history(&history_filename).unwrap();
+
+    code
 }
 
-fn eval_import(env: &mut Env, target: &str) -> () {
-    match target.split('.').collect::<Vec<&str>>()[..] {
-        [module, "अ"] => {
-            let m = vec![Box::new(Expr::Sym(Symbol::Identifier(String::from(
-                module,
-            ))))];
+fn lint(args: &[String]) -> i32 {
+    let paths: Vec<PathBuf> = match args {
+        [] => vec![PathBuf::from(".")],
+        _ => args.iter().map(PathBuf::from).collect(),
+    };
+    let files = match testing::find_files(&paths, testing::is_alpa_file) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("Error : {}", e);
+            return EXIT_IO;
+        }
+    };
 
-            match core::exprs_import(env, &m) {
-                Err(e) => eprintln!("Error : {:?}", e),
-                Ok(_expr) => {
-                    // println!("{:?}", &expr);
-                    // println!("{}", &expr);
+    let mut code = 0;
+    let mut count = 0;
+    for file in files {
+        match linter::lint_file(&file) {
+            Ok(lints) => {
+                for lint in &lints {
+                    println!(
+                        "{}:{}:{}: {}",
+                        file.display(),
+                        lint.line,
+                        lint.col,
+                        lint.message
+                    );
                 }
+                count += lints.len();
+            }
+            Err(e) => {
+                eprintln!("Error : {}", e);
+                code = code.max(e.exit_code());
             }
         }
-        _ => eprintln!("अमान्य फाइल (हुनुपर्ने <फाइलनाम>.अ)"),
+    }
+
+    match (code, count) {
+        (0, 0) => 0,
+        (0, _) => {
+            println!("{} चेतावनी", Sankhya(count as i32));
+            1
+        }
+        _ => code,
     }
 }
 
-fn read_eval(env: &mut Env, line: &str) -> () {
-    match core::parse_and_eval_str(env, line) {
-        Err(e) => eprintln!("Error : {:?}", e),
-        Ok(_expr) => {}
+fn lsp() -> i32 {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    match lsp::serve(stdin.lock(), stdout.lock()) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error : {}", e);
+            EXIT_IO
+        }
+    }
+}
+
+fn test(args: &[String]) -> i32 {
+    let paths: Vec<PathBuf> = match args {
+        [] => vec![PathBuf::from(".")],
+        _ => args.iter().map(PathBuf::from).collect(),
+    };
+
+    match testing::run(&paths) {
+        Ok(summary) if summary.failed == 0 => 0,
+        Ok(_) => 1,
+        Err(e) => {
+            eprintln!("Error : {}", e);
+            EXIT_IO
+        }
+    }
+}
+
+fn print_banner() {
+    let name = env!("CARGO_PKG_NAME");
+    let version = env!("CARGO_PKG_VERSION");
+    println!("{} version {}", name, version);
+    println!("Use Ctrl-C, or Ctrl-D to exit prompt");
+    println!();
+}
+
+fn env_with_stdlib_and_core_fns() -> Env {
+    let mut env = environment::new(HashMap::new(), None);
+
+    environment::load_core_fns(&env);
+    if let Err(e) = environment::load_prelude(&mut env) {
+        eprintln!("Error : {}", e);
     }
+
+    env
 }
 
-fn read_eval_print(env: &mut Env, line: &str) -> () {
-    match core::parse_and_eval_str(env, line) {
-        Err(e) => eprintln!("Error : {:?}", e),
-        Ok(expr) => {
-            // println!("{:?}", &expr);
-            println!("{}", &expr);
+fn bind_script_path(env: &Env, path: String) {
+    environment::bind_global_symbol(
+        env,
+        &Symbol::Identifier(String::from(SCRIPT_PATH)),
+        &Expr::Str(path),
+    );
+}
+
+fn eval_file(env: &mut Env, file: &str) -> i32 {
+    bind_script_path(env, file.to_string());
+
+    match core::eval_file(env, Path::new(file)) {
+        Err(Error::Exit(code)) => code,
+        Err(e) => {
+            eprintln!("Error : {}", e);
+            e.exit_code()
         }
+        Ok(_expr) => 0,
     }
 }
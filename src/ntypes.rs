@@ -40,7 +40,6 @@ impl FromStr for Sankhya {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let value = s
             .graphemes(true)
-            .into_iter()
             .map(|n| match n {
                 "+" => "+",
                 "-" => "-",
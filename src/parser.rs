@@ -1,16 +1,39 @@
+use std::rc::Rc;
+
 use pest::error::Error as PError;
-use pest::iterators::Pair;
+use pest::iterators::{Pair, Pairs};
 use pest::Parser;
 
 use crate::ntypes::Sankhya;
-use crate::types::{Boolean, Expr, ExprsOp, NumOp, QExprOp, QExprsOp, SExprOp, Symbol};
+use crate::types::{
+    BoolOp, Boolean, Expr, ExprsOp, LoopOp, NumOp, QExprOp, QExprsOp, RefOp, SExprOp, Symbol,
+    TestOp, TimeOp, VectorOp,
+};
 
 #[derive(pest_derive::Parser)]
 #[grammar = "grammar.pest"]
 struct AlpaParser;
 
+#[allow(clippy::result_large_err)]
+fn parse_program(source: &str) -> Result<Pair<'_, Rule>, PError<Rule>> {
+    let program = AlpaParser::parse(Rule::alpa, source)?
+        .find(|pair| pair.as_rule() == Rule::program)
+        .unwrap();
+
+    Ok(program)
+}
+
+/// Parses `source` keeping comments and whitespace, for the formatter.
+#[allow(clippy::result_large_err)]
+pub fn parse_layout(source: &str) -> Result<Pairs<'_, Rule>, PError<Rule>> {
+    let file = AlpaParser::parse(Rule::fmt_file, source)?.next().unwrap();
+
+    Ok(file.into_inner())
+}
+
+#[allow(clippy::result_large_err)]
 pub fn parse(source: &str) -> Result<Expr, PError<Rule>> {
-    let parse_tree_pair = AlpaParser::parse(Rule::alpa, source)?.next().unwrap();
+    let parse_tree_pair = parse_program(source)?;
 
     let parsed_expr = match parse_tree_pair.as_rule() {
         Rule::program => parse_s_expression(parse_tree_pair),
@@ -20,6 +43,66 @@ pub fn parse(source: &str) -> Result<Expr, PError<Rule>> {
     Ok(parsed_expr)
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub col: usize,
+    pub children: Vec<Span>,
+}
+
+pub type Spanned = (Vec<Box<Expr>>, Vec<Span>);
+
+#[allow(clippy::result_large_err)]
+pub fn parse_spanned(source: &str) -> Result<Spanned, PError<Rule>> {
+    let parse_tree_pair = parse_program(source)?;
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect();
+
+    let mut exprs = vec![];
+    let mut spans = vec![];
+    for pair in parse_tree_pair.into_inner() {
+        match pair.as_rule() {
+            Rule::expr => {
+                spans.push(parse_span(source, &line_starts, pair.clone()));
+                exprs.push(Box::new(parse_expression(pair)));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    Ok((exprs, spans))
+}
+
+fn parse_span(source: &str, line_starts: &[usize], pair: Pair<Rule>) -> Span {
+    let span = pair.as_span();
+    let start = span.start();
+    let line = match line_starts.binary_search(&start) {
+        Ok(idx) => idx,
+        Err(idx) => idx - 1,
+    };
+    let col = source[line_starts[line]..start].chars().count() + 1;
+
+    let inner = pair.into_inner().next().unwrap();
+    let children = match inner.as_rule() {
+        Rule::sexpr | Rule::qexpr | Rule::vector => inner
+            .into_inner()
+            .map(|pair| parse_span(source, line_starts, pair))
+            .collect(),
+        _ => vec![],
+    };
+
+    Span {
+        start,
+        end: span.end(),
+        line: line + 1,
+        col,
+        children,
+    }
+}
+
 fn parse_s_expression(pair: Pair<Rule>) -> Expr {
     Expr::SExpr(parse_expressions(pair))
 }
@@ -28,6 +111,16 @@ fn parse_q_expression(pair: Pair<Rule>) -> Expr {
     Expr::QExpr(parse_expressions(pair))
 }
 
+fn parse_vector(pair: Pair<Rule>) -> Expr {
+    let items = parse_expressions(pair)
+        .into_iter()
+        .map(|expr| *expr)
+        .collect();
+
+    Expr::Vector(Rc::new(items))
+}
+
+#[allow(clippy::vec_box)]
 fn parse_expressions(pair: Pair<Rule>) -> Vec<Box<Expr>> {
     let mut exprs: Vec<Box<Expr>> = vec![];
 
@@ -54,6 +147,7 @@ fn parse_expression(pair: Pair<Rule>) -> Expr {
         Rule::symbol => parse_symbol(pair),
         Rule::sexpr => parse_s_expression(pair),
         Rule::qexpr => parse_q_expression(pair),
+        Rule::vector => parse_vector(pair),
         _ => unreachable!(),
     }
 }
@@ -82,11 +176,17 @@ fn parse_symbol(pair: Pair<Rule>) -> Expr {
     let pair = pair.into_inner().next().unwrap();
 
     match pair.as_rule() {
+        Rule::vector_op => parse_vector_op(pair),
+        Rule::time_op => parse_time_op(pair),
         Rule::exprs_op => parse_exprs_op(pair),
+        Rule::bool_op => parse_bool_op(pair),
+        Rule::loop_op => parse_loop_op(pair),
+        Rule::ref_op => parse_ref_op(pair),
         Rule::numbers_op => parse_numbers_op(pair),
         Rule::qexpr_op => parse_qexpr_op(pair),
         Rule::qexprs_op => parse_qexprs_op(pair),
         Rule::sexpr_op => parse_sexpr_op(pair),
+        Rule::test_op => parse_test_op(pair),
         Rule::identifier => parse_identifier(pair),
         _ => unreachable!(),
     }
@@ -99,16 +199,97 @@ fn parse_exprs_op(pair: Pair<Rule>) -> Expr {
         Rule::equal => ExprsOp::Equal,
         Rule::not_equal => ExprsOp::NotEqual,
         Rule::yadi => ExprsOp::If,
+        Rule::milau => ExprsOp::Match,
         Rule::list => ExprsOp::List,
         Rule::import => ExprsOp::Import,
         Rule::print => ExprsOp::Print,
         Rule::error => ExprsOp::Error,
+        Rule::help => ExprsOp::Help,
+        Rule::exit => ExprsOp::Exit,
         _ => unreachable!(),
     };
 
     Expr::Sym(Symbol::ExprsOp(op))
 }
 
+fn parse_bool_op(pair: Pair<Rule>) -> Expr {
+    let pair = pair.into_inner().next().unwrap();
+
+    let op = match pair.as_rule() {
+        Rule::not => BoolOp::Not,
+        Rule::or => BoolOp::Or,
+        Rule::and => BoolOp::And,
+        _ => unreachable!(),
+    };
+
+    Expr::Sym(Symbol::BoolOp(op))
+}
+
+fn parse_loop_op(pair: Pair<Rule>) -> Expr {
+    let pair = pair.into_inner().next().unwrap();
+
+    let op = match pair.as_rule() {
+        Rule::jabasamma => LoopOp::While,
+        Rule::for_each => LoopOp::ForEach,
+        Rule::count => LoopOp::Count,
+        Rule::range => LoopOp::Range,
+        Rule::rok => LoopOp::Break,
+        Rule::jari => LoopOp::Continue,
+        _ => unreachable!(),
+    };
+
+    Expr::Sym(Symbol::LoopOp(op))
+}
+
+fn parse_vector_op(pair: Pair<Rule>) -> Expr {
+    let pair = pair.into_inner().next().unwrap();
+
+    let op = match pair.as_rule() {
+        Rule::vector_new => VectorOp::New,
+        Rule::vector_get => VectorOp::Get,
+        Rule::vector_set => VectorOp::Set,
+        Rule::push => VectorOp::Push,
+        Rule::slice => VectorOp::Slice,
+        Rule::to_vector => VectorOp::FromQExpr,
+        Rule::to_qexpr => VectorOp::ToQExpr,
+        _ => unreachable!(),
+    };
+
+    Expr::Sym(Symbol::VectorOp(op))
+}
+
+fn parse_time_op(pair: Pair<Rule>) -> Expr {
+    let pair = pair.into_inner().next().unwrap();
+
+    let op = match pair.as_rule() {
+        Rule::now => TimeOp::Now,
+        Rule::from_bs => TimeOp::FromBs,
+        Rule::to_bs => TimeOp::ToBs,
+        Rule::from_gregorian => TimeOp::FromGregorian,
+        Rule::to_gregorian => TimeOp::ToGregorian,
+        Rule::time_add => TimeOp::Add,
+        Rule::time_diff => TimeOp::Diff,
+        Rule::time_format => TimeOp::Format,
+        _ => unreachable!(),
+    };
+
+    Expr::Sym(Symbol::TimeOp(op))
+}
+
+fn parse_ref_op(pair: Pair<Rule>) -> Expr {
+    let pair = pair.into_inner().next().unwrap();
+
+    let op = match pair.as_rule() {
+        Rule::reference => RefOp::New,
+        Rule::deref => RefOp::Get,
+        Rule::set => RefOp::Set,
+        Rule::swap => RefOp::Swap,
+        _ => unreachable!(),
+    };
+
+    Expr::Sym(Symbol::RefOp(op))
+}
+
 fn parse_identifier(pair: Pair<Rule>) -> Expr {
     Expr::Sym(Symbol::Identifier(pair.as_str().to_string()))
 }
@@ -139,6 +320,7 @@ fn parse_qexpr_op(pair: Pair<Rule>) -> Expr {
         Rule::tail => QExprOp::Tail,
         Rule::len => QExprOp::Len,
         Rule::eval => QExprOp::Eval,
+        Rule::trace => QExprOp::Trace,
         _ => unreachable!(),
     };
 
@@ -154,6 +336,8 @@ fn parse_qexprs_op(pair: Pair<Rule>) -> Expr {
         Rule::def => QExprsOp::Def,
         Rule::put => QExprsOp::Put,
         Rule::lambda => QExprsOp::Lambda,
+        Rule::sthaniya => QExprsOp::Let,
+        Rule::punah_sthaniya => QExprsOp::LetRec,
         _ => unreachable!(),
     };
 
@@ -171,6 +355,20 @@ fn parse_sexpr_op(pair: Pair<Rule>) -> Expr {
     Expr::Sym(Symbol::SExprOp(op))
 }
 
+fn parse_test_op(pair: Pair<Rule>) -> Expr {
+    let pair = pair.into_inner().next().unwrap();
+
+    let op = match pair.as_rule() {
+        Rule::assert => TestOp::Assert,
+        Rule::assert_equal => TestOp::AssertEqual,
+        Rule::assert_throws => TestOp::AssertThrows,
+        Rule::test => TestOp::Test,
+        _ => unreachable!(),
+    };
+
+    Expr::Sym(Symbol::TestOp(op))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,4 +399,68 @@ mod tests {
         ]);
         assert_eq!(parse(input), Ok(expected));
     }
+
+    #[test]
+    fn test_parse_shebang() {
+        let expected = Expr::SExpr(vec![Box::new(Expr::Num(Sankhya(1)))]);
+        assert_eq!(parse("#!/usr/bin/env alpa\n१"), Ok(expected.clone()));
+        assert_eq!(parse("#!/usr/bin/env alpa"), Ok(Expr::SExpr(vec![])));
+        assert!(parse("१\n#!/usr/bin/env alpa").is_err());
+
+        let (_, spans) = parse_spanned("#!/usr/bin/env alpa\n१").unwrap();
+        assert_eq!((spans[0].line, spans[0].col), (2, 1));
+    }
+
+    #[test]
+    fn test_parse_spanned() {
+        let input = "; टिप्पणी\n(+ १\n   (* २ ३))\n\"क\"";
+        let (exprs, spans) = parse_spanned(input).unwrap();
+
+        assert_eq!(exprs.len(), 2);
+        assert_eq!(spans.len(), 2);
+        assert_eq!((spans[0].line, spans[0].col), (2, 1));
+        assert_eq!(spans[0].children.len(), 3);
+        assert_eq!((spans[0].children[2].line, spans[0].children[2].col), (3, 4));
+        assert_eq!(&input[spans[0].children[2].start..spans[0].children[2].end], "(* २ ३)");
+        assert_eq!((spans[1].line, spans[1].col), (4, 1));
+    }
+
+    #[test]
+    fn test_parse_keyword_prefix() {
+        let ident = |name: &str| Box::new(Expr::Sym(Symbol::Identifier(name.to_string())));
+
+        assert_eq!(
+            parse("दाबीहरु (दाबी ५)"),
+            Ok(Expr::SExpr(vec![
+                ident("दाबीहरु"),
+                Box::new(Expr::SExpr(vec![
+                    Box::new(Expr::Sym(Symbol::TestOp(TestOp::Assert))),
+                    Box::new(Expr::Num(Sankhya(5))),
+                ])),
+            ]))
+        );
+        for name in [
+            "परीक्षणहरु",
+            "दाबीबराबरी",
+            "रोकेर",
+            "दायराभित्र",
+            "राखेको",
+            "हेरचाह",
+            "तानेर",
+            "भेक्टरहरु",
+            "अहिलेको",
+            "मितिहरु",
+            "हैनभने",
+            "अथवाको",
+            "साथैमा",
+            "स्थानीयता",
+            "पुनःस्थानीयमा",
+            "मद्दतगार",
+            "निकासी",
+            "मिलाउने",
+            "ट्रेसर",
+        ] {
+            assert_eq!(parse(name), Ok(Expr::SExpr(vec![ident(name)])));
+        }
+    }
 }
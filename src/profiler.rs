@@ -0,0 +1,352 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::core;
+use crate::environment::{self, Env};
+use crate::evaluator::{self, Frame, Hook, SharedHook};
+use crate::parser::{self, Span};
+use crate::types::{Error, Expr, Function};
+
+pub const DEFAULT_TOP: usize = 20;
+
+const PRELUDE_NAME: &str = "प्रस्तावना.अ";
+const FORM_WIDTH: usize = 40;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Options {
+    /// Where to write the folded stacks, if anywhere.
+    pub folded: Option<PathBuf>,
+    /// Rows to show in each table of the report.
+    pub top: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            folded: None,
+            top: DEFAULT_TOP,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Stats {
+    pub calls: usize,
+    /// Time from entry to exit, counted once for recursive calls.
+    pub inclusive: Duration,
+    /// Time spent in the application itself, not in the ones it makes.
+    pub exclusive: Duration,
+}
+
+/// An application that has been entered but not exited.
+struct Active {
+    name: String,
+    form: String,
+    start: Instant,
+    children: Duration,
+}
+
+/// Counts calls and measures inclusive and exclusive time for every
+/// function application, by the name of the function and by where the
+/// application is written.
+pub struct Profiler {
+    /// Source locations of the applications in the known files, by the
+    /// application as printed.
+    index: HashMap<String, String>,
+    stack: Vec<Active>,
+    pub functions: HashMap<String, Stats>,
+    pub forms: HashMap<String, Stats>,
+    /// Exclusive time of each call stack, outermost function first.
+    pub folded: HashMap<String, Duration>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Profiler::new()
+    }
+}
+
+/// Records where each application in `exprs`, quoted bodies included, is
+/// written. The first of identical applications wins.
+fn index_forms(
+    exprs: &[Box<Expr>],
+    spans: &[Span],
+    name: &str,
+    index: &mut HashMap<String, String>,
+) {
+    for (expr, span) in exprs.iter().zip(spans.iter()) {
+        if let Expr::SExpr(items) | Expr::QExpr(items) = &**expr {
+            if items.len() > 1 {
+                index
+                    .entry(Expr::SExpr(items.clone()).to_string())
+                    .or_insert_with(|| format!("{}:{}:{}", name, span.line, span.col));
+            }
+            index_forms(items, &span.children, name, index);
+        }
+    }
+}
+
+/// The name the function was called by, `λ` for anonymous ones.
+fn function_name(frame: &Frame) -> String {
+    match (&frame.function, &frame.fun) {
+        (Some(sym), _) | (None, Function::Core(sym, _, _)) => Expr::Sym(sym.clone()).to_string(),
+        (None, Function::Lambda(..)) => String::from("λ"),
+    }
+}
+
+fn shorten(text: &str, width: usize) -> String {
+    match text.chars().count() > width {
+        true => format!("{}…", text.chars().take(width - 1).collect::<String>()),
+        false => text.to_string(),
+    }
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+/// Stats sorted by exclusive time, then by calls, most expensive first.
+fn ranked(stats: &HashMap<String, Stats>) -> Vec<(&String, &Stats)> {
+    let mut rows: Vec<(&String, &Stats)> = stats.iter().collect();
+    rows.sort_by(|a, b| (b.1.exclusive, b.1.calls, a.0).cmp(&(a.1.exclusive, a.1.calls, b.0)));
+
+    rows
+}
+
+impl Profiler {
+    /// A profiler that already knows where the prelude's applications are.
+    pub fn new() -> Profiler {
+        let mut profiler = Profiler {
+            index: HashMap::new(),
+            stack: vec![],
+            functions: HashMap::new(),
+            forms: HashMap::new(),
+            folded: HashMap::new(),
+        };
+        profiler.add_source(PRELUDE_NAME, environment::PRELUDE);
+
+        profiler
+    }
+
+    /// Remembers the applications in `source`, so they are reported by
+    /// location. Sources that do not parse are left out.
+    pub fn add_source(&mut self, name: &str, source: &str) {
+        if let Ok((exprs, spans)) = parser::parse_spanned(source) {
+            index_forms(&exprs, &spans, name, &mut self.index);
+        }
+    }
+
+    pub fn add_file(&mut self, path: &Path) {
+        if let Ok(source) = fs::read_to_string(path) {
+            self.add_source(&path.display().to_string(), &source);
+        }
+    }
+
+    /// Where `form` is written, if it is in a known source.
+    pub fn location(&self, form: &str) -> Option<&String> {
+        self.index.get(form)
+    }
+
+    /// Tables of the most expensive functions and source locations.
+    pub fn report(&self, top: usize) -> String {
+        let mut out = String::new();
+        let total: Duration = self.functions.values().map(|stats| stats.exclusive).sum();
+        out.push_str(&format!("कुल समय : {} ms\n\n", millis(total)));
+
+        out.push_str("  प्रयोग      कुल ms    आफ्नै ms  फलन\n");
+        for (name, stats) in ranked(&self.functions).into_iter().take(top) {
+            out.push_str(&format!(
+                "{:>8} {:>11} {:>11}  {}\n",
+                stats.calls,
+                millis(stats.inclusive),
+                millis(stats.exclusive),
+                name
+            ));
+        }
+
+        out.push_str("\n  प्रयोग      कुल ms    आफ्नै ms  स्थान\n");
+        for (form, stats) in ranked(&self.forms).into_iter().take(top) {
+            let location = match self.location(form) {
+                Some(location) => format!("{} {}", location, shorten(form, FORM_WIDTH)),
+                None => shorten(form, FORM_WIDTH),
+            };
+            out.push_str(&format!(
+                "{:>8} {:>11} {:>11}  {}\n",
+                stats.calls,
+                millis(stats.inclusive),
+                millis(stats.exclusive),
+                location
+            ));
+        }
+
+        out
+    }
+
+    /// One line per call stack with its exclusive time in microseconds, as
+    /// read by flamegraph.pl and inferno.
+    pub fn write_folded(&self, output: &mut dyn Write) -> io::Result<()> {
+        let mut stacks: Vec<(&String, &Duration)> = self.folded.iter().collect();
+        stacks.sort();
+        for (stack, duration) in stacks {
+            writeln!(output, "{} {}", stack, duration.as_micros())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Adds an exited application to `stats`. Inclusive time is only added by
+/// the outermost of nested applications with the same key, so recursion is
+/// not counted twice.
+fn add(
+    stats: &mut HashMap<String, Stats>,
+    key: &str,
+    nested: bool,
+    elapsed: Duration,
+    own: Duration,
+) {
+    let entry = stats.entry(key.to_string()).or_default();
+    entry.calls += 1;
+    entry.exclusive += own;
+    if !nested {
+        entry.inclusive += elapsed;
+    }
+}
+
+impl Hook for Profiler {
+    fn enter(&mut self, frames: &[Frame]) -> Result<(), Error> {
+        let frame = &frames[frames.len() - 1];
+        self.stack.push(Active {
+            name: function_name(frame),
+            form: Expr::SExpr(frame.form.clone()).to_string(),
+            start: Instant::now(),
+            children: Duration::ZERO,
+        });
+
+        Ok(())
+    }
+
+    fn exit(&mut self, _frames: &[Frame], _result: &Result<Expr, Error>) {
+        let active = match self.stack.pop() {
+            Some(active) => active,
+            None => return,
+        };
+        let elapsed = active.start.elapsed();
+        let own = elapsed.saturating_sub(active.children);
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children += elapsed;
+        }
+
+        let name_nested = self.stack.iter().any(|outer| outer.name == active.name);
+        let form_nested = self.stack.iter().any(|outer| outer.form == active.form);
+        add(&mut self.functions, &active.name, name_nested, elapsed, own);
+        add(&mut self.forms, &active.form, form_nested, elapsed, own);
+
+        let mut names: Vec<&str> = self.stack.iter().map(|outer| outer.name.as_str()).collect();
+        names.push(&active.name);
+        *self.folded.entry(names.join(";")).or_default() += own;
+    }
+}
+
+/// Profiles the rest of the program, returning the profiler to report from.
+pub fn install(profiler: Profiler) -> Rc<RefCell<Profiler>> {
+    let profiler = Rc::new(RefCell::new(profiler));
+    evaluator::set_hook(Some(Rc::clone(&profiler) as SharedHook));
+
+    profiler
+}
+
+/// Prints the report to stderr and writes the folded stacks if asked to.
+pub fn finish(profiler: &Profiler, options: &Options) -> io::Result<()> {
+    eprint!("{}", profiler.report(options.top));
+    match &options.folded {
+        Some(path) => profiler.write_folded(&mut fs::File::create(path)?),
+        None => Ok(()),
+    }
+}
+
+/// Evaluates `source` while `profiler` measures it, then puts back whatever
+/// hook was installed before.
+pub fn eval_str(
+    profiler: &Rc<RefCell<Profiler>>,
+    env: &mut Env,
+    source: &str,
+) -> Result<Expr, Error> {
+    profiler.borrow_mut().add_source("<repl>", source);
+    let previous = evaluator::set_hook(Some(Rc::clone(profiler) as SharedHook));
+    let result = core::parse_and_eval_str(env, source);
+    evaluator::set_hook(previous);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_counts() {
+        let mut env = environment::new_with_prelude().unwrap();
+        let source = "(फलन '(दोब्बर क) '(* २ क))\n(लहरै दोब्बर '(१ २ ३))";
+        let profiler = Rc::new(RefCell::new(Profiler::new()));
+        let result = eval_str(&profiler, &mut env, source);
+        assert!(result.is_ok());
+
+        let profiler = profiler.borrow();
+        assert_eq!(profiler.functions["लहरै"].calls, 1);
+        assert_eq!(profiler.functions["*"].calls, 3);
+        for stats in profiler.functions.values() {
+            assert!(stats.exclusive <= stats.inclusive);
+        }
+        assert_eq!(
+            profiler.location("(लहरै दोब्बर '(१ २ ३))"),
+            Some(&String::from("<repl>:2:1"))
+        );
+        assert_eq!(profiler.forms["(* २ क)"].calls, 3);
+        assert_eq!(
+            profiler.location("(* २ क)"),
+            Some(&String::from("<repl>:1:18"))
+        );
+        assert!(profiler
+            .folded
+            .keys()
+            .any(|stack| stack.starts_with("लहरै;") && stack.ends_with(";*")));
+    }
+
+    #[test]
+    fn test_profile_recursion() {
+        let mut env = environment::new_with_prelude().unwrap();
+        core::parse_and_eval_str(
+            &mut env,
+            "(फलन '(फिब न) '(यदि (< न २) '(न) '(+ (फिब (- न १)) (फिब (- न २)))))",
+        )
+        .unwrap();
+
+        let profiler = Rc::new(RefCell::new(Profiler::new()));
+        eval_str(&profiler, &mut env, "(फिब ५)").unwrap();
+
+        let profiler = profiler.borrow();
+        let fib = &profiler.functions["फिब"];
+        assert_eq!(fib.calls, 15);
+        assert!(fib.inclusive <= profiler.forms["(फिब ५)"].inclusive);
+
+        let mut folded = vec![];
+        profiler.write_folded(&mut folded).unwrap();
+        let folded = String::from_utf8(folded).unwrap();
+        assert!(folded
+            .lines()
+            .any(|line| line.starts_with("फिब;यदि;+;फिब ")));
+        assert!(folded.lines().all(|line| line
+            .rsplit_once(' ')
+            .unwrap()
+            .1
+            .parse::<u128>()
+            .is_ok()));
+        assert!(profiler.report(5).contains("फिब"));
+    }
+}
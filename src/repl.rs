@@ -0,0 +1,514 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Instant;
+
+use rustyline::error::ReadlineError;
+use rustyline::{Config, Editor};
+
+use crate::core;
+use crate::debugger::{self, Debugger};
+use crate::docs;
+use crate::environment::{self, Env};
+use crate::ntypes::Sankhya;
+use crate::parser;
+use crate::profiler::{self, Profiler};
+use crate::types::{Error, Expr, Symbol};
+
+const HELP: &str = "\
+:help, :h          यो सहायता देखाउने (show this help)
+:load <फाइल>       फाइल लोड गर्ने (load a .अ file)
+:reload            पछिल्लो लोड गरिएको फाइल फेरि लोड गर्ने (reload the last file)
+:reset             प्रस्तावनासहित नयाँ वातावरण (fresh prelude environment)
+:env               प्रयोगकर्ताले बनाएका नामहरु (list user bindings)
+:time <एक्सपर्>     मूल्याङ्कनको समय नाप्ने (time an evaluation)
+:profile <एक्सपर्>  फलन र स्थान अनुसार समय नाप्ने (profile an evaluation)
+:type <एक्सपर्>     मानको प्रकार देखाउने (show a value's type)
+:doc <नाम>         नामको विवरण देखाउने (show documentation for a symbol)
+:debug <एक्सपर्>    डिबगरमा पाइलैपिच्छे चलाउने (step through an evaluation)
+:break [नाम|लाइन]  ब्रेकपोइन्ट राख्ने, नदिए सूची देखाउने (add or list breakpoints,
+                   by function name, line of the last loaded file or फाइल:लाइन)
+:delete <n>        ब्रेकपोइन्ट हटाउने (remove a breakpoint)";
+
+const DEFAULT_HISTORY_SIZE: usize = 1000;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Options {
+    pub prelude: bool,
+    pub init: bool,
+    pub history: Option<PathBuf>,
+    pub history_size: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            prelude: true,
+            init: true,
+            history: None,
+            history_size: None,
+        }
+    }
+}
+
+fn env_path(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    env_path(var)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env_path("HOME").map(|home| home.join(fallback)))
+}
+
+/// `--history`, then `$ALPA_HISTORY`, then `$XDG_DATA_HOME/alpa/history`.
+pub fn history_path(options: &Options) -> Option<PathBuf> {
+    options
+        .history
+        .clone()
+        .or_else(|| env_path("ALPA_HISTORY"))
+        .or_else(|| xdg_dir("XDG_DATA_HOME", ".local/share").map(|dir| dir.join("alpa/history")))
+}
+
+/// `--history-size`, then `$ALPA_HISTORY_SIZE`, then 1000 entries.
+pub fn history_size(options: &Options) -> usize {
+    options
+        .history_size
+        .or_else(|| env::var("ALPA_HISTORY_SIZE").ok()?.parse().ok())
+        .unwrap_or(DEFAULT_HISTORY_SIZE)
+}
+
+/// `$ALPA_INIT`, then `$XDG_CONFIG_HOME/alpa/init.अ`, then `~/.alpaarc`.
+pub fn init_path() -> Option<PathBuf> {
+    if let Some(path) = env_path("ALPA_INIT") {
+        return Some(path);
+    }
+
+    let candidates = vec![
+        xdg_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join("alpa/init.अ")),
+        env_path("HOME").map(|home| home.join(".alpaarc")),
+    ];
+    candidates.into_iter().flatten().find(|path| path.is_file())
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Help,
+    Load(String),
+    Reload,
+    Reset,
+    Env,
+    Time(String),
+    Profile(String),
+    Type(String),
+    Doc(String),
+    Debug(String),
+    Break(String),
+    Delete(usize),
+}
+
+pub fn parse_command(line: &str) -> Option<Result<Command, String>> {
+    let line = line.trim();
+    if !line.starts_with(':') {
+        return None;
+    }
+
+    let (name, arg) = match line.find(char::is_whitespace) {
+        Some(idx) => (&line[1..idx], line[idx..].trim()),
+        None => (&line[1..], ""),
+    };
+
+    let command = match (name, arg) {
+        ("help", "") | ("h", "") => Ok(Command::Help),
+        ("reload", "") => Ok(Command::Reload),
+        ("reset", "") => Ok(Command::Reset),
+        ("env", "") => Ok(Command::Env),
+        ("load", "")
+        | ("time", "")
+        | ("profile", "")
+        | ("type", "")
+        | ("doc", "")
+        | ("debug", "") => Err(format!(":{} लाई एउटा तर्क चाहिन्छ", name)),
+        ("load", path) => Ok(Command::Load(path.to_string())),
+        ("time", src) => Ok(Command::Time(src.to_string())),
+        ("profile", src) => Ok(Command::Profile(src.to_string())),
+        ("type", src) => Ok(Command::Type(src.to_string())),
+        ("doc", sym) => Ok(Command::Doc(sym.to_string())),
+        ("debug", src) => Ok(Command::Debug(src.to_string())),
+        ("break", spec) => Ok(Command::Break(spec.to_string())),
+        ("delete", n) => match n.parse::<Sankhya>() {
+            Ok(Sankhya(n)) if n > 0 => Ok(Command::Delete(n as usize)),
+            _ => Err(format!(":{} लाई ब्रेकपोइन्टको नम्बर चाहिन्छ", name)),
+        },
+        ("help", _) | ("h", _) | ("reload", _) | ("reset", _) | ("env", _) => {
+            Err(format!(":{} ले कुनै तर्क लिँदैन", name))
+        }
+        _ => Err(format!("अज्ञात आदेश :{} (:help हेर्नुहोस्)", name)),
+    };
+
+    Some(command)
+}
+
+struct Session {
+    env: Env,
+    prelude: bool,
+    init: Option<PathBuf>,
+    baseline: HashMap<Symbol, Expr>,
+    last_loaded: Option<String>,
+    debugger: Rc<RefCell<Debugger>>,
+}
+
+impl Session {
+    fn new(prelude: bool, init: Option<PathBuf>) -> Result<Session, Error> {
+        let mut env = match prelude {
+            true => environment::new_with_prelude()?,
+            false => environment::new_with_core_fns(),
+        };
+        if let Some(path) = &init {
+            if let Err(e) = core::eval_file(&mut env, path) {
+                eprintln!("Error : {} ({})", e, path.display());
+            }
+        }
+        let baseline = environment::bindings(&env);
+
+        Ok(Session {
+            env,
+            prelude,
+            init,
+            baseline,
+            last_loaded: None,
+            debugger: Rc::new(RefCell::new(Debugger::default())),
+        })
+    }
+
+    fn user_bindings(&self) -> Vec<(Symbol, Expr)> {
+        let mut bindings: Vec<(Symbol, Expr)> = environment::bindings(&self.env)
+            .into_iter()
+            .filter(|(sym, expr)| self.baseline.get(sym) != Some(expr))
+            .collect();
+        bindings.sort_by_key(|(sym, _)| Expr::Sym(sym.clone()).to_string());
+
+        bindings
+    }
+
+    /// Evaluates `source`, stopping at breakpoints if there are any.
+    fn eval(&mut self, source: &str) -> Result<Expr, Error> {
+        let watched = !self.debugger.borrow().breakpoints.is_empty();
+        match watched {
+            false => core::parse_and_eval_str(&mut self.env, source),
+            true => debugger::eval_str(&self.debugger, &mut self.env, source),
+        }
+    }
+
+    fn load(&mut self, path: &str) -> Result<Expr, Error> {
+        self.debugger.borrow_mut().add_source(Path::new(path));
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                self.last_loaded = Some(path.to_string());
+                core::within_file(Path::new(path), || self.eval(&contents))
+            }
+            Err(_) => Err(Error::ImportError(Expr::Str(path.to_string()))),
+        }
+    }
+
+    fn run_command(&mut self, command: Command) -> Result<(), Error> {
+        match command {
+            Command::Help => println!("{}", HELP),
+            Command::Load(path) => {
+                self.load(&path)?;
+                println!("{} लोड भयो", path);
+            }
+            Command::Reload => match self.last_loaded.clone() {
+                Some(path) => {
+                    self.load(&path)?;
+                    println!("{} फेरि लोड भयो", path);
+                }
+                None => eprintln!("अहिलेसम्म कुनै फाइल लोड गरिएको छैन"),
+            },
+            Command::Reset => {
+                *self = Session::new(self.prelude, self.init.clone())?;
+                println!("वातावरण रिसेट भयो");
+            }
+            Command::Env => {
+                let bindings = self.user_bindings();
+                if bindings.is_empty() {
+                    println!("प्रयोगकर्ताले कुनै नाम बनाएको छैन");
+                }
+                for (sym, expr) in bindings {
+                    println!("{} : {}", Expr::Sym(sym.clone()), describe(&sym, &expr));
+                }
+            }
+            Command::Time(src) => {
+                let start = Instant::now();
+                let result = core::parse_and_eval_str(&mut self.env, &src)?;
+                let elapsed = start.elapsed();
+                println!("{}", last_value(result));
+                println!("समय : {:?}", elapsed);
+            }
+            Command::Profile(src) => {
+                let profiler = Rc::new(RefCell::new(Profiler::new()));
+                let result = profiler::eval_str(&profiler, &mut self.env, &src);
+                print!("{}", profiler.borrow().report(profiler::DEFAULT_TOP));
+                println!("{}", last_value(result?));
+            }
+            Command::Type(src) => {
+                let result = core::parse_and_eval_str(&mut self.env, &src)?;
+                println!("{}", last_value(result).type_name());
+            }
+            Command::Debug(src) => {
+                self.debugger.borrow_mut().step_in();
+                let result = debugger::eval_str(&self.debugger, &mut self.env, &src)?;
+                println!("{}", last_value(result));
+            }
+            Command::Break(spec) if spec.is_empty() => {
+                let debugger = self.debugger.borrow();
+                if debugger.breakpoints.is_empty() {
+                    println!("कुनै ब्रेकपोइन्ट छैन");
+                }
+                for (idx, breakpoint) in debugger.breakpoints.iter().enumerate() {
+                    println!("{} : {}", idx + 1, breakpoint);
+                }
+            }
+            Command::Break(spec) => match self.debugger.borrow_mut().add_breakpoint(&spec) {
+                Ok(breakpoint) => println!("ब्रेकपोइन्ट : {}", breakpoint),
+                Err(msg) => eprintln!("{}", msg),
+            },
+            Command::Delete(n) => {
+                let mut debugger = self.debugger.borrow_mut();
+                match n <= debugger.breakpoints.len() {
+                    true => println!("{} हटाइयो", debugger.breakpoints.remove(n - 1)),
+                    false => eprintln!("{} नम्बरको ब्रेकपोइन्ट छैन", n),
+                }
+            }
+            Command::Doc(name) => match parser::parse(&name) {
+                Ok(Expr::SExpr(exprs)) => match &exprs[..] {
+                    [expr] => match &**expr {
+                        Expr::Sym(sym) => match environment::lookup(&self.env, sym)? {
+                            Expr::Fun(fun) => println!("{}", docs::render(sym, &fun)),
+                            value => println!("{}", describe(sym, &value)),
+                        },
+                        x => return Err(Error::NotASymbol(x.clone())),
+                    },
+                    _ => return Err(Error::NotASymbol(Expr::Str(name))),
+                },
+                _ => return Err(Error::ParseError(name)),
+            },
+        }
+        Ok(())
+    }
+}
+
+fn last_value(result: Expr) -> Expr {
+    match result {
+        Expr::QExpr(mut values) => match values.pop() {
+            Some(value) => *value,
+            None => Expr::QExpr(vec![]),
+        },
+        x => x,
+    }
+}
+
+fn describe(sym: &Symbol, expr: &Expr) -> String {
+    match expr {
+        Expr::Fun(fun) => format!("{} {}", expr.type_name(), docs::signature(sym, fun)),
+        x => format!("{} {}", x.type_name(), x),
+    }
+}
+
+fn save_history(rl: &mut Editor<()>, path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+
+    rl.save_history(path).map_err(|e| e.to_string())
+}
+
+pub fn run(options: &Options) -> i32 {
+    let config = Config::builder()
+        .max_history_size(history_size(options))
+        .build();
+    let mut rl = Editor::<()>::with_config(config);
+    let history = history_path(options);
+    if let Some(path) = history.as_ref().filter(|path| path.exists()) {
+        if let Err(e) = rl.load_history(path) {
+            eprintln!("इतिहास पढ्न सकिएन ({}) : {}", path.display(), e);
+        }
+    }
+
+    let init = match options.init {
+        true => init_path(),
+        false => None,
+    };
+    let mut session = match Session::new(options.prelude, init) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("Error : {}", e);
+            return e.exit_code();
+        }
+    };
+    let mut code = 0;
+    loop {
+        let readline = rl.readline(">> ");
+        match readline {
+            Ok(line) => {
+                rl.add_history_entry(line.as_str());
+
+                let result = match parse_command(&line) {
+                    Some(Ok(command)) => session.run_command(command),
+                    Some(Err(msg)) => {
+                        eprintln!("{}", msg);
+                        Ok(())
+                    }
+                    None => session.eval(&line).map(|expr| println!("{}", &expr)),
+                };
+                match result {
+                    Err(Error::Exit(exit_code)) => {
+                        code = exit_code;
+                        break;
+                    }
+                    Err(e) => eprintln!("Error : {}", e),
+                    Ok(()) => {}
+                }
+            }
+            Err(ReadlineError::Interrupted) => {
+                eprintln!("CTRL-C");
+                break;
+            }
+            Err(ReadlineError::Eof) => {
+                eprintln!("CTRL-D");
+                break;
+            }
+            Err(err) => {
+                eprintln!("Error: {:?}", err);
+                break;
+            }
+        }
+    }
+    if let Some(path) = &history {
+        if let Err(e) = save_history(&mut rl, path) {
+            eprintln!("इतिहास सेभ गर्न सकिएन ({}) : {}", path.display(), e);
+        }
+    }
+
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse_command("(+ १ २)"), None);
+        assert_eq!(parse_command(":help"), Some(Ok(Command::Help)));
+        assert_eq!(
+            parse_command(":load  उदाहरण.अ "),
+            Some(Ok(Command::Load("उदाहरण.अ".to_string())))
+        );
+        assert_eq!(
+            parse_command(":time (फिब १०)"),
+            Some(Ok(Command::Time("(फिब १०)".to_string())))
+        );
+        assert_eq!(
+            parse_command(":profile (फिब १०)"),
+            Some(Ok(Command::Profile("(फिब १०)".to_string())))
+        );
+        assert_eq!(
+            parse_command(":break घटाउ"),
+            Some(Ok(Command::Break("घटाउ".to_string())))
+        );
+        assert_eq!(parse_command(":delete २"), Some(Ok(Command::Delete(2))));
+        assert!(matches!(parse_command(":delete ०"), Some(Err(_))));
+        assert!(matches!(parse_command(":load"), Some(Err(_))));
+        assert!(matches!(parse_command(":reset now"), Some(Err(_))));
+        assert!(matches!(parse_command(":foo"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_user_bindings() {
+        let mut session = Session::new(true, None).unwrap();
+        assert!(session.user_bindings().is_empty());
+
+        core::parse_and_eval_str(&mut session.env, "(फलन '(दोब्बर अ) '(* २ अ))").unwrap();
+        let names: Vec<String> = session
+            .user_bindings()
+            .iter()
+            .map(|(sym, _)| Expr::Sym(sym.clone()).to_string())
+            .collect();
+        assert_eq!(names, vec!["दोब्बर"]);
+
+        session.run_command(Command::Reset).unwrap();
+        assert!(session.user_bindings().is_empty());
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let mut session = Session::new(false, None).unwrap();
+        assert_eq!(
+            session.load("हराएको.अ"),
+            Err(Error::ImportError(Expr::Str("हराएको.अ".to_string())))
+        );
+        assert_eq!(session.last_loaded, None);
+    }
+
+    #[test]
+    fn test_init_file() {
+        let path = env::temp_dir().join(format!("alpa-init-{}.अ", std::process::id()));
+        fs::write(&path, "(फलन '(तेब्बर अ) '(* ३ अ))").unwrap();
+
+        let mut session = Session::new(true, Some(path.clone())).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(session.user_bindings().is_empty());
+        assert_eq!(
+            core::parse_and_eval_str(&mut session.env, "(तेब्बर ३)"),
+            Ok(Expr::QExpr(vec![Box::new(Expr::Num(Sankhya(9)))]))
+        );
+    }
+
+    #[test]
+    fn test_load_with_breakpoint() {
+        let path = env::temp_dir().join(format!("alpa-load-{}.अ", std::process::id()));
+        fs::write(&path, "(+ १ १)\n(* २ ३)\n").unwrap();
+
+        let mut session = Session::new(false, None).unwrap();
+        session.debugger = Rc::new(RefCell::new(Debugger::new(
+            Box::new(std::io::empty()),
+            Box::new(std::io::sink()),
+        )));
+        let spec = format!("{}:२", path.display());
+        session.run_command(Command::Break(spec)).unwrap();
+        let result = session.load(&path.display().to_string());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            result,
+            Ok(Expr::QExpr(vec![
+                Box::new(Expr::Num(Sankhya(2))),
+                Box::new(Expr::Num(Sankhya(6)))
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_history_path() {
+        let options = Options {
+            history: Some(PathBuf::from("/tmp/alpa-history")),
+            ..Options::default()
+        };
+        assert_eq!(
+            history_path(&options),
+            Some(PathBuf::from("/tmp/alpa-history"))
+        );
+
+        let options = Options {
+            history_size: Some(10),
+            ..Options::default()
+        };
+        assert_eq!(history_size(&options), 10);
+    }
+}
@@ -0,0 +1,352 @@
+use std::cell::RefCell;
+use std::mem;
+use std::time::{Duration, Instant};
+
+use crate::core;
+use crate::environment::Env;
+use crate::types::{Error, Expr, ExprsOp, Function, QExprOp, SExprOp, Symbol, TestOp};
+
+/// How many steps, or ticks, go by between looks at the clock.
+const CLOCK_EVERY: usize = 256;
+
+/// What a script is allowed to do besides computing.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Capabilities(u8);
+
+impl Capabilities {
+    pub const NONE: Capabilities = Capabilities(0);
+    /// `आयात`.
+    pub const IMPORT: Capabilities = Capabilities(1);
+    /// Reading and writing files, which `आयात` does too.
+    pub const FILES: Capabilities = Capabilities(1 << 1);
+    /// `छाप`, `मद्दत`, `वातावरण`, `ट्रेस` and `परीक्षण`.
+    pub const PRINT: Capabilities = Capabilities(1 << 2);
+    pub const ALL: Capabilities = Capabilities(0b111);
+
+    pub fn contains(self, other: Capabilities) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn without(self, other: Capabilities) -> Capabilities {
+        Capabilities(self.0 & !other.0)
+    }
+
+    /// `import`, `files` or `print`.
+    pub fn from_name(name: &str) -> Option<Capabilities> {
+        match name {
+            "import" => Some(Capabilities::IMPORT),
+            "files" => Some(Capabilities::FILES),
+            "print" => Some(Capabilities::PRINT),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities::ALL
+    }
+}
+
+/// What the core function `sym` needs to run.
+fn required(sym: &Symbol) -> Capabilities {
+    match sym {
+        Symbol::ExprsOp(ExprsOp::Import) => {
+            Capabilities(Capabilities::IMPORT.0 | Capabilities::FILES.0)
+        }
+        Symbol::ExprsOp(ExprsOp::Print)
+        | Symbol::ExprsOp(ExprsOp::Help)
+        | Symbol::SExprOp(SExprOp::PrintEnv)
+        | Symbol::QExprOp(QExprOp::Trace)
+        | Symbol::TestOp(TestOp::Test) => Capabilities::PRINT,
+        _ => Capabilities::NONE,
+    }
+}
+
+/// Limits on an evaluation. Nothing is limited by default.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Limits {
+    /// Expressions to evaluate before giving up.
+    pub max_steps: Option<usize>,
+    /// Function applications that may be in progress at once.
+    pub max_depth: Option<usize>,
+    /// Bytes the core functions may allocate for their results, in total.
+    pub max_memory: Option<usize>,
+    pub timeout: Option<Duration>,
+    pub capabilities: Capabilities,
+}
+
+impl Limits {
+    pub fn is_unlimited(&self) -> bool {
+        *self == Limits::default()
+    }
+}
+
+struct State {
+    limits: Limits,
+    steps: usize,
+    ticks: usize,
+    depth: usize,
+    allocated: usize,
+    deadline: Option<Instant>,
+}
+
+thread_local! {
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+fn with_state<T>(f: impl FnOnce(&mut State) -> Result<T, Error>, default: T) -> Result<T, Error> {
+    STATE.with(|cell| match &mut *cell.borrow_mut() {
+        Some(state) => f(state),
+        None => Ok(default),
+    })
+}
+
+/// Counts an evaluation step, failing once the steps or the time run out.
+pub fn step() -> Result<(), Error> {
+    with_state(
+        |state| {
+            state.steps += 1;
+            if let Some(max) = state.limits.max_steps {
+                if state.steps > max {
+                    return Err(Error::StepLimitExceeded(max));
+                }
+            }
+            check_clock(state, state.steps)
+        },
+        (),
+    )
+}
+
+/// Counts one turn of a loop inside a core function, such as building the
+/// list of `दायरा` or sorting, so that the timeout also stops long calls
+/// that evaluate nothing along the way.
+pub fn tick() -> Result<(), Error> {
+    with_state(
+        |state| {
+            state.ticks += 1;
+            check_clock(state, state.ticks)
+        },
+        (),
+    )
+}
+
+fn check_clock(state: &State, count: usize) -> Result<(), Error> {
+    if let (Some(deadline), 0) = (state.deadline, count % CLOCK_EVERY) {
+        if Instant::now() >= deadline {
+            return Err(Error::Timeout(state.limits.timeout.unwrap_or_default()));
+        }
+    }
+    Ok(())
+}
+
+/// Starts applying `fun`, failing if it goes too deep or needs a capability
+/// that was taken away. Every successful `enter` is matched by a `leave`.
+pub fn enter(fun: &Function) -> Result<(), Error> {
+    with_state(
+        |state| {
+            if let Function::Core(sym, _, _) = fun {
+                if !state.limits.capabilities.contains(required(sym)) {
+                    return Err(Error::NotPermitted(sym.clone()));
+                }
+            }
+            if let Some(max) = state.limits.max_depth {
+                if state.depth >= max {
+                    return Err(Error::DepthLimitExceeded(max));
+                }
+            }
+            state.depth += 1;
+            Ok(())
+        },
+        (),
+    )
+}
+
+pub fn leave() {
+    let _ = with_state(
+        |state| {
+            state.depth -= 1;
+            Ok(())
+        },
+        (),
+    );
+}
+
+/// Roughly how many bytes `expr` takes up.
+fn size_of(expr: &Expr) -> usize {
+    mem::size_of::<Expr>()
+        + match expr {
+            Expr::Str(s) => s.len(),
+            Expr::SExpr(items) | Expr::QExpr(items) => items.iter().map(|item| size_of(item)).sum(),
+            Expr::Vector(items) => items.iter().map(size_of).sum(),
+            Expr::Fun(Function::Lambda(formals, body, bindings, _)) => {
+                formals.len() * mem::size_of::<Symbol>()
+                    + size_of(body)
+                    + bindings
+                        .values()
+                        .map(|value| mem::size_of::<Symbol>() + size_of(value))
+                        .sum::<usize>()
+            }
+            Expr::Ref(reference) => reference.visit(|value| value.map_or(0, size_of)),
+            _ => 0,
+        }
+}
+
+/// Charges a value built by a core function against the memory budget.
+pub fn allocate(value: &Expr) -> Result<(), Error> {
+    with_state(
+        |state| match state.limits.max_memory {
+            Some(max) => {
+                state.allocated += size_of(value);
+                match state.allocated > max {
+                    true => Err(Error::MemoryLimitExceeded(max)),
+                    false => Ok(()),
+                }
+            }
+            None => Ok(()),
+        },
+        (),
+    )
+}
+
+/// Applies `limits` to the rest of the program. The clock starts now.
+pub fn install(limits: Limits) {
+    let state = match limits.is_unlimited() {
+        true => None,
+        false => Some(State {
+            deadline: limits.timeout.map(|timeout| Instant::now() + timeout),
+            limits,
+            steps: 0,
+            ticks: 0,
+            depth: 0,
+            allocated: 0,
+        }),
+    };
+    STATE.with(|cell| *cell.borrow_mut() = state);
+}
+
+/// Evaluates `source` within `limits`, then puts back whatever limits were
+/// in place before.
+pub fn eval_str(limits: &Limits, env: &mut Env, source: &str) -> Result<Expr, Error> {
+    let previous = STATE.with(|cell| cell.borrow_mut().take());
+    install(limits.clone());
+    let result = core::parse_and_eval_str(env, source);
+    STATE.with(|cell| *cell.borrow_mut() = previous);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment;
+    use crate::ntypes::Sankhya;
+
+    #[test]
+    fn test_step_and_depth_limits() {
+        let mut env = environment::new_with_prelude().unwrap();
+        core::parse_and_eval_str(&mut env, "(फलन '(लुप क) '(लुप क))").unwrap();
+
+        let limits = Limits {
+            max_depth: Some(100),
+            ..Limits::default()
+        };
+        assert_eq!(
+            eval_str(&limits, &mut env, "(लुप १)"),
+            Err(Error::DepthLimitExceeded(100))
+        );
+
+        let limits = Limits {
+            max_steps: Some(50),
+            ..Limits::default()
+        };
+        assert_eq!(
+            eval_str(&limits, &mut env, "(लुप १)"),
+            Err(Error::StepLimitExceeded(50))
+        );
+        assert_eq!(
+            eval_str(&limits, &mut env, "(+ १ २)"),
+            Ok(Expr::QExpr(vec![Box::new(Expr::Num(Sankhya(3)))]))
+        );
+    }
+
+    #[test]
+    fn test_assert_throws_keeps_limits() {
+        let mut env = environment::new_with_prelude().unwrap();
+        core::parse_and_eval_str(&mut env, "(फलन '(अनन्त क) '(अनन्त क))").unwrap();
+
+        let limits = Limits {
+            max_depth: Some(50),
+            ..Limits::default()
+        };
+        assert_eq!(
+            eval_str(&limits, &mut env, "(दाबीसमस्या '(अनन्त १))"),
+            Err(Error::DepthLimitExceeded(50))
+        );
+
+        let limits = Limits {
+            capabilities: Capabilities::NONE,
+            ..Limits::default()
+        };
+        assert_eq!(
+            eval_str(&limits, &mut env, "(दाबीसमस्या '(छाप १))"),
+            Err(Error::NotPermitted(Symbol::ExprsOp(ExprsOp::Print)))
+        );
+    }
+
+    #[test]
+    fn test_memory_limit_and_timeout() {
+        let mut env = environment::new_with_prelude().unwrap();
+        core::parse_and_eval_str(&mut env, "(फलन '(बढाउ क) '(बढाउ (एकत्र क क)))").unwrap();
+
+        let limits = Limits {
+            max_memory: Some(1 << 20),
+            ..Limits::default()
+        };
+        assert_eq!(
+            eval_str(&limits, &mut env, "(बढाउ '(१))"),
+            Err(Error::MemoryLimitExceeded(1 << 20))
+        );
+        assert!(eval_str(&limits, &mut env, "(नामक '(स) (सन्दर्भ नील)) (राख स (सुची स))").is_ok());
+
+        core::parse_and_eval_str(&mut env, "(फलन '(गन न) '(यदि (== न ०) '(न) '(गन (- न १))))")
+            .unwrap();
+        let limits = Limits {
+            timeout: Some(Duration::ZERO),
+            ..Limits::default()
+        };
+        assert_eq!(
+            eval_str(&limits, &mut env, "(गन १००)"),
+            Err(Error::Timeout(Duration::ZERO))
+        );
+        assert_eq!(
+            eval_str(&limits, &mut env, "(लम्बाई (दायरा ० १००००००))"),
+            Err(Error::Timeout(Duration::ZERO))
+        );
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let mut env = environment::new_with_prelude().unwrap();
+        let limits = Limits {
+            capabilities: Capabilities::ALL.without(Capabilities::PRINT),
+            ..Limits::default()
+        };
+        assert_eq!(
+            eval_str(&limits, &mut env, "(छाप \"नमस्ते\")"),
+            Err(Error::NotPermitted(Symbol::ExprsOp(ExprsOp::Print)))
+        );
+
+        let limits = Limits {
+            capabilities: Capabilities::ALL.without(Capabilities::FILES),
+            ..Limits::default()
+        };
+        assert_eq!(
+            eval_str(&limits, &mut env, "(आयात उदाहरण)"),
+            Err(Error::NotPermitted(Symbol::ExprsOp(ExprsOp::Import)))
+        );
+        assert!(eval_str(&limits, &mut env, "(लहरै (\\ '(क) '(* २ क)) '(१ २))").is_ok());
+        assert_eq!(Capabilities::from_name("print"), Some(Capabilities::PRINT));
+        assert_eq!(Capabilities::from_name("network"), None);
+    }
+}
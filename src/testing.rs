@@ -0,0 +1,302 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::core;
+use crate::environment;
+use crate::evaluator;
+use crate::ntypes::Sankhya;
+use crate::parser;
+use crate::types::{Error, Expr, Symbol, TestOp};
+
+const TEST_SUFFIX: &str = "परीक्षण";
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TestCase {
+    pub name: String,
+    pub line: usize,
+    pub bodies: Vec<Box<Expr>>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TestResult {
+    pub name: String,
+    pub line: usize,
+    pub outcome: Result<(), Error>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Summary {
+    pub passed: usize,
+    pub failed: usize,
+}
+
+pub fn collect(source: &str) -> Result<(Vec<Box<Expr>>, Vec<TestCase>), Error> {
+    let (exprs, spans) =
+        parser::parse_spanned(source).map_err(|pe| Error::ParseError(pe.to_string()))?;
+
+    let mut setup = vec![];
+    let mut tests = vec![];
+    for (expr, span) in exprs.into_iter().zip(spans.iter()) {
+        match &*expr {
+            Expr::SExpr(exprs) => match &exprs[..] {
+                [oper, name, bodies @ ..] if **oper == Expr::Sym(Symbol::TestOp(TestOp::Test)) => {
+                    match &**name {
+                        Expr::Str(name) => tests.push(TestCase {
+                            name: name.clone(),
+                            line: span.line,
+                            bodies: bodies.to_vec(),
+                        }),
+                        _ => setup.push(expr.clone()),
+                    }
+                }
+                _ => setup.push(expr.clone()),
+            },
+            _ => setup.push(expr.clone()),
+        }
+    }
+
+    Ok((setup, tests))
+}
+
+pub fn run_source(source: &str) -> Result<Vec<TestResult>, Error> {
+    let (setup, tests) = collect(source)?;
+
+    let mut base = environment::new_with_prelude()?;
+    for expr in setup.iter() {
+        evaluator::eval(&mut base, expr)?;
+    }
+
+    let results = tests
+        .into_iter()
+        .map(|test| {
+            let env = environment::new(environment::bindings(&base), None);
+            TestResult {
+                name: test.name,
+                line: test.line,
+                outcome: core::run_test_bodies(&env, &test.bodies).map(|_| ()),
+            }
+        })
+        .collect();
+
+    Ok(results)
+}
+
+pub fn discover(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    find_files(paths, is_test_file)
+}
+
+/// Files named directly in `paths` are kept as is, directories are walked
+/// for files matching `is_match`, skipping hidden directories and `target`.
+pub fn find_files(paths: &[PathBuf], is_match: fn(&Path) -> bool) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for path in paths {
+        if path.is_dir() {
+            discover_dir(path, is_match, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+fn discover_dir(
+    dir: &Path,
+    is_match: fn(&Path) -> bool,
+    files: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if name.starts_with('.') || name == "target" {
+            continue;
+        }
+
+        if path.is_dir() {
+            discover_dir(&path, is_match, files)?;
+        } else if is_match(&path) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn is_alpa_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "अ")
+}
+
+fn is_test_file(path: &Path) -> bool {
+    let is_alpa = is_alpa_file(path);
+    let is_test = path
+        .file_stem()
+        .is_some_and(|stem| stem.to_string_lossy().ends_with(TEST_SUFFIX));
+
+    is_alpa && is_test
+}
+
+pub fn describe_failure(error: &Error) -> Vec<String> {
+    match error {
+        Error::AssertEqualFailed(expected, actual) => {
+            let mut lines = vec![
+                format!("- अपेक्षित : {}", expected),
+                format!("+ वास्तविक : {}", actual),
+            ];
+            if let (Expr::QExpr(expected), Expr::QExpr(actual)) = (&**expected, &**actual) {
+                lines.extend(diff_items(expected, actual));
+            }
+            lines
+        }
+        e => e.to_string().lines().map(String::from).collect(),
+    }
+}
+
+fn diff_items(expected: &[Box<Expr>], actual: &[Box<Expr>]) -> Vec<String> {
+    let missing = String::from("(छैन)");
+    let mut lines = vec![];
+    for idx in 0..expected.len().max(actual.len()) {
+        let e = expected.get(idx).map(|e| e.to_string());
+        let a = actual.get(idx).map(|a| a.to_string());
+        if e != a {
+            let index = format!("[{}]", Sankhya(idx as i32));
+            lines.push(format!("  {} - {}", index, e.unwrap_or_else(|| missing.clone())));
+            lines.push(format!(
+                "  {} + {}",
+                " ".repeat(index.chars().count()),
+                a.unwrap_or_else(|| missing.clone())
+            ));
+        }
+    }
+
+    lines
+}
+
+pub fn run(paths: &[PathBuf]) -> io::Result<Summary> {
+    let mut summary = Summary::default();
+
+    for file in discover(paths)? {
+        println!("{}", file.display());
+
+        let results = fs::read_to_string(&file)
+            .map_err(|_| Error::ImportError(Expr::Str(file.display().to_string())))
+            .and_then(|source| core::within_file(&file, || run_source(&source)));
+
+        match results {
+            Ok(results) => {
+                for result in results {
+                    match &result.outcome {
+                        Ok(()) => {
+                            summary.passed += 1;
+                            println!("  ✓ {}", result.name);
+                        }
+                        Err(e) => {
+                            summary.failed += 1;
+                            println!("  ✗ {} (लाइन {})", result.name, Sankhya(result.line as i32));
+                            for line in describe_failure(e) {
+                                println!("      {}", line);
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                summary.failed += 1;
+                println!("  ✗ फाइल चलाउन सकिएन");
+                for line in describe_failure(&e) {
+                    println!("      {}", line);
+                }
+            }
+        }
+    }
+
+    println!(
+        "नतिजा : {} सफल, {} असफल",
+        Sankhya(summary.passed as i32),
+        Sankhya(summary.failed as i32)
+    );
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntypes::Sankhya;
+
+    const SOURCE: &str = r#"
+(फलन '(दोब्बर अ) '(* २ अ))
+
+(परीक्षण "दोब्बर"
+  '(दाबीबराबर ४ (दोब्बर २))
+  '(दाबी (== ६ (दोब्बर ३))))
+
+(परीक्षण "गलत दोब्बर"
+  '(दाबीबराबर '(१ २ ३) (लहरै दोब्बर '(१ २ ३))))
+
+(परीक्षण "भाग"
+  '(दाबीसमस्या '(/ १ ०))
+  '(दाबीसमस्या '(समस्या "हो") "हो"))
+
+(परीक्षण "वातावरण अलग"
+  '(नामक '(क) १)
+  '(दाबीबराबर १ क))
+
+(परीक्षण "वातावरण अलग फेरि"
+  '(दाबीसमस्या '(क)))
+"#;
+
+    #[test]
+    fn test_run_source() {
+        let results = run_source(SOURCE).unwrap();
+        let outcomes: Vec<(&str, bool)> = results
+            .iter()
+            .map(|r| (r.name.as_str(), r.outcome.is_ok()))
+            .collect();
+
+        assert_eq!(
+            outcomes,
+            vec![
+                ("दोब्बर", true),
+                ("गलत दोब्बर", false),
+                ("भाग", true),
+                ("वातावरण अलग", true),
+                ("वातावरण अलग फेरि", true),
+            ]
+        );
+        assert_eq!(results[1].line, 8);
+    }
+
+    #[test]
+    fn test_describe_failure() {
+        let num = |n| Box::new(Expr::Num(Sankhya(n)));
+        let error = Error::AssertEqualFailed(
+            Box::new(Expr::QExpr(vec![num(1), num(2), num(3)])),
+            Box::new(Expr::QExpr(vec![num(1), num(4)])),
+        );
+
+        assert_eq!(
+            describe_failure(&error),
+            vec![
+                "- अपेक्षित : '(१ २ ३)",
+                "+ वास्तविक : '(१ ४)",
+                "  [१] - २",
+                "      + ४",
+                "  [२] - ३",
+                "      + (छैन)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_test_file() {
+        assert!(is_test_file(Path::new("lib/सुची_परीक्षण.अ")));
+        assert!(!is_test_file(Path::new("lib/सुची.अ")));
+        assert!(!is_test_file(Path::new("lib/परीक्षण.txt")));
+    }
+}
@@ -0,0 +1,201 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::environment::{self, Env};
+use crate::evaluator::{self, Frame, Hook, SharedHook};
+use crate::types::{Error, Expr, Symbol};
+
+pub const DEFAULT_LIMIT: usize = 1000;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Options {
+    /// Only applications of these functions are logged, all when empty.
+    pub functions: Vec<String>,
+    /// Lines to write before the trace is cut short.
+    pub limit: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            functions: vec![],
+            limit: DEFAULT_LIMIT,
+        }
+    }
+}
+
+/// Logs every function application on entry with its arguments as written,
+/// and on exit with their values and the result, indented by call depth.
+pub struct Tracer {
+    options: Options,
+    /// Whether each application entered since the tracer was installed is
+    /// being logged, innermost last.
+    shown: Vec<bool>,
+    lines: usize,
+    output: Box<dyn Write>,
+}
+
+impl Tracer {
+    pub fn new(options: Options, output: Box<dyn Write>) -> Tracer {
+        Tracer {
+            options,
+            shown: vec![],
+            lines: 0,
+            output,
+        }
+    }
+
+    fn log(&mut self, line: String) {
+        let depth = self.shown.iter().filter(|shown| **shown).count() - 1;
+        let text = match self.lines.cmp(&self.options.limit) {
+            std::cmp::Ordering::Less => format!("{}{}", "  ".repeat(depth), line),
+            std::cmp::Ordering::Equal => String::from("... ट्रेसको सीमा पुग्यो"),
+            std::cmp::Ordering::Greater => return,
+        };
+        self.lines += 1;
+        let _ = writeln!(self.output, "{}", text);
+    }
+}
+
+/// Whether `frame` applies the function called `name`, directly or through
+/// another name such as a parameter.
+fn applies(frame: &Frame, name: &str) -> bool {
+    let sym = Symbol::Identifier(name.to_string());
+    frame.form[0].to_string() == name
+        || environment::lookup(&frame.env, &sym).ok() == Some(Expr::Fun(frame.fun.clone()))
+}
+
+/// The application with the values its arguments evaluated to. Arguments
+/// that were not evaluated, or that named a function, are shown as written.
+fn with_values(frame: &Frame) -> Expr {
+    let mut items = vec![frame.form[0].clone()];
+    for (arg, value) in frame.form[1..].iter().zip(frame.values.iter()) {
+        match (&**arg, value) {
+            (Expr::Sym(_), Some(Expr::Fun(_))) | (_, None) => items.push(arg.clone()),
+            (_, Some(value)) => items.push(Box::new(value.clone())),
+        }
+    }
+
+    Expr::SExpr(items)
+}
+
+impl Hook for Tracer {
+    fn enter(&mut self, frames: &[Frame]) -> Result<(), Error> {
+        let frame = &frames[frames.len() - 1];
+        let functions = &self.options.functions;
+        let shown = functions.is_empty() || functions.iter().any(|name| applies(frame, name));
+        self.shown.push(shown);
+        if shown {
+            self.log(format!("→ {}", Expr::SExpr(frame.form.clone())));
+        }
+
+        Ok(())
+    }
+
+    fn exit(&mut self, frames: &[Frame], result: &Result<Expr, Error>) {
+        if self.shown.last() == Some(&true) {
+            let frame = &frames[frames.len() - 1];
+            let line = match result {
+                Ok(value) => format!("← {} = {}", with_values(frame), value),
+                Err(e) => format!("← {} : समस्या : {}", with_values(frame), e),
+            };
+            self.log(line);
+        }
+        self.shown.pop();
+    }
+}
+
+/// Traces the rest of the program to stderr.
+pub fn install(options: Options) {
+    let tracer = Tracer::new(options, Box::new(io::stderr()));
+    evaluator::set_hook(Some(Rc::new(RefCell::new(tracer)) as SharedHook));
+}
+
+/// Evaluates `expr` while `tracer` logs it, then puts back whatever hook was
+/// installed before.
+pub fn eval(tracer: Tracer, env: &mut Env, expr: &Expr) -> Result<Expr, Error> {
+    let previous = evaluator::set_hook(Some(Rc::new(RefCell::new(tracer)) as SharedHook));
+    let result = evaluator::eval(env, expr);
+    evaluator::set_hook(previous);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core;
+    use crate::environment;
+
+    struct Output(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn trace(env: &mut Env, source: &str, options: Options) -> (Result<Expr, Error>, String) {
+        let output = Rc::new(RefCell::new(vec![]));
+        let tracer = Tracer::new(options, Box::new(Output(Rc::clone(&output))));
+        let expr = match crate::parser::parse(source).unwrap() {
+            Expr::SExpr(mut exprs) => *exprs.remove(0),
+            _ => unreachable!(),
+        };
+        let result = eval(tracer, env, &expr);
+        let text = String::from_utf8(output.borrow().clone()).unwrap();
+
+        (result, text)
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut env = environment::new_with_core_fns();
+        core::parse_and_eval_str(&mut env, "(= '(दोब्बर) (\\ '(क) '(* २ क)))").unwrap();
+
+        let (result, text) = trace(&mut env, "(+ १ (दोब्बर (- ५ २)))", Options::default());
+        assert_eq!(result, Ok(Expr::Num(crate::ntypes::Sankhya(7))));
+        assert_eq!(
+            text,
+            "\
+→ (+ १ (दोब्बर (- ५ २)))
+  → (दोब्बर (- ५ २))
+    → (- ५ २)
+    ← (- ५ २) = ३
+    → (* २ क)
+    ← (* २ ३) = ६
+  ← (दोब्बर ३) = ६
+← (+ १ ६) = ७
+"
+        );
+    }
+
+    #[test]
+    fn test_trace_filter_and_limit() {
+        let mut env = environment::new_with_prelude().unwrap();
+        let options = Options {
+            functions: vec![String::from("दोब्बर"), String::from("/")],
+            limit: 3,
+        };
+        core::parse_and_eval_str(&mut env, "(फलन '(दोब्बर क) '(* २ क))").unwrap();
+
+        let (result, text) = trace(&mut env, "(लहरै दोब्बर '(१ २ ३))", options.clone());
+        assert!(result.is_ok());
+        assert_eq!(
+            text,
+            "→ (दोब्बर १)\n← (दोब्बर १) = २\n→ (दोब्बर २)\n... ट्रेसको सीमा पुग्यो\n"
+        );
+
+        let (result, text) = trace(&mut env, "(/ १ ०)", options);
+        assert!(result.is_err());
+        assert_eq!(
+            text,
+            "→ (/ १ ०)\n← (/ १ ०) : समस्या : शुन्यले भाग गर्न मिल्दैन : (/ १ ०)\n"
+        );
+    }
+}
@@ -1,7 +1,12 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
+use std::rc::Rc;
+use std::time::Duration;
 
+use crate::calendar::Samaya;
 use crate::core::CoreFn;
+use crate::docs::Doc;
 use crate::ntypes::Sankhya;
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
@@ -9,10 +14,13 @@ pub enum ExprsOp {
     Equal,
     NotEqual,
     If,
+    Match,
     List,
     Import,
     Print,
     Error,
+    Help,
+    Exit,
 }
 
 impl fmt::Display for ExprsOp {
@@ -21,10 +29,169 @@ impl fmt::Display for ExprsOp {
             ExprsOp::Equal => write!(f, "=="),
             ExprsOp::NotEqual => write!(f, "!="),
             ExprsOp::If => write!(f, "यदि"),
+            ExprsOp::Match => write!(f, "मिलाउ"),
             ExprsOp::List => write!(f, "सुची"),
             ExprsOp::Import => write!(f, "आयात"),
             ExprsOp::Print => write!(f, "छाप"),
             ExprsOp::Error => write!(f, "समस्या"),
+            ExprsOp::Help => write!(f, "मद्दत"),
+            ExprsOp::Exit => write!(f, "निकास"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum BoolOp {
+    Not,
+    Or,
+    And,
+}
+
+impl fmt::Display for BoolOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoolOp::Not => write!(f, "हैन"),
+            BoolOp::Or => write!(f, "अथवा"),
+            BoolOp::And => write!(f, "साथै"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum LoopOp {
+    While,
+    ForEach,
+    Count,
+    Range,
+    Break,
+    Continue,
+}
+
+impl fmt::Display for LoopOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoopOp::While => write!(f, "जबसम्म"),
+            LoopOp::ForEach => write!(f, "हरेक"),
+            LoopOp::Count => write!(f, "गन्ती"),
+            LoopOp::Range => write!(f, "दायरा"),
+            LoopOp::Break => write!(f, "रोक"),
+            LoopOp::Continue => write!(f, "जारी"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum TimeOp {
+    Now,
+    FromBs,
+    ToBs,
+    FromGregorian,
+    ToGregorian,
+    Add,
+    Diff,
+    Format,
+}
+
+impl fmt::Display for TimeOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeOp::Now => write!(f, "अहिले"),
+            TimeOp::FromBs => write!(f, "मिति"),
+            TimeOp::ToBs => write!(f, "मितिभाग"),
+            TimeOp::FromGregorian => write!(f, "इस्वी"),
+            TimeOp::ToGregorian => write!(f, "इस्वीभाग"),
+            TimeOp::Add => write!(f, "समयथप"),
+            TimeOp::Diff => write!(f, "समयअन्तर"),
+            TimeOp::Format => write!(f, "समयलेख"),
+        }
+    }
+}
+
+/// List functions that are bound to ordinary names rather than keywords, so
+/// they can be redefined like the rest of the prelude.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum ListOp {
+    Map,
+    Filter,
+    Fold,
+    Length,
+    Nth,
+    Take,
+    Drop,
+    Elem,
+    Last,
+    Sort,
+    Reverse,
+    Zip,
+    Enumerate,
+    Any,
+    All,
+    FlatMap,
+}
+
+impl fmt::Display for ListOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ListOp::Map => write!(f, "लहरै"),
+            ListOp::Filter => write!(f, "छान"),
+            ListOp::Fold => write!(f, "पट्याउ"),
+            ListOp::Length => write!(f, "लम्बाई"),
+            ListOp::Nth => write!(f, "औँ"),
+            ListOp::Take => write!(f, "लेउ"),
+            ListOp::Drop => write!(f, "छोड"),
+            ListOp::Elem => write!(f, "मा"),
+            ListOp::Last => write!(f, "अन्तिम"),
+            ListOp::Sort => write!(f, "क्रमबद्ध"),
+            ListOp::Reverse => write!(f, "उल्टाउ"),
+            ListOp::Zip => write!(f, "जोडी"),
+            ListOp::Enumerate => write!(f, "क्रमाङ्क"),
+            ListOp::Any => write!(f, "कुनै"),
+            ListOp::All => write!(f, "सबै"),
+            ListOp::FlatMap => write!(f, "लहरैएकत्र"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum VectorOp {
+    New,
+    Get,
+    Set,
+    Push,
+    Slice,
+    FromQExpr,
+    ToQExpr,
+}
+
+impl fmt::Display for VectorOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VectorOp::New => write!(f, "भेक्टर"),
+            VectorOp::Get => write!(f, "तान"),
+            VectorOp::Set => write!(f, "बदल"),
+            VectorOp::Push => write!(f, "थप"),
+            VectorOp::Slice => write!(f, "टुक्रा"),
+            VectorOp::FromQExpr => write!(f, "भेक्टरमा"),
+            VectorOp::ToQExpr => write!(f, "सुचीमा"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum RefOp {
+    New,
+    Get,
+    Set,
+    Swap,
+}
+
+impl fmt::Display for RefOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RefOp::New => write!(f, "सन्दर्भ"),
+            RefOp::Get => write!(f, "हेर"),
+            RefOp::Set => write!(f, "राख"),
+            RefOp::Swap => write!(f, "फेर"),
         }
     }
 }
@@ -62,6 +229,7 @@ pub enum QExprOp {
     Tail,
     Len,
     Eval,
+    Trace,
 }
 
 impl fmt::Display for QExprOp {
@@ -71,6 +239,7 @@ impl fmt::Display for QExprOp {
             QExprOp::Tail => write!(f, "पुच्छर"),
             QExprOp::Len => write!(f, "वटा"),
             QExprOp::Eval => write!(f, "बिस्तार"),
+            QExprOp::Trace => write!(f, "ट्रेस"),
         }
     }
 }
@@ -82,6 +251,8 @@ pub enum QExprsOp {
     Def,
     Put,
     Lambda,
+    Let,
+    LetRec,
 }
 
 impl fmt::Display for QExprsOp {
@@ -92,6 +263,8 @@ impl fmt::Display for QExprsOp {
             QExprsOp::Def => write!(f, "नामक"),
             QExprsOp::Put => write!(f, "="),
             QExprsOp::Lambda => write!(f, "ल्याम्बडा"),
+            QExprsOp::Let => write!(f, "स्थानीय"),
+            QExprsOp::LetRec => write!(f, "पुनःस्थानीय"),
         }
     }
 }
@@ -109,6 +282,25 @@ impl fmt::Display for SExprOp {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum TestOp {
+    Assert,
+    AssertEqual,
+    AssertThrows,
+    Test,
+}
+
+impl fmt::Display for TestOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TestOp::Assert => write!(f, "दाबी"),
+            TestOp::AssertEqual => write!(f, "दाबीबराबर"),
+            TestOp::AssertThrows => write!(f, "दाबीसमस्या"),
+            TestOp::Test => write!(f, "परीक्षण"),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub struct Boolean(pub bool);
 
@@ -126,10 +318,17 @@ impl fmt::Display for Boolean {
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub enum Symbol {
     ExprsOp(ExprsOp),
+    BoolOp(BoolOp),
+    LoopOp(LoopOp),
+    RefOp(RefOp),
+    VectorOp(VectorOp),
+    TimeOp(TimeOp),
+    ListOp(ListOp),
     NumOp(NumOp),
     QExprOp(QExprOp),
     QExprsOp(QExprsOp),
     SExprOp(SExprOp),
+    TestOp(TestOp),
     Identifier(String),
 }
 
@@ -137,10 +336,17 @@ impl fmt::Display for Symbol {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Symbol::ExprsOp(o) => write!(f, "एक्सपर्स-अप({})", o),
+            Symbol::BoolOp(o) => write!(f, "बुल-अप({})", o),
+            Symbol::LoopOp(o) => write!(f, "लुप-अप({})", o),
+            Symbol::RefOp(o) => write!(f, "सन्दर्भ-अप({})", o),
+            Symbol::VectorOp(o) => write!(f, "भेक्टर-अप({})", o),
+            Symbol::TimeOp(o) => write!(f, "समय-अप({})", o),
+            Symbol::ListOp(o) => write!(f, "सुची-अप({})", o),
             Symbol::NumOp(o) => write!(f, "नम-अप({})", o),
             Symbol::QExprOp(o) => write!(f, "क्यु-एक्सपर्-अप({})", o),
             Symbol::QExprsOp(o) => write!(f, "क्यु-एक्सपर्स-अप({})", o),
             Symbol::SExprOp(o) => write!(f, "एस्-एक्सपर्-अप({})", o),
+            Symbol::TestOp(o) => write!(f, "टेस्ट-अप({})", o),
             Symbol::Identifier(s) => write!(f, "आइडेन्टिफायर({})", s),
         }
     }
@@ -148,19 +354,19 @@ impl fmt::Display for Symbol {
 
 #[derive(Clone)]
 pub enum Function {
-    Core(Symbol, CoreFn),
-    Lambda(Vec<Symbol>, Box<Expr>, HashMap<Symbol, Expr>),
+    Core(Symbol, CoreFn, Doc),
+    Lambda(Vec<Symbol>, Box<Expr>, HashMap<Symbol, Expr>, Option<String>),
 }
 
 impl fmt::Debug for Function {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Function::Core(sym, _) => write!(f, "Core({:?}, pointer)", sym),
-            Function::Lambda(syms, expr, hmap) => {
+            Function::Core(sym, _, _) => write!(f, "Core({:?}, pointer)", sym),
+            Function::Lambda(syms, expr, hmap, doc) => {
                 write!(
                     f,
-                    "Lambda(syms : {:?}, body : {:?}, env : {:?})",
-                    syms, expr, hmap
+                    "Lambda(syms : {:?}, body : {:?}, env : {:?}, doc : {:?})",
+                    syms, expr, hmap, doc
                 )
             }
         }
@@ -171,9 +377,9 @@ impl PartialEq for Function {
     fn eq(&self, other: &Self) -> bool {
         use Function::*;
         match (self, other) {
-            (Core(self_sym, _), Core(other_sym, _)) => self_sym == other_sym,
-            (Lambda(s_syms, s_expr, s_hmap), Lambda(o_syms, o_expr, o_hmap)) => {
-                s_syms == o_syms && s_expr == o_expr && s_hmap == o_hmap
+            (Core(self_sym, _, _), Core(other_sym, _, _)) => self_sym == other_sym,
+            (Lambda(s_syms, s_expr, s_hmap, s_doc), Lambda(o_syms, o_expr, o_hmap, o_doc)) => {
+                s_syms == o_syms && s_expr == o_expr && s_hmap == o_hmap && s_doc == o_doc
             }
             _ => false,
         }
@@ -183,8 +389,8 @@ impl PartialEq for Function {
 impl fmt::Display for Function {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Function::Core(sym, _) => write!(f, "कोर({}, प्वाइन्टर)", sym),
-            Function::Lambda(syms, expr, hmap) => {
+            Function::Core(sym, _, _) => write!(f, "कोर({}, प्वाइन्टर)", sym),
+            Function::Lambda(syms, expr, hmap, _) => {
                 write!(
                     f,
                     "ल्याम्बडा(सिम्बलहरु : {:?}, बडी : {:?}, वातावरण : {:?})",
@@ -195,6 +401,50 @@ impl fmt::Display for Function {
     }
 }
 
+/// A mutable cell shared by every copy of it. Two references are equal
+/// only when they are the same cell.
+#[derive(Clone)]
+pub struct Reference(pub Rc<RefCell<Expr>>);
+
+impl Reference {
+    pub fn new(value: Expr) -> Reference {
+        Reference(Rc::new(RefCell::new(value)))
+    }
+
+    /// Runs `f` on the value, or on `None` when the value is already being
+    /// visited further up, as when a reference holds itself.
+    pub fn visit<T>(&self, f: impl FnOnce(Option<&Expr>) -> T) -> T {
+        match self.0.try_borrow_mut() {
+            Ok(value) => f(Some(&value)),
+            Err(_) => f(None),
+        }
+    }
+}
+
+impl fmt::Debug for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.visit(|value| match value {
+            Some(value) => write!(f, "Reference({:?})", value),
+            None => write!(f, "Reference(…)"),
+        })
+    }
+}
+
+impl PartialEq for Reference {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.visit(|value| match value {
+            Some(value) => write!(f, "सन्दर्भ({})", value),
+            None => write!(f, "सन्दर्भ(…)"),
+        })
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Expr {
     Bool(Boolean),
@@ -203,7 +453,31 @@ pub enum Expr {
     Sym(Symbol),
     SExpr(Vec<Box<Expr>>),
     QExpr(Vec<Box<Expr>>),
+    /// Written `[क ख ...]`, which is quoted like `'(क ख ...)` : its items are
+    /// data and are never evaluated, so a vector evaluates to itself. Copies
+    /// of it share the items.
+    Vector(Rc<Vec<Expr>>),
     Fun(Function),
+    Ref(Reference),
+    Time(Samaya),
+}
+
+impl Expr {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Expr::Bool(_) => "बुलियन",
+            Expr::Num(_) => "संख्या",
+            Expr::Str(_) => "स्ट्रिङ",
+            Expr::Sym(_) => "सिम्बल",
+            Expr::SExpr(_) => "एस्-एक्सपर्",
+            Expr::QExpr(_) => "क्यु-एक्सपर्",
+            Expr::Vector(_) => "भेक्टर",
+            Expr::Fun(Function::Core(..)) => "कोर",
+            Expr::Fun(Function::Lambda(..)) => "ल्याम्बडा",
+            Expr::Ref(_) => "सन्दर्भ",
+            Expr::Time(_) => "समय",
+        }
+    }
 }
 
 impl fmt::Display for Expr {
@@ -214,10 +488,17 @@ impl fmt::Display for Expr {
             Expr::Str(s) => write!(f, "\"{}\"", s),
             Expr::Sym(s) => match s {
                 Symbol::ExprsOp(o) => write!(f, "{}", o),
+                Symbol::BoolOp(o) => write!(f, "{}", o),
+                Symbol::LoopOp(o) => write!(f, "{}", o),
+                Symbol::RefOp(o) => write!(f, "{}", o),
+                Symbol::VectorOp(o) => write!(f, "{}", o),
+                Symbol::TimeOp(o) => write!(f, "{}", o),
+                Symbol::ListOp(o) => write!(f, "{}", o),
                 Symbol::NumOp(o) => write!(f, "{}", o),
                 Symbol::QExprOp(o) => write!(f, "{}", o),
                 Symbol::QExprsOp(o) => write!(f, "{}", o),
                 Symbol::SExprOp(o) => write!(f, "{}", o),
+                Symbol::TestOp(o) => write!(f, "{}", o),
                 Symbol::Identifier(s) => write!(f, "{}", s),
             },
             Expr::SExpr(sexpr) => {
@@ -242,9 +523,22 @@ impl fmt::Display for Expr {
                         .join(" ")
                 )
             }
+            Expr::Vector(items) => {
+                write!(
+                    f,
+                    "[{}]",
+                    items
+                        .iter()
+                        .map(|e| format!("{}", e))
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                )
+            }
             Expr::Fun(fun) => {
                 write!(f, "{}", fun)
             }
+            Expr::Ref(reference) => write!(f, "{}", reference),
+            Expr::Time(samaya) => write!(f, "{}", samaya),
         }
     }
 }
@@ -254,21 +548,190 @@ pub enum Error {
     DivideByZero(Sankhya, Sankhya),
     InvalidNumberOfNumArguments(NumOp, usize),
     InvalidNumberOfExprsArguments(ExprsOp, usize),
+    InvalidNumberOfBoolArguments(BoolOp, usize),
+    InvalidNumberOfLoopArguments(LoopOp, usize),
+    InvalidNumberOfRefArguments(RefOp, usize),
+    InvalidNumberOfVectorArguments(VectorOp, usize),
+    InvalidNumberOfTimeArguments(TimeOp, usize),
+    InvalidNumberOfListArguments(ListOp, usize),
     InvalidNumberOfQExprArguments(QExprOp, usize),
     InvalidNumberOfQExprsArguments(QExprsOp, usize),
     InvalidNumberOfSExprArguments(SExprOp, usize),
+    InvalidNumberOfTestArguments(TestOp, usize),
     EmptyQExpr(Expr),
     ParseError(String),
     ImportError(Expr),
     InvalidOp(Expr),
     NotABoolean(Expr),
+    NotAFunction(Expr),
     NotANumber(Expr),
     NotASymbol(Expr),
     NotAString(Expr),
     NotAnIdentifier(Expr),
     NotAQExpr(Expr),
+    NotAReference(Expr),
+    NotAVector(Expr),
+    NotATime(Expr),
+    InvalidDate(String),
+    DateOutOfRange,
+    IndexOutOfRange(i32, usize),
+    InvalidSlice(usize, usize),
+    NotComparable(Box<Expr>, Box<Expr>),
     NotASExpr(Expr),
     UnboundSymbol(Symbol),
-    UnEqualDefList(Expr, Vec<Box<Expr>>),
+    UnEqualDefList(Box<Expr>, Vec<Box<Expr>>),
+    InvalidBinding(Expr),
+    InvalidPattern(Expr),
+    InvalidMatchClause(Expr),
+    NoMatch(Expr),
     ThrowError(String),
+    AssertionFailed(Expr),
+    AssertEqualFailed(Box<Expr>, Box<Expr>),
+    AssertThrowsFailed(Expr),
+    Exit(i32),
+    InvalidExitCode(i32),
+    Break(Expr),
+    Continue(Expr),
+    ZeroStep,
+    Interrupted,
+    StepLimitExceeded(usize),
+    DepthLimitExceeded(usize),
+    MemoryLimitExceeded(usize),
+    Timeout(Duration),
+    NotPermitted(Symbol),
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Exit(code) => *code,
+            Error::ParseError(_) => 65,
+            Error::ImportError(_) => 66,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = |exprs: &Vec<Box<Expr>>| {
+            exprs
+                .iter()
+                .map(|e| format!("{}", e))
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+
+        match self {
+            Error::DivideByZero(x, y) => write!(f, "शुन्यले भाग गर्न मिल्दैन : (/ {} {})", x, y),
+            Error::InvalidNumberOfNumArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::InvalidNumberOfExprsArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::InvalidNumberOfBoolArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::InvalidNumberOfLoopArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::InvalidNumberOfRefArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::InvalidNumberOfVectorArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::InvalidNumberOfTimeArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::InvalidNumberOfListArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::InvalidNumberOfQExprArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::InvalidNumberOfQExprsArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::InvalidNumberOfSExprArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::InvalidNumberOfTestArguments(op, n) => {
+                write!(f, "{} लाई दिइएको तर्कहरुको संख्या ({}) मिलेन", op, Sankhya(*n as i32))
+            }
+            Error::EmptyQExpr(e) => write!(f, "खाली क्यु-एक्सपर् : {}", e),
+            Error::ParseError(msg) => write!(f, "पार्स गर्न सकिएन :\n{}", msg),
+            Error::ImportError(e) => write!(f, "आयात गर्न सकिएन : {}", e),
+            Error::InvalidOp(e) => write!(f, "अमान्य अपरेसन : {}", e),
+            Error::NotABoolean(e) => write!(f, "बुलियन होइन : {}", e),
+            Error::NotAFunction(e) => write!(f, "फलन होइन : {}", e),
+            Error::NotANumber(e) => write!(f, "संख्या होइन : {}", e),
+            Error::NotASymbol(e) => write!(f, "सिम्बल होइन : {}", e),
+            Error::NotAString(e) => write!(f, "स्ट्रिङ होइन : {}", e),
+            Error::NotAnIdentifier(e) => write!(f, "आइडेन्टिफायर होइन : {}", e),
+            Error::NotAQExpr(e) => write!(f, "क्यु-एक्सपर् होइन : {}", e),
+            Error::NotAReference(e) => write!(f, "सन्दर्भ होइन : {}", e),
+            Error::NotAVector(e) => write!(f, "भेक्टर होइन : {}", e),
+            Error::NotATime(e) => write!(f, "समय होइन : {}", e),
+            Error::InvalidDate(date) => write!(f, "मिति मिलेन : {}", date),
+            Error::DateOutOfRange => write!(f, "मिति पात्रोको दायरा (२००० देखि २०९० सम्म) बाहिर छ"),
+            Error::NotComparable(a, b) => write!(f, "तुलना गर्न मिल्दैन : {} र {}", a, b),
+            Error::IndexOutOfRange(idx, len) => write!(
+                f,
+                "{} औँ बुँदा छैन, जम्मा {} वटा बुँदा छन्",
+                Sankhya(*idx),
+                Sankhya(*len as i32)
+            ),
+            Error::InvalidSlice(start, end) => write!(
+                f,
+                "टुक्राको सुरु ({}) अन्त्य ({}) भन्दा पछि छ",
+                Sankhya(*start as i32),
+                Sankhya(*end as i32)
+            ),
+            Error::NotASExpr(e) => write!(f, "एस्-एक्सपर् होइन : {}", e),
+            Error::UnboundSymbol(s) => write!(f, "नाम भेटिएन : {}", Expr::Sym(s.clone())),
+            Error::UnEqualDefList(names, values) => write!(
+                f,
+                "नाम र मानहरुको संख्या मिलेन : {} ({})",
+                names,
+                list(values)
+            ),
+            Error::InvalidBinding(e) => write!(f, "नाम र मानको जोडी होइन : {}", e),
+            Error::InvalidPattern(e) => write!(f, "ढाँचा मिलेन : {}", e),
+            Error::InvalidMatchClause(e) => {
+                write!(f, "'(ढाँचा नतिजा) वा '(ढाँचा शर्त नतिजा) होइन : {}", e)
+            }
+            Error::NoMatch(e) => write!(f, "कुनै ढाँचासँग मिलेन : {}", e),
+            Error::ThrowError(msg) => write!(f, "{}", msg),
+            Error::AssertionFailed(e) => write!(f, "दाबी असफल : {}", e),
+            Error::AssertEqualFailed(expected, actual) => write!(
+                f,
+                "दाबी असफल : अपेक्षित {}, वास्तविक {}",
+                expected, actual
+            ),
+            Error::AssertThrowsFailed(e) => write!(f, "समस्या आउनुपर्नेमा मान आयो : {}", e),
+            Error::Exit(code) => write!(f, "निकास {}", Sankhya(*code)),
+            Error::InvalidExitCode(code) => {
+                write!(f, "निकास कोड ० देखि २५५ सम्म हुनुपर्छ : {}", Sankhya(*code))
+            }
+            Error::Break(e) => write!(f, "लुपबाहिर रोक : {}", e),
+            Error::Continue(e) => write!(f, "लुपबाहिर जारी : {}", e),
+            Error::ZeroStep => write!(f, "पाइला शुन्य हुन मिल्दैन"),
+            Error::Interrupted => write!(f, "मूल्याङ्कन बीचमै रोकियो"),
+            Error::StepLimitExceeded(max) => {
+                write!(f, "मूल्याङ्कनका पाइलाहरुको सीमा ({}) नाघ्यो", Sankhya(*max as i32))
+            }
+            Error::DepthLimitExceeded(max) => {
+                write!(f, "फलनहरुको गहिराइको सीमा ({}) नाघ्यो", Sankhya(*max as i32))
+            }
+            Error::MemoryLimitExceeded(max) => {
+                write!(f, "मेमोरीको सीमा ({} बाइट) नाघ्यो", Sankhya(*max as i32))
+            }
+            Error::Timeout(timeout) => write!(f, "समय सकियो ({:?})", timeout),
+            Error::NotPermitted(sym) => {
+                write!(f, "यो लिपिलाई अनुमति छैन : {}", Expr::Sym(sym.clone()))
+            }
+        }
+    }
 }